@@ -0,0 +1,34 @@
+package uk.co.palmr.classfileparser;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+
+@Annotations.Marker(name = "class", values = {1, 2, 3}, policy = RetentionPolicy.RUNTIME, type = String.class, nested = @Annotations.Nested)
+public class Annotations {
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Marker {
+        String name() default "unnamed";
+        int[] values() default {};
+        RetentionPolicy policy() default RetentionPolicy.CLASS;
+        Class<?> type() default Object.class;
+        Nested nested() default @Nested;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Nested {
+        long value() default 42L;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target(ElementType.TYPE_USE)
+    public @interface TypeUse {
+    }
+
+    @Marker
+    public @TypeUse String field;
+
+    public void method(@Nested(7) int first, int second) {
+    }
+}
@@ -3,11 +3,20 @@ mod types;
 
 pub use self::types::*;
 
+pub use self::parser::annotation_default_attribute_parser;
+pub use self::parser::annotation_parser;
 pub use self::parser::attribute_parser;
 pub use self::parser::bootstrap_methods_attribute_parser;
 pub use self::parser::code_attribute_parser;
 pub use self::parser::constant_value_attribute_parser;
+pub use self::parser::element_value_parser;
+pub use self::parser::enclosing_method_attribute_parser;
 pub use self::parser::exceptions_attribute_parser;
+pub use self::parser::inner_classes_attribute_parser;
 pub use self::parser::method_parameters_attribute_parser;
+pub use self::parser::runtime_annotations_attribute_parser;
+pub use self::parser::runtime_parameter_annotations_attribute_parser;
+pub use self::parser::runtime_type_annotations_attribute_parser;
+pub use self::parser::signature_attribute_parser;
 pub use self::parser::sourcefile_attribute_parser;
 pub use self::parser::stack_map_table_attribute_parser;
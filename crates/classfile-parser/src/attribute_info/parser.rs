@@ -284,3 +284,295 @@ pub fn method_parameters_attribute_parser(input: &[u8]) -> Result<(&[u8], Method
             })
     )
 }
+
+fn inner_class_parser(input: &[u8]) -> Result<(&[u8], InnerClass), Err<&[u8]>> {
+    do_parse!(
+        input,
+        inner_class_info_index: be_u16
+            >> outer_class_info_index: be_u16
+            >> inner_name_index: be_u16
+            >> inner_class_access_flags: be_u16
+            >> (InnerClass {
+                inner_class_info_index,
+                outer_class_info_index,
+                inner_name_index,
+                inner_class_access_flags,
+            })
+    )
+}
+
+pub fn inner_classes_attribute_parser(input: &[u8]) -> Result<(&[u8], InnerClassesAttribute), Err<&[u8]>> {
+    do_parse!(
+        input,
+        number_of_classes: be_u16
+            >> classes: count!(inner_class_parser, number_of_classes as usize)
+            >> (InnerClassesAttribute {
+                number_of_classes,
+                classes,
+            })
+    )
+}
+
+pub fn enclosing_method_attribute_parser(input: &[u8]) -> Result<(&[u8], EnclosingMethodAttribute), Err<&[u8]>> {
+    do_parse!(
+        input,
+        class_index: be_u16
+            >> method_index: be_u16
+            >> (EnclosingMethodAttribute {
+                class_index,
+                method_index,
+            })
+    )
+}
+
+pub fn signature_attribute_parser(input: &[u8]) -> Result<(&[u8], SignatureAttribute), Err<&[u8]>> {
+    do_parse!(input, signature_index: be_u16 >> (SignatureAttribute { signature_index }))
+}
+
+fn element_value_body_parser(input: &[u8], tag: u8) -> Result<(&[u8], ElementValue), Err<&[u8]>> {
+    match tag {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => do_parse!(
+            input,
+            const_value_index: be_u16
+                >> (ElementValue::ConstValueIndex {
+                    tag: tag as char,
+                    const_value_index,
+                })
+        ),
+        b'e' => do_parse!(
+            input,
+            type_name_index: be_u16
+                >> const_name_index: be_u16
+                >> (ElementValue::EnumConstValue {
+                    type_name_index,
+                    const_name_index,
+                })
+        ),
+        b'c' => do_parse!(input, class_info_index: be_u16 >> (ElementValue::ClassInfoIndex(class_info_index))),
+        b'@' => do_parse!(input, annotation: annotation_parser >> (ElementValue::AnnotationValue(annotation))),
+        b'[' => do_parse!(
+            input,
+            num_values: be_u16
+                >> values: count!(element_value_parser, num_values as usize)
+                >> (ElementValue::ArrayValue {
+                    num_values,
+                    values,
+                })
+        ),
+        _ => Result::Err(Err::Error(error_position!(input, ErrorKind::Custom(3)))),
+    }
+}
+
+pub fn element_value_parser(input: &[u8]) -> Result<(&[u8], ElementValue), Err<&[u8]>> {
+    do_parse!(input, tag: be_u8 >> value: apply!(element_value_body_parser, tag) >> (value))
+}
+
+fn element_value_pair_parser(input: &[u8]) -> Result<(&[u8], ElementValuePair), Err<&[u8]>> {
+    do_parse!(
+        input,
+        element_name_index: be_u16
+            >> value: element_value_parser
+            >> (ElementValuePair {
+                element_name_index,
+                value,
+            })
+    )
+}
+
+pub fn annotation_parser(input: &[u8]) -> Result<(&[u8], Annotation), Err<&[u8]>> {
+    do_parse!(
+        input,
+        type_index: be_u16
+            >> num_element_value_pairs: be_u16
+            >> element_value_pairs: count!(element_value_pair_parser, num_element_value_pairs as usize)
+            >> (Annotation {
+                type_index,
+                num_element_value_pairs,
+                element_value_pairs,
+            })
+    )
+}
+
+/// Parses both the RuntimeVisibleAnnotations and the RuntimeInvisibleAnnotations attribute.
+pub fn runtime_annotations_attribute_parser(input: &[u8]) -> Result<(&[u8], RuntimeAnnotationsAttribute), Err<&[u8]>> {
+    do_parse!(
+        input,
+        num_annotations: be_u16
+            >> annotations: count!(annotation_parser, num_annotations as usize)
+            >> (RuntimeAnnotationsAttribute {
+                num_annotations,
+                annotations,
+            })
+    )
+}
+
+fn parameter_annotations_parser(input: &[u8]) -> Result<(&[u8], ParameterAnnotations), Err<&[u8]>> {
+    do_parse!(
+        input,
+        num_annotations: be_u16
+            >> annotations: count!(annotation_parser, num_annotations as usize)
+            >> (ParameterAnnotations {
+                num_annotations,
+                annotations,
+            })
+    )
+}
+
+/// Parses both the RuntimeVisibleParameterAnnotations and the RuntimeInvisibleParameterAnnotations attribute.
+pub fn runtime_parameter_annotations_attribute_parser(
+    input: &[u8],
+) -> Result<(&[u8], RuntimeParameterAnnotationsAttribute), Err<&[u8]>> {
+    do_parse!(
+        input,
+        num_parameters: be_u8
+            >> parameter_annotations: count!(parameter_annotations_parser, num_parameters as usize)
+            >> (RuntimeParameterAnnotationsAttribute {
+                num_parameters,
+                parameter_annotations,
+            })
+    )
+}
+
+pub fn annotation_default_attribute_parser(input: &[u8]) -> Result<(&[u8], AnnotationDefaultAttribute), Err<&[u8]>> {
+    do_parse!(input, default_value: element_value_parser >> (AnnotationDefaultAttribute { default_value }))
+}
+
+fn local_variable_target_parser(input: &[u8]) -> Result<(&[u8], LocalVariableTarget), Err<&[u8]>> {
+    do_parse!(
+        input,
+        start_pc: be_u16
+            >> length: be_u16
+            >> index: be_u16
+            >> (LocalVariableTarget {
+                start_pc,
+                length,
+                index,
+            })
+    )
+}
+
+fn target_info_parser(input: &[u8], target_type: u8) -> Result<(&[u8], TargetInfo), Err<&[u8]>> {
+    use self::TargetInfo::*;
+    match target_type {
+        0x00 | 0x01 => do_parse!(
+            input,
+            type_parameter_index: be_u8
+                >> (TypeParameter {
+                    type_parameter_index
+                })
+        ),
+        0x10 => do_parse!(
+            input,
+            supertype_index: be_u16
+                >> (Supertype {
+                    supertype_index
+                })
+        ),
+        0x11 | 0x12 => do_parse!(
+            input,
+            type_parameter_index: be_u8
+                >> bound_index: be_u8
+                >> (TypeParameterBound {
+                    type_parameter_index,
+                    bound_index,
+                })
+        ),
+        0x13..=0x15 => value!(input, Empty),
+        0x16 => do_parse!(
+            input,
+            formal_parameter_index: be_u8
+                >> (FormalParameter {
+                    formal_parameter_index
+                })
+        ),
+        0x17 => do_parse!(
+            input,
+            throws_type_index: be_u16
+                >> (Throws {
+                    throws_type_index
+                })
+        ),
+        0x40 | 0x41 => do_parse!(
+            input,
+            table_length: be_u16
+                >> table: count!(local_variable_target_parser, table_length as usize)
+                >> (LocalVariable {
+                    table_length,
+                    table,
+                })
+        ),
+        0x42 => do_parse!(
+            input,
+            exception_table_index: be_u16
+                >> (Catch {
+                    exception_table_index
+                })
+        ),
+        0x43..=0x46 => do_parse!(
+            input,
+            offset: be_u16
+                >> (Offset {
+                    offset
+                })
+        ),
+        0x47..=0x4B => do_parse!(
+            input,
+            offset: be_u16
+                >> type_argument_index: be_u8
+                >> (TypeArgument {
+                    offset,
+                    type_argument_index,
+                })
+        ),
+        _ => Result::Err(Err::Error(error_position!(input, ErrorKind::Custom(4)))),
+    }
+}
+
+fn type_path_entry_parser(input: &[u8]) -> Result<(&[u8], TypePathEntry), Err<&[u8]>> {
+    do_parse!(
+        input,
+        type_path_kind: be_u8
+            >> type_argument_index: be_u8
+            >> (TypePathEntry {
+                type_path_kind,
+                type_argument_index,
+            })
+    )
+}
+
+fn type_annotation_parser(input: &[u8]) -> Result<(&[u8], TypeAnnotation), Err<&[u8]>> {
+    do_parse!(
+        input,
+        target_type: be_u8
+            >> target_info: apply!(target_info_parser, target_type)
+            >> path_length: be_u8
+            >> target_path: count!(type_path_entry_parser, path_length as usize)
+            >> type_index: be_u16
+            >> num_element_value_pairs: be_u16
+            >> element_value_pairs: count!(element_value_pair_parser, num_element_value_pairs as usize)
+            >> (TypeAnnotation {
+                target_type,
+                target_info,
+                path_length,
+                target_path,
+                type_index,
+                num_element_value_pairs,
+                element_value_pairs,
+            })
+    )
+}
+
+/// Parses both the RuntimeVisibleTypeAnnotations and the RuntimeInvisibleTypeAnnotations attribute.
+pub fn runtime_type_annotations_attribute_parser(
+    input: &[u8],
+) -> Result<(&[u8], RuntimeTypeAnnotationsAttribute), Err<&[u8]>> {
+    do_parse!(
+        input,
+        num_annotations: be_u16
+            >> annotations: count!(type_annotation_parser, num_annotations as usize)
+            >> (RuntimeTypeAnnotationsAttribute {
+                num_annotations,
+                annotations,
+            })
+    )
+}
@@ -134,3 +134,147 @@ pub struct MethodParametersAttribute {
     pub parameters_count: u8,
     pub parameters: Vec<MethodParameter>,
 }
+
+#[derive(Clone, Debug)]
+pub struct InnerClass {
+    pub inner_class_info_index: u16,
+    pub outer_class_info_index: u16,
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct InnerClassesAttribute {
+    pub number_of_classes: u16,
+    pub classes: Vec<InnerClass>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnclosingMethodAttribute {
+    pub class_index: u16,
+    pub method_index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureAttribute {
+    pub signature_index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub enum ElementValue {
+    ConstValueIndex {
+        tag: char,
+        const_value_index: u16,
+    },
+    EnumConstValue {
+        type_name_index: u16,
+        const_name_index: u16,
+    },
+    ClassInfoIndex(u16),
+    AnnotationValue(Annotation),
+    ArrayValue {
+        num_values: u16,
+        values: Vec<ElementValue>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
+}
+
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub type_index: u16,
+    pub num_element_value_pairs: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RuntimeAnnotationsAttribute {
+    pub num_annotations: u16,
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ParameterAnnotations {
+    pub num_annotations: u16,
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RuntimeParameterAnnotationsAttribute {
+    pub num_parameters: u8,
+    pub parameter_annotations: Vec<ParameterAnnotations>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnnotationDefaultAttribute {
+    pub default_value: ElementValue,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalVariableTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub enum TargetInfo {
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    Supertype {
+        supertype_index: u16,
+    },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Empty,
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    Throws {
+        throws_type_index: u16,
+    },
+    LocalVariable {
+        table_length: u16,
+        table: Vec<LocalVariableTarget>,
+    },
+    Catch {
+        exception_table_index: u16,
+    },
+    Offset {
+        offset: u16,
+    },
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub path_length: u8,
+    pub target_path: Vec<TypePathEntry>,
+    pub type_index: u16,
+    pub num_element_value_pairs: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RuntimeTypeAnnotationsAttribute {
+    pub num_annotations: u16,
+    pub annotations: Vec<TypeAnnotation>,
+}
@@ -0,0 +1,157 @@
+extern crate classfile_parser;
+
+use classfile_parser::attribute_info::{
+    annotation_default_attribute_parser, runtime_annotations_attribute_parser,
+    runtime_parameter_annotations_attribute_parser, runtime_type_annotations_attribute_parser, AttributeInfo,
+    ElementValue,
+};
+use classfile_parser::class_parser;
+use classfile_parser::constant_info::ConstantInfo;
+use classfile_parser::ClassFile;
+
+fn get_utf8(class: &ClassFile, index: u16) -> &str {
+    match &class.const_pool[index as usize - 1] {
+        ConstantInfo::Utf8(utf8) => &utf8.utf8_string,
+        other => panic!("expected a utf8 constant, found {:?}", other),
+    }
+}
+
+fn find_attribute<'a>(class: &ClassFile, attributes: &'a [AttributeInfo], name: &str) -> &'a AttributeInfo {
+    attributes
+        .iter()
+        .find(|attribute| get_utf8(class, attribute.attribute_name_index) == name)
+        .unwrap_or_else(|| panic!("missing {} attribute", name))
+}
+
+#[test]
+fn test_class_annotations() {
+    let (_, c) = class_parser(include_bytes!("../java-assets/compiled-classes/Annotations.class")).unwrap();
+    let attribute = find_attribute(&c, &c.attributes, "RuntimeVisibleAnnotations");
+    let (rest, annotations) = runtime_annotations_attribute_parser(&attribute.info).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(annotations.num_annotations, 1);
+
+    let marker = &annotations.annotations[0];
+    assert_eq!(get_utf8(&c, marker.type_index), "Luk/co/palmr/classfileparser/Annotations$Marker;");
+    assert_eq!(marker.num_element_value_pairs, 5);
+
+    let names: Vec<&str> =
+        marker.element_value_pairs.iter().map(|pair| get_utf8(&c, pair.element_name_index)).collect();
+    assert_eq!(names, vec!["name", "values", "policy", "type", "nested"]);
+
+    match &marker.element_value_pairs[0].value {
+        ElementValue::ConstValueIndex {
+            tag: 's',
+            const_value_index,
+        } => assert_eq!(get_utf8(&c, *const_value_index), "class"),
+        other => panic!("unexpected name value {:?}", other),
+    }
+    match &marker.element_value_pairs[1].value {
+        ElementValue::ArrayValue {
+            num_values: 3,
+            values,
+        } => {
+            for (i, value) in values.iter().enumerate() {
+                match value {
+                    ElementValue::ConstValueIndex {
+                        tag: 'I',
+                        const_value_index,
+                    } => match &c.const_pool[*const_value_index as usize - 1] {
+                        ConstantInfo::Integer(integer) => assert_eq!(integer.value, i as i32 + 1),
+                        other => panic!("expected an integer constant, found {:?}", other),
+                    },
+                    other => panic!("unexpected array element {:?}", other),
+                }
+            }
+        }
+        other => panic!("unexpected values value {:?}", other),
+    }
+    match &marker.element_value_pairs[2].value {
+        ElementValue::EnumConstValue {
+            type_name_index,
+            const_name_index,
+        } => {
+            assert_eq!(get_utf8(&c, *type_name_index), "Ljava/lang/annotation/RetentionPolicy;");
+            assert_eq!(get_utf8(&c, *const_name_index), "RUNTIME");
+        }
+        other => panic!("unexpected policy value {:?}", other),
+    }
+    match &marker.element_value_pairs[3].value {
+        ElementValue::ClassInfoIndex(index) => assert_eq!(get_utf8(&c, *index), "Ljava/lang/String;"),
+        other => panic!("unexpected type value {:?}", other),
+    }
+    match &marker.element_value_pairs[4].value {
+        ElementValue::AnnotationValue(nested) => {
+            assert_eq!(get_utf8(&c, nested.type_index), "Luk/co/palmr/classfileparser/Annotations$Nested;");
+            assert_eq!(nested.num_element_value_pairs, 0);
+        }
+        other => panic!("unexpected nested value {:?}", other),
+    }
+}
+
+#[test]
+fn test_member_annotations() {
+    let (_, c) = class_parser(include_bytes!("../java-assets/compiled-classes/Annotations.class")).unwrap();
+
+    let field = c.fields.iter().find(|field| get_utf8(&c, field.name_index) == "field").unwrap();
+    let attribute = find_attribute(&c, &field.attributes, "RuntimeVisibleAnnotations");
+    let (_, annotations) = runtime_annotations_attribute_parser(&attribute.info).unwrap();
+    assert_eq!(annotations.num_annotations, 1);
+    assert_eq!(annotations.annotations[0].num_element_value_pairs, 0);
+
+    let attribute = find_attribute(&c, &field.attributes, "RuntimeVisibleTypeAnnotations");
+    let (rest, type_annotations) = runtime_type_annotations_attribute_parser(&attribute.info).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(type_annotations.num_annotations, 1);
+    assert_eq!(type_annotations.annotations[0].target_type, 0x13);
+    assert_eq!(type_annotations.annotations[0].path_length, 0);
+    assert_eq!(
+        get_utf8(&c, type_annotations.annotations[0].type_index),
+        "Luk/co/palmr/classfileparser/Annotations$TypeUse;"
+    );
+
+    let method = c.methods.iter().find(|method| get_utf8(&c, method.name_index) == "method").unwrap();
+    let attribute = find_attribute(&c, &method.attributes, "RuntimeVisibleParameterAnnotations");
+    let (rest, parameter_annotations) = runtime_parameter_annotations_attribute_parser(&attribute.info).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(parameter_annotations.num_parameters, 2);
+    assert_eq!(parameter_annotations.parameter_annotations[0].num_annotations, 1);
+    assert_eq!(parameter_annotations.parameter_annotations[1].num_annotations, 0);
+
+    let nested = &parameter_annotations.parameter_annotations[0].annotations[0];
+    match &nested.element_value_pairs[0].value {
+        ElementValue::ConstValueIndex {
+            tag: 'J',
+            const_value_index,
+        } => match &c.const_pool[*const_value_index as usize - 1] {
+            ConstantInfo::Long(long) => assert_eq!(long.value, 7),
+            other => panic!("expected a long constant, found {:?}", other),
+        },
+        other => panic!("unexpected nested value {:?}", other),
+    }
+}
+
+#[test]
+fn test_annotation_default() {
+    let (_, c) = class_parser(include_bytes!("../java-assets/compiled-classes/AnnotationsMarker.class")).unwrap();
+
+    let method = c.methods.iter().find(|method| get_utf8(&c, method.name_index) == "values").unwrap();
+    let attribute = find_attribute(&c, &method.attributes, "AnnotationDefault");
+    let (rest, default) = annotation_default_attribute_parser(&attribute.info).unwrap();
+    assert!(rest.is_empty());
+    match default.default_value {
+        ElementValue::ArrayValue {
+            num_values: 0,
+            ..
+        } => (),
+        other => panic!("unexpected default value {:?}", other),
+    }
+
+    let method = c.methods.iter().find(|method| get_utf8(&c, method.name_index) == "nested").unwrap();
+    let attribute = find_attribute(&c, &method.attributes, "AnnotationDefault");
+    let (_, default) = annotation_default_attribute_parser(&attribute.info).unwrap();
+    match default.default_value {
+        ElementValue::AnnotationValue(_) => (),
+        other => panic!("unexpected default value {:?}", other),
+    }
+}
@@ -0,0 +1,10 @@
+package webjvm.js;
+
+public class JSException extends RuntimeException {
+    public JSException() {
+    }
+
+    public JSException(String message) {
+        super(message);
+    }
+}
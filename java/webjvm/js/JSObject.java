@@ -0,0 +1,27 @@
+package webjvm.js;
+
+// a reference to a JavaScript object, the referenced object is kept alive for as long as
+// this instance is reachable from Java
+public final class JSObject {
+    private JSObject() {
+    }
+
+    public static native JSObject getWindow() throws JSException;
+
+    public native Object getMember(String name) throws JSException;
+
+    public native void setMember(String name, Object value) throws JSException;
+
+    public native void removeMember(String name) throws JSException;
+
+    public native Object getSlot(int index) throws JSException;
+
+    public native void setSlot(int index, Object value) throws JSException;
+
+    public native Object call(String methodName, Object... args) throws JSException;
+
+    public native Object eval(String script) throws JSException;
+
+    @Override
+    public native String toString();
+}
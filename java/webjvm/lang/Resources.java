@@ -0,0 +1,96 @@
+package webjvm.lang;
+
+import java.io.ByteArrayInputStream;
+import java.io.FileNotFoundException;
+import java.io.IOException;
+import java.io.InputStream;
+import java.net.MalformedURLException;
+import java.net.URL;
+import java.net.URLConnection;
+import java.net.URLStreamHandler;
+import java.util.Enumeration;
+import java.util.Vector;
+
+// resources are addressed as webjvm:/<index>/<name>, where index selects between
+// identically named entries contributed by different jars, in classpath order
+public final class Resources extends URLStreamHandler {
+    private static final Resources HANDLER = new Resources();
+
+    private Resources() {
+    }
+
+    public static URL getResource(String name) {
+        Enumeration<URL> resources = getResources(name);
+        return resources.hasMoreElements() ? resources.nextElement() : null;
+    }
+
+    public static Enumeration<URL> getResources(String name) {
+        int count = getResourceCount(name);
+        Vector<URL> urls = new Vector<>(count);
+        for (int i = 0; i < count; i++) {
+            try {
+                urls.add(new URL("webjvm", null, -1, "/" + i + "/" + name, HANDLER));
+            } catch (MalformedURLException e) {
+                throw new InternalError(e);
+            }
+        }
+        return urls.elements();
+    }
+
+    @Override
+    protected URLConnection openConnection(URL url) {
+        return new ResourceConnection(url);
+    }
+
+    private static native int getResourceCount(String name);
+
+    private static native byte[] getResourceBytes(String name, int index);
+
+    private static final class ResourceConnection extends URLConnection {
+        private byte[] bytes;
+
+        ResourceConnection(URL url) {
+            super(url);
+        }
+
+        @Override
+        public void connect() throws IOException {
+            if (connected) {
+                return;
+            }
+
+            String path = url.getPath();
+            int separator = path.indexOf('/', 1);
+            if (separator < 0) {
+                throw new FileNotFoundException(url.toString());
+            }
+            int index;
+            try {
+                index = Integer.parseInt(path.substring(1, separator));
+            } catch (NumberFormatException e) {
+                throw new FileNotFoundException(url.toString());
+            }
+            bytes = getResourceBytes(path.substring(separator + 1), index);
+            if (bytes == null) {
+                throw new FileNotFoundException(url.toString());
+            }
+            connected = true;
+        }
+
+        @Override
+        public InputStream getInputStream() throws IOException {
+            connect();
+            return new ByteArrayInputStream(bytes);
+        }
+
+        @Override
+        public int getContentLength() {
+            try {
+                connect();
+            } catch (IOException e) {
+                return -1;
+            }
+            return bytes.length;
+        }
+    }
+}
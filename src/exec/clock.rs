@@ -0,0 +1,135 @@
+use std::cell::Cell;
+
+// the source of time for System.currentTimeMillis, System.nanoTime and the deadlines of sleeps, timed waits
+// and parks, deadlines are in the milliseconds of current_time_millis
+pub trait Clock {
+    fn current_time_millis(&self) -> i64;
+
+    // monotonic nanoseconds since an arbitrary origin
+    fn nano_time(&self) -> i64;
+
+    // called when every thread is waiting for a deadline and execution cannot be handed back to the host
+    fn wait_until(&self, deadline: i64);
+
+    // real time clocks block while waiting, so deadlines are waited for by the host whenever possible
+    fn is_real_time(&self) -> bool;
+}
+
+pub struct RealClock {
+    #[cfg(not(target_arch = "wasm32"))]
+    origin: std::time::Instant,
+}
+
+impl RealClock {
+    pub fn new() -> RealClock {
+        RealClock {
+            #[cfg(not(target_arch = "wasm32"))]
+            origin: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        RealClock::new()
+    }
+}
+
+impl Clock for RealClock {
+    fn current_time_millis(&self) -> i64 {
+        crate::util::current_time_millis()
+    }
+
+    fn nano_time(&self) -> i64 {
+        #[cfg(target_arch = "wasm32")]
+        return (crate::js::performance_now() * 1_000_000.0) as i64;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.origin.elapsed().as_nanos() as i64;
+    }
+
+    fn wait_until(&self, deadline: i64) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let remaining = deadline - self.current_time_millis();
+            if remaining > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(remaining as u64));
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        while self.current_time_millis() < deadline {}
+    }
+
+    fn is_real_time(&self) -> bool {
+        true
+    }
+}
+
+// time only moves when it is advanced or when every thread waits, in which case it jumps to the next
+// deadline, so programs that sleep or time out run instantly and reproducibly
+pub struct VirtualClock {
+    epoch_millis: i64,
+    nanos: Cell<i64>,
+}
+
+impl VirtualClock {
+    pub fn new(epoch_millis: i64) -> VirtualClock {
+        VirtualClock {
+            epoch_millis,
+            nanos: Cell::new(0),
+        }
+    }
+
+    pub fn advance(&self, nanos: i64) {
+        self.nanos.set(self.nanos.get().saturating_add(nanos.max(0)));
+    }
+}
+
+impl Clock for VirtualClock {
+    fn current_time_millis(&self) -> i64 {
+        self.epoch_millis + self.nanos.get() / 1_000_000
+    }
+
+    fn nano_time(&self) -> i64 {
+        self.nanos.get()
+    }
+
+    fn wait_until(&self, deadline: i64) {
+        let target = (deadline - self.epoch_millis).saturating_mul(1_000_000);
+        if target > self.nanos.get() {
+            self.nanos.set(target);
+        }
+    }
+
+    fn is_real_time(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, VirtualClock};
+
+    #[test]
+    fn virtual_clock_only_moves_forward() {
+        let clock = VirtualClock::new(1_000);
+        assert_eq!(clock.current_time_millis(), 1_000);
+        assert_eq!(clock.nano_time(), 0);
+        assert!(!clock.is_real_time());
+
+        clock.advance(1_500_000);
+        assert_eq!(clock.nano_time(), 1_500_000);
+        assert_eq!(clock.current_time_millis(), 1_001);
+        clock.advance(-1);
+        assert_eq!(clock.nano_time(), 1_500_000);
+
+        clock.wait_until(1_250);
+        assert_eq!(clock.current_time_millis(), 1_250);
+        assert_eq!(clock.nano_time(), 250_000_000);
+        clock.wait_until(1_100);
+        assert_eq!(clock.current_time_millis(), 1_250);
+
+        clock.advance(i64::MAX);
+        assert_eq!(clock.nano_time(), i64::MAX);
+    }
+}
@@ -1,19 +1,50 @@
-use super::jvm::Jvm;
+use super::{jvm::Jvm, thread::MAIN_THREAD};
 use crate::{
     model::{
-        CallStackFrame, CallStackFrameState, InternalMetadata, JavaArrayType, JavaClass, JavaThrowable, JavaValue,
-        JavaValueVec, RuntimeResult,
+        InternalMetadata, JavaArray, JavaArrayType, JavaClass, JavaObject, JavaThrowable, JavaValue, JavaValueVec,
+        RuntimeResult, VmError,
     },
     InvokeType, StackTraceElement,
 };
-use classfile_parser::{
-    method_info::{MethodAccessFlags, MethodInfo},
-    ClassFile,
-};
+use classfile_parser::{method_info::MethodInfo, ClassFile};
+use std::convert::TryFrom;
+
+// bulk copies between Rust slices and primitive arrays, like JNI's Get/Set<Type>ArrayRegion
+macro_rules! define_array_region {
+    ( $get:ident, $set:ident, $t:ty, $array_type:ident, $read:ident ) => {
+        pub fn $get(&self, array_id: usize, start: usize, buf: &mut [$t]) -> RuntimeResult<()> {
+            let is_valid =
+                self.with_array_region(array_id, start, buf.len(), JavaArrayType::$array_type, |values| {
+                    for (dst, value) in buf.iter_mut().zip(values.iter()) {
+                        // sub-int values are stored as ints by the array store instructions
+                        match value.$read().ok().and_then(|x| <$t>::try_from(x).ok()) {
+                            Some(value) => *dst = value,
+                            None => return false,
+                        }
+                    }
+                    true
+                })?;
+            if !is_valid {
+                return Err(self.jvm.raise(VmError::Internal(format!("invalid array contents {}", array_id))));
+            }
+            Ok(())
+        }
+
+        pub fn $set(&self, array_id: usize, start: usize, buf: &[$t]) -> RuntimeResult<()> {
+            self.with_array_region(array_id, start, buf.len(), JavaArrayType::$array_type, |values| {
+                for (dst, value) in values.iter_mut().zip(buf.iter()) {
+                    *dst = JavaValue::$array_type(*value);
+                }
+            })
+        }
+    };
+}
 
 pub struct JniEnv<'a> {
     pub jvm: &'a Jvm,
     pub container_class: String,
+    pub container_method: String,
+    pub is_static: bool,
     pub parameters: JavaValueVec,
     pub stack_trace: Vec<StackTraceElement>,
 }
@@ -23,16 +54,18 @@ impl<'a> JniEnv<'a> {
         JniEnv {
             jvm,
             container_class: String::new(),
+            container_method: String::new(),
+            is_static: true,
             parameters: JavaValueVec::new(),
             stack_trace: Vec::new(),
         }
     }
 
-    pub fn new_string(&self, str: &str) -> usize {
+    pub fn new_string(&self, str: &str) -> RuntimeResult<usize> {
         self.jvm.create_string_object(str, false)
     }
 
-    pub fn new_interned_string(&self, str: &str) -> usize {
+    pub fn new_interned_string(&self, str: &str) -> RuntimeResult<usize> {
         self.jvm.create_string_object(str, true)
     }
 
@@ -40,27 +73,90 @@ impl<'a> JniEnv<'a> {
         self.jvm.create_empty_array(array_type, length)
     }
 
-    pub fn get_array_length(&self, array_id: usize) -> usize {
-        let heap = self.jvm.heap.borrow();
-        let array = heap.array_heap_map.get(&array_id).expect("invalid array ref");
-        array.values.len()
+    fn with_array<T>(&self, array_id: usize, f: impl FnOnce(&mut JavaArray) -> T) -> RuntimeResult<T> {
+        let mut heap = self.jvm.heap.borrow_mut();
+        match heap.array_heap_map.get_mut(&array_id) {
+            Some(array) => Ok(f(array)),
+            None => {
+                drop(heap);
+                Err(self.jvm.invalid_reference(array_id))
+            }
+        }
     }
 
-    pub fn get_array_element(&self, array_id: usize, index: usize) -> JavaValue {
-        let heap = self.jvm.heap.borrow();
-        let array = heap.array_heap_map.get(&array_id).expect("invalid array ref");
-        array.values[index].clone()
+    fn with_object<T>(&self, instance_id: usize, f: impl FnOnce(&mut JavaObject) -> T) -> RuntimeResult<T> {
+        let mut heap = self.jvm.heap.borrow_mut();
+        match heap.object_heap_map.get_mut(&instance_id) {
+            Some(obj) => Ok(f(obj)),
+            None => {
+                drop(heap);
+                Err(self.jvm.invalid_reference(instance_id))
+            }
+        }
     }
 
-    pub fn set_array_element(&self, array_id: usize, index: usize, value: JavaValue) {
-        let mut heap = self.jvm.heap.borrow_mut();
-        let array = heap.array_heap_map.get_mut(&array_id).expect("invalid array ref");
-        array.values[index] = value;
+    fn with_array_region<T>(
+        &self,
+        array_id: usize,
+        start: usize,
+        length: usize,
+        array_type: JavaArrayType,
+        f: impl FnOnce(&mut [JavaValue]) -> T,
+    ) -> RuntimeResult<T> {
+        let region = self.with_array(array_id, |array| {
+            if std::mem::discriminant(&array.array_type) != std::mem::discriminant(&array_type) {
+                return Err("java/lang/ArrayStoreException");
+            }
+            match start.checked_add(length).and_then(|end| array.values.get_mut(start..end)) {
+                Some(values) => Ok(f(values)),
+                None => Err("java/lang/ArrayIndexOutOfBoundsException"),
+            }
+        })?;
+        region.map_err(|exception_class| {
+            self.throw_exception(
+                exception_class,
+                Some(&format!("region {}+{} of {:?} array", start, length, array_type)),
+            )
+        })
     }
 
-    pub fn get_main_thread(&self) -> usize {
-        let heap = self.jvm.heap.borrow();
-        heap.main_thread_object
+    define_array_region!(get_byte_array_region, set_byte_array_region, i8, Byte, as_int);
+    define_array_region!(get_short_array_region, set_short_array_region, i16, Short, as_int);
+    define_array_region!(get_char_array_region, set_char_array_region, u16, Char, as_int);
+    define_array_region!(get_int_array_region, set_int_array_region, i32, Int, as_int);
+    define_array_region!(get_boolean_array_region, set_boolean_array_region, bool, Boolean, as_boolean);
+    define_array_region!(get_long_array_region, set_long_array_region, i64, Long, as_long);
+    define_array_region!(get_float_array_region, set_float_array_region, f32, Float, as_float);
+    define_array_region!(get_double_array_region, set_double_array_region, f64, Double, as_double);
+
+    pub fn get_array_length(&self, array_id: usize) -> RuntimeResult<usize> {
+        self.with_array(array_id, |array| array.values.len())
+    }
+
+    pub fn get_array_element(&self, array_id: usize, index: usize) -> RuntimeResult<JavaValue> {
+        match self.with_array(array_id, |array| array.values.get(index).cloned())? {
+            Some(value) => Ok(value),
+            None => Err(self.throw_exception("java/lang/ArrayIndexOutOfBoundsException", Some(&index.to_string()))),
+        }
+    }
+
+    pub fn set_array_element(&self, array_id: usize, index: usize, value: JavaValue) -> RuntimeResult<()> {
+        let is_in_bounds = self.with_array(array_id, |array| match array.values.get_mut(index) {
+            Some(element) => {
+                *element = value;
+                true
+            }
+            None => false,
+        })?;
+        if !is_in_bounds {
+            return Err(self.throw_exception("java/lang/ArrayIndexOutOfBoundsException", Some(&index.to_string())));
+        }
+        Ok(())
+    }
+
+    pub fn get_current_thread(&self) -> usize {
+        let threads = self.jvm.threads.borrow();
+        threads.get_current_thread().thread_object
     }
 
     pub fn load_class(&self, class: &str, initialize: bool) -> RuntimeResult<usize> {
@@ -83,32 +179,30 @@ impl<'a> JniEnv<'a> {
         class.class_object_id
     }
 
-    pub fn get_object_type_name(&self, instance_id: usize) -> String {
-        let heap = self.jvm.heap.borrow();
-        let obj = &heap.object_heap_map.get(&instance_id).expect("invalid object ref");
-        let class = &heap.loaded_classes[obj.class_id];
-        class.java_type.clone()
+    pub fn get_object_type_name(&self, instance_id: usize) -> RuntimeResult<String> {
+        let class_id = self.with_object(instance_id, |obj| obj.class_id)?;
+        Ok(self.jvm.get_class_name_from_id(class_id))
     }
 
-    pub fn get_string(&self, str_id: usize) -> String {
-        let heap = self.jvm.heap.borrow();
-        let obj = heap.object_heap_map.get(&str_id).expect("invalid object ref");
-        if obj.class_id != self.get_class_id("java/lang/String").unwrap() {
-            panic!("invalid string ref: {:?}", obj);
+    pub fn get_string(&self, str_id: usize) -> RuntimeResult<String> {
+        let string_class = self.get_class_id("java/lang/String")?;
+        let (class_id, value_array) =
+            self.with_object(str_id, |obj| (obj.class_id, obj.instance_fields.get("value").cloned()))?;
+        if class_id != string_class {
+            return Err(self.jvm.raise(VmError::Internal(format!("invalid string reference {}", str_id))));
         }
 
-        let value_array = obj.get_field(self.jvm, "value").unwrap();
         match value_array {
-            JavaValue::Array(ptr) => {
-                let heap = self.jvm.heap.borrow();
-                let array = &heap.array_heap_map[ptr];
-
-                String::from_utf16(
-                    &array.values.iter().map(|x| x.as_int().expect("invalid array item") as u16).collect::<Vec<u16>>(),
-                )
-                .expect("invalid string encoding")
+            Some(JavaValue::Array(ptr)) => {
+                let chars = self.with_array(ptr, |array| {
+                    array.values.iter().map(|x| x.as_int().map(|ch| ch as u16)).collect::<Result<Vec<u16>, ()>>()
+                })?;
+                match chars {
+                    Ok(chars) => Ok(String::from_utf16_lossy(&chars)),
+                    Err(_) => Err(self.jvm.raise(VmError::Internal(format!("invalid string contents {}", str_id)))),
+                }
             }
-            _ => panic!("invalid string value"),
+            _ => Err(self.jvm.raise(VmError::Internal(format!("invalid string value {}", str_id)))),
         }
     }
 
@@ -117,38 +211,73 @@ impl<'a> JniEnv<'a> {
         Ok(self.jvm.heap_store_instance(obj))
     }
 
-    pub fn set_static_field(&self, class_name: &str, field_name: &str, value: JavaValue) {
-        JavaClass::set_static_field(self.jvm, class_name, field_name, value).unwrap();
+    pub fn set_static_field(&self, class_name: &str, field_name: &str, value: JavaValue) -> RuntimeResult<()> {
+        JavaClass::set_static_field(self.jvm, class_name, field_name, value)
     }
 
-    pub fn set_field(&self, instance_id: usize, field_name: &str, value: JavaValue) {
-        let mut heap = self.jvm.heap.borrow_mut();
-        let obj = heap.object_heap_map.get_mut(&instance_id).expect("invalid instance ID");
-        obj.set_field(self.jvm, field_name, value).unwrap();
+    pub fn set_field(&self, instance_id: usize, field_name: &str, value: JavaValue) -> RuntimeResult<()> {
+        let has_field = self.with_object(instance_id, |obj| match obj.instance_fields.get_mut(field_name) {
+            Some(field) => {
+                *field = value;
+                true
+            }
+            None => false,
+        })?;
+        if !has_field {
+            return Err(self.throw_exception("java/lang/NoSuchFieldError", Some(field_name)));
+        }
+        Ok(())
     }
 
-    pub fn get_field(&self, instance_id: usize, field_name: &str) -> JavaValue {
-        let heap = self.jvm.heap.borrow();
-        let obj = heap.object_heap_map.get(&instance_id).expect("invalid instance ID");
-        obj.get_field(self.jvm, field_name).unwrap().clone()
+    pub fn get_field(&self, instance_id: usize, field_name: &str) -> RuntimeResult<JavaValue> {
+        match self.with_object(instance_id, |obj| obj.instance_fields.get(field_name).cloned())? {
+            Some(value) => Ok(value),
+            None => Err(self.throw_exception("java/lang/NoSuchFieldError", Some(field_name))),
+        }
     }
 
-    pub fn set_internal_metadata(&self, instance_id: usize, field_name: &str, value: InternalMetadata) {
-        let mut heap = self.jvm.heap.borrow_mut();
-        let obj = heap.object_heap_map.get_mut(&instance_id).expect("invalid instance ID");
-        obj.set_internal_metadata(field_name, value);
+    pub fn set_internal_metadata(
+        &self,
+        instance_id: usize,
+        field_name: &str,
+        value: InternalMetadata,
+    ) -> RuntimeResult<()> {
+        self.with_object(instance_id, |obj| obj.set_internal_metadata(field_name, value))
     }
 
-    pub fn remove_internal_metadata(&self, instance_id: usize, field_name: &str) -> Option<InternalMetadata> {
-        let mut heap = self.jvm.heap.borrow_mut();
-        let obj = heap.object_heap_map.get_mut(&instance_id).expect("invalid instance ID");
-        obj.remove_internal_metadata(field_name)
+    pub fn remove_internal_metadata(
+        &self,
+        instance_id: usize,
+        field_name: &str,
+    ) -> RuntimeResult<Option<InternalMetadata>> {
+        self.with_object(instance_id, |obj| obj.remove_internal_metadata(field_name))
     }
 
-    pub fn get_internal_metadata(&self, instance_id: usize, field_name: &str) -> Option<InternalMetadata> {
-        let heap = self.jvm.heap.borrow();
-        let obj = heap.object_heap_map.get(&instance_id).expect("invalid instance ID");
-        obj.get_internal_metadata(field_name).cloned()
+    pub fn get_internal_metadata(
+        &self,
+        instance_id: usize,
+        field_name: &str,
+    ) -> RuntimeResult<Option<InternalMetadata>> {
+        self.with_object(instance_id, |obj| obj.get_internal_metadata(field_name).cloned())
+    }
+
+    // metadata the VM attaches to its own objects, like the class of a java.lang.Class instance
+    pub fn get_text_metadata(&self, instance_id: usize, field_name: &str) -> RuntimeResult<String> {
+        match self.get_internal_metadata(instance_id, field_name)? {
+            Some(metadata) => metadata.into_string().map_err(|err| self.jvm.raise(err)),
+            None => Err(self.missing_metadata(instance_id, field_name)),
+        }
+    }
+
+    pub fn get_numeric_metadata(&self, instance_id: usize, field_name: &str) -> RuntimeResult<usize> {
+        match self.get_internal_metadata(instance_id, field_name)? {
+            Some(metadata) => metadata.into_usize().map_err(|err| self.jvm.raise(err)),
+            None => Err(self.missing_metadata(instance_id, field_name)),
+        }
+    }
+
+    fn missing_metadata(&self, instance_id: usize, field_name: &str) -> JavaThrowable {
+        self.jvm.raise(VmError::Internal(format!("object {} has no {} metadata", instance_id, field_name)))
     }
 
     fn invoke_method(
@@ -157,17 +286,21 @@ impl<'a> JniEnv<'a> {
         method: &MethodInfo,
         params: JavaValueVec,
     ) -> RuntimeResult<Option<JavaValue>> {
-        let mut frame = self.jvm.create_stack_frame(method_class, method).unwrap();
+        let mut frame = self.jvm.create_stack_frame(method_class, method)?;
         let mut index = 0;
         for i in 0..params.len() {
-            frame.state.lvt[index] = params[i].clone();
-            index += 1;
-            if let JavaValue::Internal {
-                is_higher_bits,
-                ..
-            } = params[i]
-            {
-                if is_higher_bits {
+            match params[i] {
+                // the second slot of a long or double is already skipped below
+                JavaValue::Internal {
+                    is_higher_bits: true,
+                    ..
+                } => continue,
+                JavaValue::Long(_) | JavaValue::Double(_) => {
+                    frame.state.lvt[index] = params[i].clone();
+                    index += 2;
+                }
+                _ => {
+                    frame.state.lvt[index] = params[i].clone();
                     index += 1;
                 }
             }
@@ -195,7 +328,7 @@ impl<'a> JniEnv<'a> {
         method_descriptor: &str,
         params: &[JavaValue],
     ) -> RuntimeResult<Option<JavaValue>> {
-        let class = self.get_class_file(class_id);
+        let class = self.get_class_file(class_id)?;
         let (method_class, method) =
             match self.jvm.classpath.get_method(InvokeType::Static, class, method_name, method_descriptor) {
                 Some(method) => method,
@@ -220,15 +353,13 @@ impl<'a> JniEnv<'a> {
         params: &[JavaValue],
     ) -> RuntimeResult<Option<JavaValue>> {
         let class_id = match invoke_type {
-            InvokeType::Virtual => {
-                let heap = self.jvm.heap.borrow();
-                let obj = heap.object_heap_map.get(&instance_id).expect("invalid object ref");
-                obj.class_id
-            }
+            InvokeType::Virtual => self.with_object(instance_id, |obj| obj.class_id)?,
             InvokeType::Special => declaring_class_id,
-            _ => panic!("invalid invoke type"),
+            InvokeType::Static => {
+                return Err(self.jvm.raise(VmError::Internal(String::from("static invocation of an instance method"))))
+            }
         };
-        let class = self.get_class_file(class_id);
+        let class = self.get_class_file(class_id)?;
         let (method_class, method) =
             match self.jvm.classpath.get_method(invoke_type, class, method_name, method_descriptor) {
                 Some(method) => method,
@@ -260,41 +391,26 @@ impl<'a> JniEnv<'a> {
                 Some(obj_id) => Ok(obj_id),
                 None => Err(self.throw_exception("java/lang/NullPointerException", None)),
             },
-            _ => Err(self.throw_exception("java/lang/Error", Some("expecting object parameter"))),
+            _ => Err(self.jvm.raise(VmError::Internal(String::from("expecting object parameter")))),
         }
     }
 
-    pub fn get_class_file(&self, class_id: usize) -> &ClassFile {
+    pub fn get_class_file(&self, class_id: usize) -> RuntimeResult<&ClassFile> {
         let class_name = self.jvm.get_class_name_from_id(class_id);
-        self.jvm.classpath.get_classpath_entry(&class_name).unwrap_or_else(|| {
-            self.jvm.throw_exception("java/lang/NoClassDefFoundError", Some(&class_name));
-            panic!();
-        })
+        match self.jvm.classpath.get_classpath_entry(&class_name) {
+            Some(class_file) => Ok(class_file),
+            None => Err(self.jvm.throw_exception("java/lang/NoClassDefFoundError", Some(&class_name))),
+        }
     }
 }
 
-pub fn initialize(jvm: &mut Jvm) -> RuntimeResult<()> {
-    let virtual_frame = CallStackFrame {
-        container_class: String::from("webjvm/lang/Main"),
-        container_method: String::from("main()V"),
-        access_flags: MethodAccessFlags::STATIC,
-        instructions: Vec::new(),
-        is_native_frame: false,
-        metadata: None,
-        state: CallStackFrameState {
-            instruction_offset: 0,
-            line_number: 0,
-            lvt: JavaValueVec::new(),
-            return_stack_value: None,
-            stack: JavaValueVec::new(),
-        },
-    };
-    jvm.push_call_stack_frame(virtual_frame);
+pub fn initialize(jvm: &Jvm) -> RuntimeResult<()> {
+    jvm.prepare_main_thread()?;
 
     let required_classes =
         vec!["java/lang/Object", "java/lang/String", "java/lang/Class", "java/lang/Cloneable", "java/io/Serializable"];
     for cls in &required_classes {
-        jvm.ensure_class_loaded(cls, true).unwrap();
+        jvm.ensure_class_loaded(cls, true)?;
     }
 
     let env = JniEnv::empty(jvm);
@@ -313,18 +429,27 @@ pub fn initialize(jvm: &mut Jvm) -> RuntimeResult<()> {
 
         let thread_class_id = env.get_class_id("java/lang/Thread")?;
         let main_thread = env.new_instance(thread_class_id)?;
-        env.set_field(main_thread, "name", JavaValue::Object(Some(env.new_string("main"))));
-        env.set_field(main_thread, "group", JavaValue::Object(Some(system_thread_group)));
-        env.set_field(main_thread, "priority", JavaValue::Int(5));
+        env.set_field(main_thread, "name", JavaValue::Object(Some(env.new_string("main")?)))?;
+        env.set_field(main_thread, "group", JavaValue::Object(Some(system_thread_group)))?;
+        env.set_field(main_thread, "priority", JavaValue::Int(5))?;
 
         let mut heap = jvm.heap.borrow_mut();
         heap.main_thread_object = main_thread;
+        jvm.threads.borrow_mut().get_thread_mut(MAIN_THREAD).thread_object = main_thread;
     }
 
     let system_class_id = env.get_class_id("java/lang/System")?;
     env.invoke_static_method(system_class_id, "initializeSystemClass", "()V", &[])?;
 
-    jvm.initialized = true;
+    // initializing Reference starts the reference handler thread which enqueues collected references,
+    // initializing Finalizer starts the finalizer thread which runs the finalize methods of enqueued objects
+    for class_name in &["java/lang/ref/Reference", "java/lang/ref/Finalizer"] {
+        if jvm.classpath.get_classpath_entry(class_name).is_some() {
+            env.get_class_id(class_name)?;
+        }
+    }
+
+    jvm.initialized.set(true);
 
     Ok(())
 }
@@ -0,0 +1,541 @@
+use std::collections::{HashMap, HashSet};
+
+use classfile_parser::attribute_info::code_attribute_parser;
+
+use super::{jvm::Jvm, thread::ThreadState};
+use crate::{
+    model::{Heap, InternalMetadata, JavaObject, JavaValue},
+    util::{get_attribute, get_constant_string},
+};
+
+// collections start automatically once the heap has grown to the threshold, which is then set
+// relative to the heap that survived
+const INITIAL_GC_THRESHOLD: usize = 50_000;
+const MAX_HEAP_SIZE: usize = 2_000_000;
+// soft references are only cleared once the live heap comes close to the maximum size
+const SOFT_REFERENCE_PRESSURE: usize = MAX_HEAP_SIZE / 4 * 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReferenceKind {
+    Soft,
+    Weak,
+    Final,
+    Phantom,
+}
+
+const REFERENCE_CLASS: &str = "java/lang/ref/Reference";
+const FINALIZER_CLASS: &str = "java/lang/ref/Finalizer";
+// set on objects which have been handed to the finalizer, they are collected once unreachable again
+const FINALIZER_REGISTERED: &str = "finalizer_registered";
+const RETURN_INSTRUCTION: u8 = 0xb1;
+
+const REFERENCE_CLASSES: &[(&str, ReferenceKind)] = &[
+    ("java/lang/ref/SoftReference", ReferenceKind::Soft),
+    ("java/lang/ref/WeakReference", ReferenceKind::Weak),
+    ("java/lang/ref/FinalReference", ReferenceKind::Final),
+    ("java/lang/ref/PhantomReference", ReferenceKind::Phantom),
+];
+
+pub struct GcState {
+    threshold: usize,
+    is_requested: bool,
+    finalizable_classes: HashMap<usize, bool>,
+}
+
+impl GcState {
+    pub fn new() -> GcState {
+        GcState {
+            threshold: INITIAL_GC_THRESHOLD,
+            is_requested: false,
+            finalizable_classes: HashMap::new(),
+        }
+    }
+}
+
+// the fields of Reference may be hidden by a subclass, as next is by Finalizer
+fn get_reference_field<'a>(obj: &'a JavaObject, name: &str) -> Option<&'a JavaValue> {
+    obj.instance_fields.get(obj.get_field_key(REFERENCE_CLASS, name).as_ref())
+}
+
+fn set_reference_field(obj: &mut JavaObject, name: &str, value: JavaValue) {
+    let key = obj.get_field_key(REFERENCE_CLASS, name).into_owned();
+    obj.instance_fields.insert(key, value);
+}
+
+fn get_heap_id(value: &JavaValue) -> Option<usize> {
+    match value {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => Some(*id),
+        _ => None,
+    }
+}
+
+struct Marker<'a> {
+    heap: &'a Heap,
+    reference_kinds: HashMap<usize, Option<ReferenceKind>>,
+    marked: HashSet<usize>,
+    worklist: Vec<usize>,
+    discovered: Vec<(usize, ReferenceKind)>,
+}
+
+impl<'a> Marker<'a> {
+    fn new(heap: &'a Heap) -> Marker<'a> {
+        Marker {
+            heap,
+            reference_kinds: HashMap::new(),
+            marked: HashSet::new(),
+            worklist: Vec::new(),
+            discovered: Vec::new(),
+        }
+    }
+
+    fn get_reference_kind(&mut self, class_id: usize) -> Option<ReferenceKind> {
+        let heap = self.heap;
+        *self.reference_kinds.entry(class_id).or_insert_with(|| {
+            let mut current = Some(class_id);
+            while let Some(id) = current {
+                let class = &heap.loaded_classes[id];
+                if let Some((_, kind)) = REFERENCE_CLASSES.iter().find(|(name, _)| *name == class.java_type) {
+                    return Some(*kind);
+                }
+                current = class.superclass_id;
+            }
+            None
+        })
+    }
+
+    fn push(&mut self, value: &JavaValue) {
+        if let Some(id) = get_heap_id(value) {
+            if self.marked.insert(id) {
+                self.worklist.push(id);
+            }
+        }
+    }
+
+    fn is_marked(&self, value: &JavaValue) -> bool {
+        !matches!(get_heap_id(value), Some(id) if !self.marked.contains(&id))
+    }
+
+    fn get_referent(&self, reference: usize) -> JavaValue {
+        get_reference_field(&self.heap.object_heap_map[&reference], "referent").cloned().unwrap()
+    }
+
+    // the referents of active references are not traced while discovering, they are only reachable
+    // through the reference unless something else marks them
+    fn trace(&mut self, discover: bool, clear_soft_references: bool) {
+        let heap = self.heap;
+        while let Some(id) = self.worklist.pop() {
+            if let Some(obj) = heap.object_heap_map.get(&id) {
+                let kind = match discover {
+                    true => self.get_reference_kind(obj.class_id),
+                    false => None,
+                };
+                let is_active = matches!(get_reference_field(obj, "next"), Some(JavaValue::Object(None)));
+                let has_referent = get_reference_field(obj, "referent").and_then(get_heap_id).is_some();
+                let discovered = match kind {
+                    Some(ReferenceKind::Soft) if !clear_soft_references => None,
+                    Some(kind) if is_active && has_referent => Some(kind),
+                    _ => None,
+                };
+                if let Some(kind) = discovered {
+                    self.discovered.push((id, kind));
+                }
+
+                let referent_key = obj.get_field_key(REFERENCE_CLASS, "referent");
+                for (name, value) in &obj.instance_fields {
+                    if discovered.is_none() || *name != referent_key {
+                        self.push(value);
+                    }
+                }
+            } else if let Some(array) = heap.array_heap_map.get(&id) {
+                for value in &array.values {
+                    self.push(value);
+                }
+            }
+        }
+    }
+}
+
+impl Jvm {
+    // nested execution keeps heap references in Rust locals the collector cannot see, so collections
+    // only run while no thread is executing native code or class initialization
+    fn is_at_safe_point(&self) -> bool {
+        let threads = self.threads.borrow();
+        threads.get_threads().iter().all(|thread| thread.unwind_barriers.len() <= 1)
+    }
+
+    fn get_heap_size(&self) -> usize {
+        let heap = self.heap.borrow();
+        heap.object_heap_map.len() + heap.array_heap_map.len()
+    }
+
+    // collects right away if possible, otherwise once the scheduler reaches a safe point
+    pub fn request_garbage_collection(&self) {
+        match self.is_at_safe_point() {
+            true => self.collect_garbage(false),
+            false => self.gc.borrow_mut().is_requested = true,
+        }
+    }
+
+    pub fn collect_garbage_if_needed(&self) {
+        let is_due = {
+            let gc = self.gc.borrow();
+            gc.is_requested || self.get_heap_size() >= gc.threshold
+        };
+        if !is_due || !self.is_at_safe_point() {
+            return;
+        }
+
+        self.collect_garbage(false);
+        if self.get_heap_size() >= SOFT_REFERENCE_PRESSURE {
+            self.collect_garbage(true);
+        }
+    }
+
+    // only finalize methods overriding the one of Object with more than a return need to be run
+    fn has_finalizer(&self, class_id: usize) -> bool {
+        if let Some(has_finalizer) = self.gc.borrow().finalizable_classes.get(&class_id) {
+            return *has_finalizer;
+        }
+
+        let class_name = self.get_class_name_from_id(class_id);
+        let has_finalizer = self
+            .classpath
+            .get_classpath_entry(&class_name)
+            .and_then(|class_file| self.classpath.get_virtual_method(class_file, "finalize", "()V"))
+            .is_some_and(|(class_file, method)| {
+                get_constant_string(&class_file.const_pool, class_file.this_class) != "java/lang/Object"
+                    && get_attribute(&class_file.const_pool, &method.attributes, "Code")
+                        .and_then(|attribute| code_attribute_parser(&attribute.info).ok())
+                        .is_none_or(|(_, code_attribute)| code_attribute.code != [RETURN_INSTRUCTION])
+            });
+        self.gc.borrow_mut().finalizable_classes.insert(class_id, has_finalizer);
+        has_finalizer
+    }
+
+    fn push_roots(&self, marker: &mut Marker) {
+        let heap = marker.heap;
+        for class in &heap.loaded_classes {
+            marker.push(&JavaValue::Object(Some(class.class_object_id)));
+            for value in class.static_fields.values() {
+                marker.push(value);
+            }
+        }
+        for id in heap.interned_string_map.values() {
+            marker.push(&JavaValue::Object(Some(*id)));
+        }
+        marker.push(&JavaValue::Object(Some(heap.main_thread_object)));
+
+        let threads = self.threads.borrow();
+        let csf = self.call_stack_frames.borrow();
+        let stacks = std::iter::once(&*csf).chain(threads.get_threads().iter().map(|thread| &thread.call_stack_frames));
+        for frame in stacks.flatten() {
+            for value in frame.state.lvt.iter().chain(frame.state.stack.iter()).chain(&frame.state.return_stack_value) {
+                marker.push(value);
+            }
+            if let Some(monitor) = frame.monitor {
+                marker.push(&JavaValue::Object(Some(monitor)));
+            }
+        }
+
+        for thread in threads.get_threads() {
+            let objects = [
+                Some(thread.thread_object),
+                thread.uncaught_exception,
+                thread.pending_wait.map(|pending_wait| pending_wait.object),
+                match thread.state {
+                    ThreadState::Blocked(object)
+                    | ThreadState::Waiting {
+                        object,
+                        ..
+                    } => Some(object),
+                    _ => None,
+                },
+            ];
+            for object in objects.iter().flatten() {
+                marker.push(&JavaValue::Object(Some(*object)));
+            }
+        }
+
+        for id in self.handles.borrow().get_roots() {
+            marker.push(&JavaValue::Object(Some(id)));
+        }
+    }
+
+    // a mark and sweep collection which clears and enqueues references by the strength of their referents,
+    // the enqueued references are put on the pending list for the reference handler thread
+    pub fn collect_garbage(&self, clear_soft_references: bool) {
+        let (marked, cleared, mut enqueued, finalizable, finalizer_class_id) = {
+            let heap = self.heap.borrow();
+            // finalization needs the finalizer thread, which is started by initializing Finalizer
+            let finalizer_class_id = heap
+                .loaded_classes_lookup
+                .get(FINALIZER_CLASS)
+                .copied()
+                .filter(|class_id| heap.loaded_classes[*class_id].is_initialized);
+            let mut marker = Marker::new(&heap);
+            self.push_roots(&mut marker);
+            marker.trace(true, clear_soft_references);
+
+            let discovered = std::mem::take(&mut marker.discovered);
+            let mut cleared = Vec::new();
+            let mut enqueued = Vec::new();
+            for &(reference, kind) in &discovered {
+                if matches!(kind, ReferenceKind::Soft | ReferenceKind::Weak)
+                    && !marker.is_marked(&marker.get_referent(reference))
+                {
+                    cleared.push(reference);
+                    enqueued.push(reference);
+                }
+            }
+
+            // objects awaiting finalization and phantom reachable objects stay alive until their
+            // references have been processed
+            let mut finalizable = Vec::new();
+            for phase in &[ReferenceKind::Final, ReferenceKind::Phantom] {
+                if *phase == ReferenceKind::Final && finalizer_class_id.is_some() {
+                    for (id, obj) in &heap.object_heap_map {
+                        if !marker.marked.contains(id)
+                            && obj.get_internal_metadata(FINALIZER_REGISTERED).is_none()
+                            && self.has_finalizer(obj.class_id)
+                        {
+                            finalizable.push(*id);
+                        }
+                    }
+                    for id in &finalizable {
+                        marker.push(&JavaValue::Object(Some(*id)));
+                    }
+                }
+                for &(reference, kind) in &discovered {
+                    let referent = marker.get_referent(reference);
+                    if kind == *phase && !marker.is_marked(&referent) {
+                        enqueued.push(reference);
+                        marker.push(&referent);
+                    }
+                }
+                marker.trace(false, clear_soft_references);
+            }
+
+            (marker.marked, cleared, enqueued, finalizable, finalizer_class_id)
+        };
+
+        {
+            let mut heap = self.heap.borrow_mut();
+            heap.object_heap_map.retain(|id, _| marked.contains(id));
+            heap.array_heap_map.retain(|id, _| marked.contains(id));
+            for reference in &cleared {
+                set_reference_field(
+                    heap.object_heap_map.get_mut(reference).unwrap(),
+                    "referent",
+                    JavaValue::Object(None),
+                );
+            }
+        }
+        if let Some(finalizer_class_id) = finalizer_class_id {
+            enqueued.extend(self.register_finalizers(finalizer_class_id, &finalizable));
+        }
+        if !enqueued.is_empty() {
+            self.enqueue_pending_references(&enqueued);
+        }
+
+        self.sweep_js_objects();
+        self.sweep_handles();
+
+        let heap_size = self.get_heap_size();
+        let mut gc = self.gc.borrow_mut();
+        gc.is_requested = false;
+        gc.threshold = (heap_size * 2).clamp(INITIAL_GC_THRESHOLD, MAX_HEAP_SIZE);
+    }
+
+    // every object gets a Finalizer referring to it, which is enqueued right away so that the finalizer
+    // thread runs its finalize method. unlike Finalizer.register in the JDK, which is called for every
+    // finalizable object when it is allocated, objects are only registered once a collection finds them
+    // unreachable, so allocation stays cheap and the finalizer queue only holds objects due for finalization
+    fn register_finalizers(&self, finalizer_class_id: usize, objects: &[usize]) -> Vec<usize> {
+        let mut finalizers = Vec::with_capacity(objects.len());
+        for id in objects {
+            let mut finalizer = match self.new_instance(finalizer_class_id) {
+                Ok(finalizer) => finalizer,
+                Err(_) => break,
+            };
+            let mut heap = self.heap.borrow_mut();
+            let queue = heap.loaded_classes[finalizer_class_id].static_fields.get("queue").cloned();
+            set_reference_field(&mut finalizer, "referent", JavaValue::Object(Some(*id)));
+            set_reference_field(&mut finalizer, "queue", queue.unwrap_or(JavaValue::Object(None)));
+            heap.object_heap_map
+                .get_mut(id)
+                .unwrap()
+                .set_internal_metadata(FINALIZER_REGISTERED, InternalMetadata::Numeric(1));
+            drop(heap);
+            finalizers.push(self.heap_store_instance(finalizer));
+        }
+        finalizers
+    }
+
+    // pending references are linked through their discovered field and marked as pending by pointing
+    // next at themselves, the reference handler is woken up through the pending list lock
+    fn enqueue_pending_references(&self, references: &[usize]) {
+        let lock = {
+            let mut heap = self.heap.borrow_mut();
+            let class_id = match heap.loaded_classes_lookup.get(REFERENCE_CLASS) {
+                Some(class_id) => *class_id,
+                None => return,
+            };
+
+            let mut pending =
+                heap.loaded_classes[class_id].static_fields.get("pending").cloned().unwrap_or(JavaValue::Object(None));
+            for reference in references.iter().rev() {
+                let obj = heap.object_heap_map.get_mut(reference).unwrap();
+                set_reference_field(obj, "next", JavaValue::Object(Some(*reference)));
+                set_reference_field(obj, "discovered", pending);
+                pending = JavaValue::Object(Some(*reference));
+            }
+
+            let statics = &mut heap.loaded_classes[class_id].static_fields;
+            statics.insert(String::from("pending"), pending);
+            statics.get("lock").and_then(get_heap_id)
+        };
+
+        if let Some(lock) = lock {
+            self.threads.borrow_mut().notify(lock, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use classfile_parser::ClassAccessFlags;
+
+    use super::{get_reference_field, REFERENCE_CLASS};
+    use crate::{
+        exec::jvm::Jvm,
+        model::{JavaClass, JavaObject, JavaValue},
+        Classpath,
+    };
+
+    fn define_class(jvm: &Jvm, name: &str, superclass_id: Option<usize>) -> usize {
+        let mut heap = jvm.heap.borrow_mut();
+        let class_id = heap.loaded_classes.len();
+        heap.loaded_classes.push(JavaClass {
+            java_type: String::from(name),
+            access_flags: ClassAccessFlags::empty(),
+            class_id,
+            superclass_id,
+            direct_interfaces: Vec::new(),
+            is_array_type: false,
+            is_primitive_type: false,
+            static_fields: HashMap::new(),
+            // class objects are not part of this heap
+            class_object_id: usize::MAX - class_id,
+            is_initialized: true,
+        });
+        heap.loaded_classes_lookup.insert(String::from(name), class_id);
+        class_id
+    }
+
+    fn new_object(jvm: &Jvm, class_id: usize, fields: &[(&str, JavaValue)]) -> usize {
+        jvm.heap_store_instance(JavaObject {
+            class_id,
+            internal_metadata: HashMap::new(),
+            instance_fields: fields.iter().map(|(name, value)| (String::from(*name), value.clone())).collect(),
+            has_hidden_fields: false,
+            identity_hash: None,
+        })
+    }
+
+    fn new_reference(jvm: &Jvm, class_id: usize, referent: usize) -> usize {
+        let fields = [
+            ("referent", JavaValue::Object(Some(referent))),
+            ("queue", JavaValue::Object(None)),
+            ("next", JavaValue::Object(None)),
+            ("discovered", JavaValue::Object(None)),
+        ];
+        new_object(jvm, class_id, &fields)
+    }
+
+    fn get_referent(jvm: &Jvm, reference: usize) -> JavaValue {
+        let heap = jvm.heap.borrow();
+        get_reference_field(&heap.object_heap_map[&reference], "referent").cloned().unwrap()
+    }
+
+    fn get_pending_references(jvm: &Jvm, reference_class_id: usize) -> Vec<usize> {
+        let heap = jvm.heap.borrow();
+        let mut pending = heap.loaded_classes[reference_class_id].static_fields.get("pending").cloned();
+        let mut references = Vec::new();
+        while let Some(JavaValue::Object(Some(reference))) = pending {
+            references.push(reference);
+            pending = get_reference_field(&heap.object_heap_map[&reference], "discovered").cloned();
+        }
+        references
+    }
+
+    #[test]
+    fn clears_and_enqueues_references_to_unreachable_objects() {
+        let jvm = Jvm::new(Classpath::new());
+        let object_class_id = define_class(&jvm, "java/lang/Object", None);
+        let reference_class_id = define_class(&jvm, REFERENCE_CLASS, Some(object_class_id));
+        let weak_class_id = define_class(&jvm, "java/lang/ref/WeakReference", Some(reference_class_id));
+        let soft_class_id = define_class(&jvm, "java/lang/ref/SoftReference", Some(reference_class_id));
+        let root_class_id = define_class(&jvm, "Root", Some(object_class_id));
+        let main_thread = new_object(&jvm, object_class_id, &[]);
+        jvm.heap.borrow_mut().main_thread_object = main_thread;
+
+        let unreachable = new_object(&jvm, object_class_id, &[]);
+        let reachable = new_object(&jvm, object_class_id, &[]);
+        let softly_reachable = new_object(&jvm, object_class_id, &[]);
+        let weak = new_reference(&jvm, weak_class_id, unreachable);
+        let weak_to_reachable = new_reference(&jvm, weak_class_id, reachable);
+        let soft = new_reference(&jvm, soft_class_id, softly_reachable);
+        let garbage = new_object(&jvm, object_class_id, &[]);
+
+        let roots = [weak, weak_to_reachable, soft, reachable];
+        jvm.heap.borrow_mut().loaded_classes[root_class_id].static_fields =
+            roots.iter().enumerate().map(|(i, id)| (format!("root{}", i), JavaValue::Object(Some(*id)))).collect();
+
+        jvm.collect_garbage(false);
+        {
+            let heap = jvm.heap.borrow();
+            assert!(!heap.object_heap_map.contains_key(&unreachable));
+            assert!(!heap.object_heap_map.contains_key(&garbage));
+            assert!(heap.object_heap_map.contains_key(&softly_reachable));
+        }
+        assert_eq!(get_referent(&jvm, weak), JavaValue::Object(None));
+        assert_eq!(get_referent(&jvm, weak_to_reachable), JavaValue::Object(Some(reachable)));
+        assert_eq!(get_referent(&jvm, soft), JavaValue::Object(Some(softly_reachable)));
+        assert_eq!(get_pending_references(&jvm, reference_class_id), vec![weak]);
+
+        // enqueued references are no longer active and are not discovered again
+        jvm.collect_garbage(true);
+        assert!(!jvm.heap.borrow().object_heap_map.contains_key(&softly_reachable));
+        assert_eq!(get_referent(&jvm, soft), JavaValue::Object(None));
+        assert_eq!(get_pending_references(&jvm, reference_class_id), vec![soft, weak]);
+    }
+
+    #[test]
+    fn keeps_objects_handed_to_the_embedder_alive_until_released() {
+        let jvm = Jvm::new(Classpath::new());
+        let object_class_id = define_class(&jvm, "java/lang/Object", None);
+        let main_thread = new_object(&jvm, object_class_id, &[]);
+        jvm.heap.borrow_mut().main_thread_object = main_thread;
+
+        // newInstance hands the new object out without anything else referencing it
+        let instance = new_object(&jvm, object_class_id, &[]);
+        let handle = jvm.handles.borrow_mut().get_or_insert(JavaValue::Object(Some(instance))).unwrap();
+        jvm.collect_garbage(true);
+        assert!(jvm.heap.borrow().object_heap_map.contains_key(&instance));
+        assert_eq!(jvm.handles.borrow().get(handle), Some(JavaValue::Object(Some(instance))));
+
+        // every time the handle is handed out it has to be released once more
+        assert_eq!(jvm.handles.borrow_mut().get_or_insert(JavaValue::Object(Some(instance))), Some(handle));
+        assert!(jvm.handles.borrow_mut().release(handle));
+        jvm.collect_garbage(true);
+        assert!(jvm.heap.borrow().object_heap_map.contains_key(&instance));
+
+        assert!(jvm.handles.borrow_mut().release(handle));
+        jvm.collect_garbage(true);
+        assert!(!jvm.heap.borrow().object_heap_map.contains_key(&instance));
+        assert_eq!(jvm.handles.borrow().get(handle), None);
+        assert!(!jvm.handles.borrow_mut().release(handle));
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::model::JavaValue;
+
+struct HandleEntry {
+    reference: JavaValue,
+    pin_count: usize,
+}
+
+// stable references to heap objects and arrays handed out to the embedder, every reference gets a
+// single handle which is pinned each time it is handed out and stays a GC root until the embedder
+// releases it as often, after which it stays valid for as long as the referenced value is alive
+pub struct HandleTable {
+    next_handle: usize,
+    entries: HashMap<usize, HandleEntry>,
+    handles_by_reference: HashMap<usize, usize>,
+}
+
+impl HandleTable {
+    pub fn new() -> HandleTable {
+        HandleTable {
+            next_handle: 0,
+            entries: HashMap::new(),
+            handles_by_reference: HashMap::new(),
+        }
+    }
+
+    fn get_heap_id(reference: &JavaValue) -> Option<usize> {
+        match reference {
+            JavaValue::Object(Some(id)) | JavaValue::Array(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn get_or_insert(&mut self, reference: JavaValue) -> Option<usize> {
+        let id = HandleTable::get_heap_id(&reference)?;
+        if let Some(handle) = self.handles_by_reference.get(&id).copied() {
+            self.pin(handle);
+            return Some(handle);
+        }
+
+        self.next_handle += 1;
+        self.entries.insert(
+            self.next_handle,
+            HandleEntry {
+                reference,
+                pin_count: 1,
+            },
+        );
+        self.handles_by_reference.insert(id, self.next_handle);
+        Some(self.next_handle)
+    }
+
+    pub fn get(&self, handle: usize) -> Option<JavaValue> {
+        self.entries.get(&handle).map(|entry| entry.reference.clone())
+    }
+
+    pub fn pin(&mut self, handle: usize) -> bool {
+        match self.entries.get_mut(&handle) {
+            Some(entry) => {
+                entry.pin_count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn release(&mut self, handle: usize) -> bool {
+        match self.entries.get_mut(&handle) {
+            Some(entry) if entry.pin_count > 0 => {
+                entry.pin_count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get_roots(&self) -> Vec<usize> {
+        self.entries
+            .values()
+            .filter(|entry| entry.pin_count > 0)
+            .filter_map(|entry| HandleTable::get_heap_id(&entry.reference))
+            .collect()
+    }
+
+    // invalidates the unpinned handles whose referenced value has been collected
+    pub fn sweep(&mut self, is_live: impl Fn(usize) -> bool) {
+        let handles_by_reference = &mut self.handles_by_reference;
+        self.entries.retain(|_, entry| match HandleTable::get_heap_id(&entry.reference) {
+            Some(id) if entry.pin_count == 0 && !is_live(id) => {
+                handles_by_reference.remove(&id);
+                false
+            }
+            _ => true,
+        });
+    }
+}
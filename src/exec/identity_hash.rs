@@ -0,0 +1,77 @@
+// identity hash codes are drawn from a xorshift generator rather than derived from heap ids, so they
+// are not predictable unless the generator is explicitly seeded
+pub struct IdentityHashGenerator {
+    state: u64,
+}
+
+impl IdentityHashGenerator {
+    pub fn new(seed: u64) -> IdentityHashGenerator {
+        // splitmix64 spreads similar seeds apart, a zero state would make xorshift return only zeroes
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        IdentityHashGenerator {
+            state: if state == 0 {
+                1
+            } else {
+                state
+            },
+        }
+    }
+
+    pub fn with_random_seed() -> IdentityHashGenerator {
+        IdentityHashGenerator::new(get_random_seed())
+    }
+
+    // hashes are positive 31 bit values like in HotSpot, zero is never returned
+    pub fn next_hash(&mut self) -> i32 {
+        loop {
+            self.state ^= self.state >> 12;
+            self.state ^= self.state << 25;
+            self.state ^= self.state >> 27;
+            let hash = (self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 33) as i32;
+            if hash != 0 {
+                return hash;
+            }
+        }
+    }
+}
+
+fn get_random_seed() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    return (js_sys::Math::random() * u64::MAX as f64) as u64 ^ crate::util::current_time_millis() as u64;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::{
+            collections::hash_map::RandomState,
+            hash::{BuildHasher, Hasher},
+        };
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_i64(crate::util::current_time_millis());
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdentityHashGenerator;
+
+    fn take_hashes(generator: &mut IdentityHashGenerator) -> Vec<i32> {
+        (0..1000).map(|_| generator.next_hash()).collect()
+    }
+
+    #[test]
+    fn seeded_generators_are_deterministic() {
+        let hashes = take_hashes(&mut IdentityHashGenerator::new(42));
+        assert_eq!(hashes, take_hashes(&mut IdentityHashGenerator::new(42)));
+        assert_ne!(hashes, take_hashes(&mut IdentityHashGenerator::new(43)));
+        assert!(hashes.iter().all(|hash| *hash > 0));
+
+        // a zero seed must not leave the generator stuck
+        let hashes = take_hashes(&mut IdentityHashGenerator::new(0));
+        assert!(hashes.iter().all(|hash| *hash > 0));
+        assert!(hashes.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+}
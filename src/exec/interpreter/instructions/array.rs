@@ -1,7 +1,7 @@
 use crate::{
     exec::interpreter::InstructionEnvironment,
     model::{JavaArrayType, JavaValue, RuntimeResult},
-    util::get_constant_string,
+    util::get_class_constant_name,
 };
 
 pub fn newarray(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
@@ -15,10 +15,10 @@ pub fn newarray(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
         9 => JavaArrayType::Short,
         10 => JavaArrayType::Int,
         11 => JavaArrayType::Long,
-        _ => panic!("invalid array type code"),
+        _ => return Err(env.verify_error("invalid array type code")),
     };
 
-    let length = pop!(env).as_int().expect("expected integral value");
+    let length = pop!(env).as_int().map_err(|_| env.verify_error("expected integral value"))?;
     let arr = env.jvm.create_empty_array(array_type, length as usize);
 
     env.state.stack.push(JavaValue::Array(arr));
@@ -29,10 +29,10 @@ pub fn newarray(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn anewarray(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (type_ref_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(env);
-    let type_str = get_constant_string(const_pool, type_ref_id);
+    let type_str = get_class_constant_name(const_pool, type_ref_id).ok_or_else(|| env.verify_error("bad class ref"))?;
     let type_id = env.jvm.ensure_class_loaded(type_str, true)?;
 
-    let length = env.state.stack.pop().expect("stack underflow").as_int().expect("expected integral value");
+    let length = pop!(env).as_int().map_err(|_| env.verify_error("expected integral value"))?;
     let arr = env.jvm.create_empty_array(JavaArrayType::Object(type_id), length as usize);
 
     env.state.stack.push(JavaValue::Array(arr));
@@ -47,7 +47,10 @@ pub fn arraylength(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
         _ => return Err(env.jvm.throw_exception("java/lang/Error", Some("invalid array instance ID"))),
     };
     let heap = env.jvm.heap.borrow();
-    let arrayref = heap.array_heap_map.get(&arrayref_id).expect("arraylength: invalid array instance ID");
+    let arrayref = match heap.array_heap_map.get(&arrayref_id) {
+        Some(val) => val,
+        None => return Err(env.jvm.invalid_reference(arrayref_id)),
+    };
     env.state.stack.push(JavaValue::Int(arrayref.values.len() as i32));
 
     Ok(())
@@ -55,7 +58,7 @@ pub fn arraylength(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 
 pub fn arraystore(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let value = pop_full!(env);
-    let index = pop!(env).as_int().expect("invalid array index");
+    let index = pop!(env).as_int().map_err(|_| env.verify_error("invalid array index"))?;
     let arrayref_id = match pop!(env) {
         JavaValue::Array(id) => id,
         JavaValue::Object(None) => return Err(env.jvm.throw_npe()),
@@ -63,7 +66,10 @@ pub fn arraystore(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     };
 
     let mut heap = env.jvm.heap.borrow_mut();
-    let arrayref = heap.array_heap_map.get_mut(&arrayref_id).expect("invalid array instance ID");
+    let arrayref = match heap.array_heap_map.get_mut(&arrayref_id) {
+        Some(val) => val,
+        None => return Err(env.jvm.invalid_reference(arrayref_id)),
+    };
     if index >= arrayref.values.len() as i32 || index < 0 {
         return Err(env
             .jvm
@@ -76,7 +82,7 @@ pub fn arraystore(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 }
 
 pub fn arrayload(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let index = pop!(env).as_int().expect("invalid array index");
+    let index = pop!(env).as_int().map_err(|_| env.verify_error("invalid array index"))?;
     let arrayref_id = match pop!(env) {
         JavaValue::Array(id) => id,
         JavaValue::Object(None) => return Err(env.jvm.throw_npe()),
@@ -84,7 +90,10 @@ pub fn arrayload(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     };
 
     let heap = env.jvm.heap.borrow();
-    let arrayref = heap.array_heap_map.get(&arrayref_id).expect("invalid array instance ID");
+    let arrayref = match heap.array_heap_map.get(&arrayref_id) {
+        Some(val) => val,
+        None => return Err(env.jvm.invalid_reference(arrayref_id)),
+    };
 
     if index >= arrayref.values.len() as i32 || index < 0 {
         return Err(env
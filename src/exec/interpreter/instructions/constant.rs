@@ -3,7 +3,7 @@ use classfile_parser::constant_info::ConstantInfo;
 use crate::{
     exec::interpreter::InstructionEnvironment,
     model::{JavaValue, RuntimeResult},
-    util::get_constant_string,
+    util::{get_constant, get_constant_string},
 };
 
 macro_rules! define_const {
@@ -35,25 +35,21 @@ define_const!(lconst1, Long, 1);
 define_const!(dconst0, Double, 0f64);
 define_const!(dconst1, Double, 1f64);
 
-fn push_constant(
-    env: &mut InstructionEnvironment,
-    const_pool: &[ConstantInfo],
-    constant_id: usize,
-) -> RuntimeResult<()> {
-    let value = match &const_pool[constant_id - 1] {
-        ConstantInfo::Integer(ic) => JavaValue::Int(ic.value),
-        ConstantInfo::Long(lc) => JavaValue::Long(lc.value),
-        ConstantInfo::Float(fc) => JavaValue::Float(fc.value),
-        ConstantInfo::Double(dc) => JavaValue::Double(dc.value),
-        ConstantInfo::String(sc) => match &const_pool[sc.string_index as usize - 1] {
-            ConstantInfo::Utf8(inner) => {
+fn push_constant(env: &mut InstructionEnvironment, const_pool: &[ConstantInfo], constant_id: u16) -> RuntimeResult<()> {
+    let value = match get_constant(const_pool, constant_id) {
+        Some(ConstantInfo::Integer(ic)) => JavaValue::Int(ic.value),
+        Some(ConstantInfo::Long(lc)) => JavaValue::Long(lc.value),
+        Some(ConstantInfo::Float(fc)) => JavaValue::Float(fc.value),
+        Some(ConstantInfo::Double(dc)) => JavaValue::Double(dc.value),
+        Some(ConstantInfo::String(sc)) => match get_constant(const_pool, sc.string_index) {
+            Some(ConstantInfo::Utf8(inner)) => {
                 let str = inner.utf8_string.clone();
-                let obj = env.jvm.create_string_object(str.as_str(), true);
+                let obj = env.jvm.create_string_object(str.as_str(), true)?;
                 JavaValue::Object(Some(obj))
             }
-            x => panic!("bad string constant definition: {:?}", x),
+            x => return Err(env.verify_error(&format!("bad string constant definition: {:?}", x))),
         },
-        ConstantInfo::Class(cc) => {
+        Some(ConstantInfo::Class(cc)) => {
             let class_name = get_constant_string(const_pool, cc.name_index);
             let class_id = env.jvm.ensure_class_loaded(class_name, true)?;
 
@@ -62,7 +58,7 @@ fn push_constant(
 
             JavaValue::Object(Some(class_object_id))
         }
-        x => panic!("bad constant: {:?}", x),
+        x => return Err(env.verify_error(&format!("bad constant: {:?}", x))),
     };
     env.state.stack.push(value);
 
@@ -72,7 +68,7 @@ fn push_constant(
 pub fn ldc(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (constant_id,) = take_values!(env, u8);
     let const_pool = use_const_pool!(env);
-    push_constant(env, const_pool, constant_id as usize)?;
+    push_constant(env, const_pool, constant_id as u16)?;
 
     Ok(())
 }
@@ -80,7 +76,7 @@ pub fn ldc(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn ldcw(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (constant_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(env);
-    push_constant(env, const_pool, constant_id as usize)?;
+    push_constant(env, const_pool, constant_id)?;
 
     Ok(())
 }
@@ -1,7 +1,7 @@
 use crate::{
     exec::interpreter::InstructionEnvironment,
     model::{JavaValue, MethodDescriptor, RuntimeResult},
-    util::get_constant_string,
+    util::get_class_constant_name,
 };
 
 macro_rules! define_if {
@@ -9,7 +9,7 @@ macro_rules! define_if {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
             let (offset,) = take_values!(env, i16);
             let val = pop!(env);
-            let int = val.as_int().expect("expecting integral value");
+            let int = val.as_int().map_err(|_| env.verify_error("expecting integral value"))?;
             if int $op 0 {
                 branch_to!(env, offset);
             }
@@ -26,8 +26,8 @@ macro_rules! define_ificmp {
             let rhs = pop!(env);
             let lhs = pop!(env);
 
-            let lhs_int = lhs.as_int().expect("expecting integral value");
-            let rhs_int = rhs.as_int().expect("expecting integral value");
+            let lhs_int = lhs.as_int().map_err(|_| env.verify_error("expecting integral value"))?;
+            let rhs_int = rhs.as_int().map_err(|_| env.verify_error("expecting integral value"))?;
 
             if lhs_int $op rhs_int {
                 branch_to!(env, offset);
@@ -54,11 +54,12 @@ define_if!(ifne, !=);
 
 pub fn checkcast(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (compare_type_id,) = take_values!(env, u16);
-    let test = env.state.stack.last().expect("stack underflow");
+    let test = env.state.stack.last().ok_or_else(|| env.verify_error("stack underflow"))?;
 
     if (test.is_object() && test.as_object().unwrap().is_some()) || test.is_array() {
         let const_pool = use_const_pool!(&env);
-        let compare_type = get_constant_string(const_pool, compare_type_id);
+        let compare_type =
+            get_class_constant_name(const_pool, compare_type_id).ok_or_else(|| env.verify_error("bad class ref"))?;
 
         if !env.jvm.is_instance_of(test, compare_type, true)? {
             return Err(env.jvm.throw_exception("java/lang/ClassCastException", Some(compare_type)));
@@ -71,7 +72,8 @@ pub fn checkcast(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn instanceof(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (compare_type_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(&env);
-    let compare_type = get_constant_string(const_pool, compare_type_id);
+    let compare_type =
+        get_class_constant_name(const_pool, compare_type_id).ok_or_else(|| env.verify_error("bad class ref"))?;
     let res = env.jvm.is_instance_of(&pop!(env), compare_type, false)?;
     env.state.stack.push(JavaValue::Boolean(res));
 
@@ -95,7 +97,7 @@ pub fn ifnonnull(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
             }
         }
         JavaValue::Array(_) => branch_to!(env, offset), // internally the way we store arrays they can never be null
-        _ => panic!("ifnonnull expecting object"),
+        _ => return Err(env.verify_error("ifnonnull expecting object")),
     };
 
     Ok(())
@@ -111,35 +113,40 @@ pub fn ifnull(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
             }
         }
         JavaValue::Array(_) => (), // internally the way we store arrays they can never be null
-        _ => panic!("ifnull expecting object"),
+        _ => return Err(env.verify_error("ifnull expecting object")),
     };
 
     Ok(())
 }
 
 pub fn returnvoid(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let mut csf = env.jvm.call_stack_frames.borrow_mut();
-    csf.pop().unwrap();
+    env.jvm.pop_call_stack_frame().unwrap();
     Ok(())
 }
 
 pub fn returnvalue(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let return_value = pop_full!(env);
 
-    let container_method = {
-        let csf = env.jvm.call_stack_frames.borrow();
-        let top_frame = csf.last().unwrap();
-        top_frame.container_method.clone()
+    let container_method = match env.jvm.call_stack_frames.borrow().last() {
+        Some(top_frame) => top_frame.container_method.clone(),
+        None => return Err(env.jvm.missing_stack_frame()),
     };
     if let JavaValue::Object(_) = return_value {
-        let descriptor = MethodDescriptor::new(&container_method).unwrap();
+        let descriptor =
+            MethodDescriptor::new(&container_method).map_err(|_| env.verify_error("bad method descriptor"))?;
         if !env.jvm.is_instance_of(&return_value, &descriptor.return_type[1..descriptor.return_type.len() - 1], true)? {
             return Err(env.jvm.throw_exception("java/lang/ClassCastException", None));
         }
     }
+    env.jvm.pop_call_stack_frame();
     let mut csf = env.jvm.call_stack_frames.borrow_mut();
-    csf.pop().unwrap();
-    csf.last_mut().expect("stack underflow").state.return_stack_value = Some(return_value);
+    match csf.last_mut() {
+        Some(caller_frame) => caller_frame.state.return_stack_value = Some(return_value),
+        None => {
+            drop(csf);
+            return Err(env.jvm.missing_stack_frame());
+        }
+    }
 
     Ok(())
 }
@@ -180,7 +187,7 @@ pub fn compare_references(env: &mut InstructionEnvironment, jump_if_equal: bool)
 }
 
 pub fn lookupswitch(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let key = pop!(env).as_int().expect("expecting integral value");
+    let key = pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))?;
 
     env.state.instruction_offset += (4 - env.state.instruction_offset % 4) % 4;
     let (default, npairs) = take_values!(env, u32, u32);
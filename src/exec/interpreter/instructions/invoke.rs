@@ -1,7 +1,7 @@
 use crate::{
     exec::interpreter::InstructionEnvironment,
     model::{CallStackFrame, JavaValue, JavaValueVec, MethodDescriptor, RuntimeResult},
-    util::{get_constant_name_and_type, get_constant_string},
+    util::{get_constant, get_constant_name_and_type, get_constant_string},
     InvokeType,
 };
 use classfile_parser::constant_info::{ConstantInfo, MethodRefConstant};
@@ -16,7 +16,8 @@ fn create_stack_frame(
     env.jvm.ensure_class_loaded(class_str, true)?;
 
     let method_str = get_constant_name_and_type(const_pool, mr.name_and_type_index);
-    let parsed_descriptor = MethodDescriptor::new(method_str.1).expect("bad method descriptor");
+    let parsed_descriptor =
+        MethodDescriptor::new(method_str.1).map_err(|_| env.verify_error("bad method descriptor"))?;
 
     let args_len = parsed_descriptor
         .argument_types
@@ -47,7 +48,7 @@ fn create_stack_frame(
                     }
                 }
                 JavaValue::Array(_) => (),
-                _ => panic!("bad object ref"),
+                _ => return Err(env.verify_error("bad object ref")),
             };
             args.push(object_instance.clone());
             Some(object_instance)
@@ -60,7 +61,10 @@ fn create_stack_frame(
         InvokeType::Virtual => match instance.unwrap() {
             JavaValue::Object(instance_id) => {
                 let heap = env.jvm.heap.borrow();
-                let instance = &heap.object_heap_map.get(&instance_id.unwrap()).expect("bad object ref");
+                let instance = match heap.object_heap_map.get(&instance_id.unwrap()) {
+                    Some(val) => val,
+                    None => return Err(env.jvm.invalid_reference(instance_id.unwrap())),
+                };
                 let class = &heap.loaded_classes[instance.class_id];
                 class.java_type.clone()
             }
@@ -91,6 +95,13 @@ fn create_stack_frame(
     Ok(frame)
 }
 
+// the invoking frame resumes after the invoke instruction once the new frame returns
+fn push_stack_frame(env: &mut InstructionEnvironment, stack_frame: CallStackFrame) {
+    let mut csf = env.jvm.call_stack_frames.borrow_mut();
+    csf.last_mut().unwrap().state = env.state.clone();
+    csf.push(stack_frame);
+}
+
 pub fn invokevirtual(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     invoke_instance_method(env, false)
 }
@@ -102,18 +113,13 @@ pub fn invokeinterface(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn invokespecial(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (method_ref_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(env);
-    match &const_pool[method_ref_id as usize - 1] {
-        ConstantInfo::MethodRef(mr) => {
+    match get_constant(const_pool, method_ref_id) {
+        Some(ConstantInfo::MethodRef(mr)) => {
             let stack_frame = create_stack_frame(env, InvokeType::Special, const_pool, mr)?;
 
-            {
-                let mut csf = env.jvm.call_stack_frames.borrow_mut();
-                csf.push(stack_frame);
-            }
-            env.executor.step_until_stack_depth(env.jvm, env.depth)?;
-            update_stack!(env);
+            push_stack_frame(env, stack_frame);
         }
-        x => panic!("bad method ref: {:?}", x),
+        x => return Err(env.verify_error(&format!("bad method ref: {:?}", x))),
     }
 
     Ok(())
@@ -122,18 +128,13 @@ pub fn invokespecial(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn invokestatic(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (method_ref_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(env);
-    match &const_pool[method_ref_id as usize - 1] {
-        ConstantInfo::MethodRef(mr) => {
+    match get_constant(const_pool, method_ref_id) {
+        Some(ConstantInfo::MethodRef(mr)) => {
             let stack_frame = create_stack_frame(env, InvokeType::Static, const_pool, mr)?;
 
-            {
-                let mut csf = env.jvm.call_stack_frames.borrow_mut();
-                csf.push(stack_frame);
-            }
-            env.executor.step_until_stack_depth(env.jvm, env.depth)?;
-            update_stack!(env);
+            push_stack_frame(env, stack_frame);
         }
-        x => panic!("bad method ref: {:?}", x),
+        x => return Err(env.verify_error(&format!("bad method ref: {:?}", x))),
     }
 
     Ok(())
@@ -147,21 +148,16 @@ fn invoke_instance_method(env: &mut InstructionEnvironment, is_interface_method:
     }
 
     let const_pool = use_const_pool!(env);
-    let mr = match &const_pool[index as usize - 1] {
-        ConstantInfo::MethodRef(mr) => mr.clone(),
-        ConstantInfo::InterfaceMethodRef(imr) => MethodRefConstant {
+    let mr = match get_constant(const_pool, index) {
+        Some(ConstantInfo::MethodRef(mr)) => mr.clone(),
+        Some(ConstantInfo::InterfaceMethodRef(imr)) => MethodRefConstant {
             class_index: imr.class_index,
             name_and_type_index: imr.name_and_type_index,
         },
-        x => panic!("bad method ref: {:?}", x),
+        x => return Err(env.verify_error(&format!("bad method ref: {:?}", x))),
     };
     let stack_frame = create_stack_frame(env, InvokeType::Virtual, const_pool, &mr)?;
-    {
-        let mut csf = env.jvm.call_stack_frames.borrow_mut();
-        csf.push(stack_frame);
-    }
-    env.executor.step_until_stack_depth(env.jvm, env.depth)?;
-    update_stack!(env);
+    push_stack_frame(env, stack_frame);
 
     Ok(())
 }
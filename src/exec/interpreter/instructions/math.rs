@@ -6,9 +6,15 @@ use std::{cmp::Ordering, num::Wrapping};
 
 macro_rules! define_imath {
     ( $insn:ident, $op:tt ) => {
+        define_imath!($insn, $op, false);
+    };
+    ( $insn:ident, $op:tt, $is_division:expr ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = Wrapping(pop!(env).as_int().expect("expecting integral value"));
-            let lhs = Wrapping(pop!(env).as_int().expect("expecting integral value"));
+            let rhs = Wrapping(pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))?);
+            let lhs = Wrapping(pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))?);
+            if $is_division && rhs.0 == 0 {
+                return Err(env.jvm.throw_exception("java/lang/ArithmeticException", Some("/ by zero")));
+            }
             env.state.stack.push(JavaValue::Int((lhs $op rhs).0));
 
             Ok(())
@@ -19,8 +25,8 @@ macro_rules! define_imath {
 macro_rules! define_ishift {
     ( $insn:ident, $op:tt, $int_type:ty ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = pop!(env).as_int().expect("expecting integral value") & 0b11111;
-            let lhs = pop!(env).as_int().expect("expecting integral value");
+            let rhs = pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))? & 0b11111;
+            let lhs = pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))?;
             env.state.stack.push(JavaValue::Int(((lhs as $int_type) $op (rhs as $int_type)) as i32));
 
             Ok(())
@@ -31,8 +37,8 @@ macro_rules! define_ishift {
 macro_rules! define_lshift {
     ( $insn:ident, $op:tt, $int_type:ty ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = pop!(env).as_int().expect("expecting integral value") & 0b111111;
-            let lhs = pop_full!(env).as_long().expect("expecting long value");
+            let rhs = pop!(env).as_int().map_err(|_| env.verify_error("expecting integral value"))? & 0b111111;
+            let lhs = pop_full!(env).as_long().map_err(|_| env.verify_error("expecting long value"))?;
             env.state.stack.push(JavaValue::Long(((lhs as $int_type) $op (rhs as $int_type)) as i64));
 
             Ok(())
@@ -42,9 +48,15 @@ macro_rules! define_lshift {
 
 macro_rules! define_lmath {
     ( $insn:ident, $op:tt ) => {
+        define_lmath!($insn, $op, false);
+    };
+    ( $insn:ident, $op:tt, $is_division:expr ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = Wrapping(pop_full!(env).as_long().expect("expecting long value"));
-            let lhs = Wrapping(pop_full!(env).as_long().expect("expecting long value"));
+            let rhs = Wrapping(pop_full!(env).as_long().map_err(|_| env.verify_error("expecting long value"))?);
+            let lhs = Wrapping(pop_full!(env).as_long().map_err(|_| env.verify_error("expecting long value"))?);
+            if $is_division && rhs.0 == 0 {
+                return Err(env.jvm.throw_exception("java/lang/ArithmeticException", Some("/ by zero")));
+            }
             env.state.stack.push(JavaValue::Long((lhs $op rhs).0));
 
             Ok(())
@@ -55,8 +67,8 @@ macro_rules! define_lmath {
 macro_rules! define_fmath {
     ( $insn:ident, $op:tt ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = pop!(env).as_float().expect("expecting float value");
-            let lhs = pop!(env).as_float().expect("expecting float value");
+            let rhs = pop!(env).as_float().map_err(|_| env.verify_error("expecting float value"))?;
+            let lhs = pop!(env).as_float().map_err(|_| env.verify_error("expecting float value"))?;
             env.state.stack.push(JavaValue::Float(lhs $op rhs));
 
             Ok(())
@@ -67,8 +79,8 @@ macro_rules! define_fmath {
 macro_rules! define_dmath {
     ( $insn:ident, $op:tt ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-            let rhs = pop_full!(env).as_double().expect("expecting double value");
-            let lhs = pop_full!(env).as_double().expect("expecting double value");
+            let rhs = pop_full!(env).as_double().map_err(|_| env.verify_error("expecting double value"))?;
+            let lhs = pop_full!(env).as_double().map_err(|_| env.verify_error("expecting double value"))?;
             env.state.stack.push(JavaValue::Double(lhs $op rhs));
 
             Ok(())
@@ -76,16 +88,28 @@ macro_rules! define_dmath {
     }
 }
 
+macro_rules! define_neg {
+    ( $insn:ident, $from:ident, $jt:ident, $pop:ident, $negate:expr ) => {
+        pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
+            use paste::paste;
+
+            paste! {
+                let value = $pop!(env).[<as_ $from>]().map_err(|_| env.verify_error(concat!("expecting ", stringify!($from), " value")))?;
+                env.state.stack.push(JavaValue::$jt($negate(value)));
+
+                Ok(())
+            }
+        }
+    };
+}
+
 macro_rules! define_cast {
     ( $insn:ident, $from:ident, $jt:ident, $cast:ty ) => {
         pub fn $insn(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
             use paste::paste;
 
             paste! {
-                let int = match pop_full!(env).[<as_ $from>]() {
-                    Ok(e) => e,
-                    Err(_) => panic!(),
-                };
+                let int = pop_full!(env).[<as_ $from>]().map_err(|_| env.verify_error(concat!("expecting ", stringify!($from), " value")))?;
                 env.state.stack.push(JavaValue::$jt(int as $cast));
 
                 Ok(())
@@ -96,19 +120,19 @@ macro_rules! define_cast {
 
 define_imath!(iadd, +);
 define_imath!(iand, &);
-define_imath!(idiv, /);
+define_imath!(idiv, /, true);
 define_imath!(imul, *);
 define_imath!(ior, |);
-define_imath!(irem, %);
+define_imath!(irem, %, true);
 define_imath!(isub, -);
 define_imath!(ixor, ^);
 
 define_lmath!(ladd, +);
 define_lmath!(land, &);
-define_lmath!(ldiv, /);
+define_lmath!(ldiv, /, true);
 define_lmath!(lmul, *);
 define_lmath!(lor, |);
-define_lmath!(lrem, %);
+define_lmath!(lrem, %, true);
 define_lmath!(lsub, -);
 define_lmath!(lxor, ^);
 
@@ -124,6 +148,12 @@ define_dmath!(dmul, *);
 define_dmath!(ddiv, /);
 define_dmath!(drem, %);
 
+// integer negation wraps around, so the minimum value stays negative
+define_neg!(ineg, int, Int, pop, i32::wrapping_neg);
+define_neg!(lneg, long, Long, pop_full, i64::wrapping_neg);
+define_neg!(fneg, float, Float, pop, std::ops::Neg::neg);
+define_neg!(dneg, double, Double, pop_full, std::ops::Neg::neg);
+
 define_ishift!(ishl, <<, i32);
 define_ishift!(ishr, >>, i32);
 define_ishift!(iushr, >>, u32);
@@ -153,7 +183,8 @@ define_cast!(l2d, long, Double, f64);
 
 pub fn iinc(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (index, value) = take_values!(env, u8, i8);
-    let current_val = env.state.lvt[index as usize].as_int().expect("expecting integral value");
+    let current_val =
+        env.state.lvt[index as usize].as_int().map_err(|_| env.verify_error("expecting integral value"))?;
     env.state.lvt[index as usize] = JavaValue::Int(current_val + value as i32);
 
     Ok(())
@@ -161,7 +192,8 @@ pub fn iinc(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 
 pub fn iincwide(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (index, value) = take_values!(env, u16, i16);
-    let current_val = env.state.lvt[index as usize].as_int().expect("expecting integral value");
+    let current_val =
+        env.state.lvt[index as usize].as_int().map_err(|_| env.verify_error("expecting integral value"))?;
     env.state.lvt[index as usize] = JavaValue::Int(current_val + value as i32);
 
     Ok(())
@@ -177,8 +209,8 @@ pub fn dcmpl(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 
 #[allow(clippy::float_cmp)]
 pub fn compare_doubles(env: &mut InstructionEnvironment, greater: bool) -> RuntimeResult<()> {
-    let rhs = pop_full!(env).as_double().expect("expecting double");
-    let lhs = pop_full!(env).as_double().expect("expecting double");
+    let rhs = pop_full!(env).as_double().map_err(|_| env.verify_error("expecting double"))?;
+    let lhs = pop_full!(env).as_double().map_err(|_| env.verify_error("expecting double"))?;
     if lhs.is_nan() || rhs.is_nan() {
         let nan_value = match greater {
             true => 1,
@@ -206,8 +238,8 @@ pub fn fcmpl(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 
 #[allow(clippy::float_cmp)]
 pub fn compare_floats(env: &mut InstructionEnvironment, greater: bool) -> RuntimeResult<()> {
-    let rhs = pop!(env).as_float().expect("expecting float");
-    let lhs = pop!(env).as_float().expect("expecting float");
+    let rhs = pop!(env).as_float().map_err(|_| env.verify_error("expecting float"))?;
+    let lhs = pop!(env).as_float().map_err(|_| env.verify_error("expecting float"))?;
     if lhs.is_nan() || rhs.is_nan() {
         let nan_value = match greater {
             true => 1,
@@ -226,8 +258,8 @@ pub fn compare_floats(env: &mut InstructionEnvironment, greater: bool) -> Runtim
 }
 
 pub fn lcmp(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let rhs = pop_full!(env).as_long().unwrap();
-    let lhs = pop_full!(env).as_long().unwrap();
+    let rhs = pop_full!(env).as_long().map_err(|_| env.verify_error("expecting long"))?;
+    let lhs = pop_full!(env).as_long().map_err(|_| env.verify_error("expecting long"))?;
     let val = match lhs.cmp(&rhs) {
         Ordering::Greater => 1,
         Ordering::Less => -1,
@@ -1,7 +1,7 @@
 use crate::{
-    exec::interpreter::InstructionEnvironment,
+    exec::{interpreter::InstructionEnvironment, thread::ThreadState},
     model::{JavaValue, RuntimeResult},
-    util::get_constant_string,
+    util::get_class_constant_name,
 };
 
 pub fn nop(_env: &mut InstructionEnvironment) -> RuntimeResult<()> {
@@ -11,7 +11,7 @@ pub fn nop(_env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 pub fn new(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
     let (type_ref_id,) = take_values!(env, u16);
     let const_pool = use_const_pool!(env);
-    let type_str = get_constant_string(const_pool, type_ref_id);
+    let type_str = get_class_constant_name(const_pool, type_ref_id).ok_or_else(|| env.verify_error("bad class ref"))?;
 
     let type_loaded_id = env.jvm.ensure_class_loaded(type_str, true)?;
     let instance = env.jvm.new_instance(type_loaded_id)?;
@@ -23,9 +23,41 @@ pub fn new(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 }
 
 pub fn athrow(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let ex = match pop!(env).as_object().expect("expecting object ref") {
+    let ex = match pop!(env).as_object().map_err(|_| env.verify_error("expecting object ref"))? {
         Some(obj) => obj,
         None => return Err(env.jvm.throw_npe()),
     };
     Err(env.jvm.throw_exception_ref(ex))
 }
+
+pub fn monitorenter(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
+    let value = pop!(env);
+    let object = match value {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => id,
+        _ => return Err(env.jvm.throw_npe()),
+    };
+
+    let mut threads = env.jvm.threads.borrow_mut();
+    if !threads.try_enter_monitor(object) {
+        // retry the instruction once the owning thread releases the monitor
+        threads.get_current_thread_mut().state = ThreadState::Blocked(object);
+        env.state.stack.push(value);
+        env.state.instruction_offset = env.instruction_address;
+    }
+
+    Ok(())
+}
+
+pub fn monitorexit(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
+    let object = match pop!(env) {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => id,
+        _ => return Err(env.jvm.throw_npe()),
+    };
+
+    let has_exited = env.jvm.threads.borrow_mut().exit_monitor(object);
+    if !has_exited {
+        return Err(env.jvm.throw_exception("java/lang/IllegalMonitorStateException", None));
+    }
+
+    Ok(())
+}
@@ -90,18 +90,6 @@ macro_rules! use_const_pool {
     }};
 }
 
-#[macro_export]
-macro_rules! update_stack {
-    ( $env:expr ) => {{
-        let mut csf = $env.jvm.call_stack_frames.borrow_mut();
-        let frame = csf.last_mut().expect("no stack frame present");
-        if let Some(value) = &frame.state.return_stack_value {
-            $env.state.stack.push(value.clone());
-            frame.state.return_stack_value = None;
-        }
-    }};
-}
-
 #[macro_export]
 macro_rules! branch_to {
     ( $env:expr, $offset:expr ) => {{
@@ -257,10 +245,10 @@ pub fn initialize(handlers: &mut Vec<InstructionHandler>) {
     handlers[0x71] = math::lrem;
     handlers[0x72] = math::frem;
     handlers[0x73] = math::drem;
-    // handlers[0x74] = math::ineg;
-    // handlers[0x75] = math::lneg;
-    // handlers[0x76] = math::fneg;
-    // handlers[0x77] = math::dneg;
+    handlers[0x74] = math::ineg;
+    handlers[0x75] = math::lneg;
+    handlers[0x76] = math::fneg;
+    handlers[0x77] = math::dneg;
 
     handlers[0x78] = math::ishl;
     handlers[0x79] = math::lshl;
@@ -341,8 +329,8 @@ pub fn initialize(handlers: &mut Vec<InstructionHandler>) {
 
     handlers[0xc0] = control_flow::checkcast;
     handlers[0xc1] = control_flow::instanceof;
-    handlers[0xc2] = stack::pop; // TODO: monitorenter
-    handlers[0xc3] = stack::pop; // TODO: monitorexit
+    handlers[0xc2] = misc::monitorenter;
+    handlers[0xc3] = misc::monitorexit;
     handlers[0xc4] = wide::wide;
     handlers[0xc6] = control_flow::ifnull;
     handlers[0xc7] = control_flow::ifnonnull;
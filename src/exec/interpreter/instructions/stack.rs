@@ -13,14 +13,14 @@ pub fn pop2(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 }
 
 pub fn dup(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let top = env.state.stack.last().expect("stack underflow").clone();
+    let top = env.state.stack.last().ok_or_else(|| env.verify_error("stack underflow"))?.clone();
     env.state.stack.push(top);
 
     Ok(())
 }
 
 pub fn dup2(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let top = env.state.stack.last_full().expect("stack underflow").clone();
+    let top = env.state.stack.last_full().ok_or_else(|| env.verify_error("stack underflow"))?.clone();
     if !top.is_wide() {
         let under_top = env.state.stack[env.state.stack.len() - 2].clone();
         env.state.stack.push(under_top);
@@ -31,7 +31,7 @@ pub fn dup2(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
 }
 
 pub fn dupx1(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
-    let top = env.state.stack.last().expect("stack underflow").clone();
+    let top = env.state.stack.last().ok_or_else(|| env.verify_error("stack underflow"))?.clone();
     env.state.stack.insert(env.state.stack.len() - 2, top);
 
     Ok(())
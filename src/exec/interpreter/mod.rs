@@ -1,9 +1,13 @@
+use super::thread::ThreadState;
 use crate::JniEnv;
-use crate::{exec::jvm::*, model::*, StackTraceElement};
+use crate::{exec::jvm::*, model::*, util::get_jni_long_name, StackTraceElement};
+use classfile_parser::method_info::MethodAccessFlags;
 use std::cell::RefCell;
 
 mod instructions;
 
+const THREAD_QUANTUM: usize = 1000;
+
 pub type InstructionHandler = fn(env: &mut InstructionEnvironment) -> RuntimeResult<()>;
 
 pub fn empty_instruction_handler(env: &mut InstructionEnvironment) -> RuntimeResult<()> {
@@ -27,6 +31,19 @@ pub struct InstructionEnvironment<'a, 'b> {
     pub instruction: u8,
 }
 
+impl<'a, 'b> InstructionEnvironment<'a, 'b> {
+    pub fn verify_error(&self, message: &str) -> JavaThrowable {
+        let class_name = match self.jvm.call_stack_frames.borrow().last() {
+            Some(frame) => frame.container_class.clone(),
+            None => String::new(),
+        };
+        self.jvm.raise(VmError::MalformedClassFile {
+            class_name,
+            message: message.to_string(),
+        })
+    }
+}
+
 pub struct InstructionExecutor {
     instruction_count: RefCell<u64>,
 }
@@ -39,12 +56,98 @@ impl InstructionExecutor {
     }
 
     pub fn step_until_stack_depth(&self, jvm: &Jvm, depth: usize) -> RuntimeResult<()> {
-        while {
-            let csf = jvm.call_stack_frames.borrow();
-            csf.len()
-        } > depth
-        {
-            self.step(jvm)?;
+        jvm.push_unwind_barrier(depth);
+        let result = self.run_until_stack_depth(jvm, depth);
+        jvm.pop_unwind_barrier();
+        result
+    }
+
+    fn run_until_stack_depth(&self, jvm: &Jvm, depth: usize) -> RuntimeResult<()> {
+        while jvm.get_stack_depth() > depth {
+            if jvm.is_current_thread_runnable() {
+                self.step(jvm)?;
+            } else {
+                // the current thread blocked while native code is still on its stack,
+                // so other threads have to run from here until it can continue
+                self.run_other_threads(jvm)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn run_threads(&self, jvm: &Jvm) -> RuntimeResult<()> {
+        while jvm.threads.borrow().has_live_non_daemon_threads() {
+            jvm.collect_garbage_if_needed();
+            jvm.wake_expired_threads();
+            jvm.wake_completed_host_calls();
+            let next_thread = jvm.threads.borrow_mut().select_next_thread(true);
+            match next_thread {
+                Some(thread_id) => {
+                    jvm.switch_to_thread(thread_id);
+                    self.run_thread_quantum(jvm)?;
+                }
+                None => jvm.idle_until_next_deadline(true)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_other_threads(&self, jvm: &Jvm) -> RuntimeResult<()> {
+        let blocked_thread = {
+            let mut threads = jvm.threads.borrow_mut();
+            threads.get_current_thread_mut().is_suspended_in_native = true;
+            threads.get_current_thread_id()
+        };
+
+        let result = loop {
+            jvm.wake_expired_threads();
+            jvm.wake_completed_host_calls();
+            if jvm.threads.borrow().get_thread(blocked_thread).is_runnable() {
+                break Ok(());
+            }
+
+            let next_thread = jvm.threads.borrow_mut().select_next_thread(false);
+            let result = match next_thread {
+                Some(thread_id) => {
+                    jvm.switch_to_thread(thread_id);
+                    let result = self.run_thread_quantum(jvm);
+                    jvm.switch_to_thread(blocked_thread);
+                    result
+                }
+                None => jvm.idle_until_next_deadline(false),
+            };
+            if let Err(err) = result {
+                break Err(err);
+            }
+        };
+
+        jvm.threads.borrow_mut().get_thread_mut(blocked_thread).is_suspended_in_native = false;
+        result
+    }
+
+    fn run_thread_quantum(&self, jvm: &Jvm) -> RuntimeResult<()> {
+        for _ in 0..THREAD_QUANTUM {
+            if jvm.get_stack_depth() <= 1
+                || !jvm.is_current_thread_runnable()
+                || jvm.threads.borrow_mut().take_yield_request()
+            {
+                break;
+            }
+
+            match self.step(jvm) {
+                Ok(_) => (),
+                Err(JavaThrowable::Unhandled(ex)) => {
+                    jvm.report_uncaught_exception(ex);
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        if jvm.get_stack_depth() <= 1 {
+            jvm.terminate_current_thread()?;
         }
 
         Ok(())
@@ -62,6 +165,8 @@ impl InstructionExecutor {
         JniEnv {
             jvm,
             container_class: frame.container_class.clone(),
+            container_method: frame.container_method.clone(),
+            is_static: frame.access_flags.contains(MethodAccessFlags::STATIC),
             parameters: frame.state.lvt.clone(),
             stack_trace,
         }
@@ -69,22 +174,52 @@ impl InstructionExecutor {
 
     pub fn step(&self, jvm: &Jvm) -> RuntimeResult<()> {
         match self.step_unchecked(jvm) {
-            Ok(_) => Ok(()),
-            Err(ex) => match ex {
-                JavaThrowable::Handled(ex_id) => {
-                    let mut csf = jvm.call_stack_frames.borrow_mut();
-                    let last_frame = csf.last_mut().unwrap();
-                    println!(
-                        "pushing exception to stack in {}.{}",
-                        last_frame.container_class, last_frame.container_method
-                    );
-                    last_frame.state.stack.push(JavaValue::Object(Some(ex_id)));
-                    println!("stack is now: {}, depth: {}", last_frame.state.stack.jvm_debug(jvm), csf.len());
-
-                    Ok(())
+            Err(JavaThrowable::Handled(_)) => Ok(()),
+            result => result,
+        }
+    }
+
+    fn enter_frame_monitor(&self, jvm: &Jvm) -> RuntimeResult<bool> {
+        let (container_class, receiver) = {
+            let csf = jvm.call_stack_frames.borrow();
+            let frame = csf.last().ok_or_else(|| jvm.missing_stack_frame())?;
+            if !frame.access_flags.contains(MethodAccessFlags::SYNCHRONIZED) || frame.monitor.is_some() {
+                return Ok(true);
+            }
+            if frame.access_flags.contains(MethodAccessFlags::STATIC) {
+                (Some(frame.container_class.clone()), None)
+            } else {
+                (None, Some(frame.state.lvt[0].clone()))
+            }
+        };
+
+        let object = match (container_class, receiver) {
+            // the declaring class is already loaded since one of its methods is running
+            (Some(container_class), _) => {
+                let heap = jvm.heap.borrow();
+                match heap.loaded_classes_lookup.get(&container_class) {
+                    Some(class_id) => heap.loaded_classes[*class_id].class_object_id,
+                    None => {
+                        drop(heap);
+                        return Err(jvm.raise(VmError::Internal(format!(
+                            "synchronized method of unloaded class {}",
+                            container_class
+                        ))));
+                    }
                 }
-                JavaThrowable::Unhandled(_) => Err(ex),
-            },
+            }
+            (_, Some(JavaValue::Object(Some(id)))) | (_, Some(JavaValue::Array(id))) => id,
+            _ => return Err(jvm.throw_npe()),
+        };
+
+        let mut threads = jvm.threads.borrow_mut();
+        if threads.try_enter_monitor(object) {
+            let mut csf = jvm.call_stack_frames.borrow_mut();
+            csf.last_mut().unwrap().monitor = Some(object);
+            Ok(true)
+        } else {
+            threads.get_current_thread_mut().state = ThreadState::Blocked(object);
+            Ok(false)
         }
     }
 
@@ -94,50 +229,70 @@ impl InstructionExecutor {
             self.instruction_count.replace(ic + 1);
         }
 
+        if !self.enter_frame_monitor(jvm)? {
+            return Ok(());
+        }
+
         let (mut env, instruction, depth) = {
-            let is_native_frame = {
-                let csf = jvm.call_stack_frames.borrow();
-                let frame = csf.last().expect("no stack frame present");
-                frame.is_native_frame
+            let is_native_frame = match jvm.call_stack_frames.borrow().last() {
+                Some(frame) => frame.is_native_frame,
+                None => return Err(jvm.missing_stack_frame()),
             };
 
             if is_native_frame {
                 let return_value = {
-                    let (env, jni_name) = {
-                        let csf = jvm.call_stack_frames.borrow();
-                        let frame = csf.last().expect("no stack frame present");
-                        let env = self.get_native_step_env(jvm, frame);
-
-                        let method_name = &frame.container_method[0..frame.container_method.find('(').unwrap()];
-                        let jni_name = format!("Java_{}_{}", frame.container_class.replace("/", "_"), method_name);
-
-                        (env, jni_name)
-                    };
-
-                    let method = match jvm.classpath.get_native_method(&jni_name) {
-                        Some(method) => method,
-                        None => return Err(jvm.throw_exception("java/lang/UnsatisfiedLinkError", Some(&jni_name))),
-                    };
-                    method.invoke(&env)?
+                    let csf = jvm.call_stack_frames.borrow();
+                    let frame = csf.last().ok_or_else(|| jvm.missing_stack_frame())?;
+                    let env = self.get_native_step_env(jvm, frame);
+
+                    let descriptor_start = frame.container_method.find('(').unwrap_or(0);
+                    let method_name = &frame.container_method[0..descriptor_start];
+                    let method_descriptor = &frame.container_method[descriptor_start..];
+                    let method =
+                        match jvm.classpath.get_native_method(&frame.container_class, method_name, method_descriptor) {
+                            Some(method) => method,
+                            None => {
+                                let jni_name =
+                                    get_jni_long_name(&frame.container_class, method_name, method_descriptor);
+                                drop(csf);
+                                return Err(jvm.throw_exception("java/lang/UnsatisfiedLinkError", Some(&jni_name)));
+                            }
+                        };
+                    drop(csf);
+                    match method.invoke(&env) {
+                        Ok(return_value) => return_value,
+                        Err(JavaThrowable::Unhandled(ex)) => return Err(jvm.unwind_exception(ex)),
+                        Err(err) => return Err(err),
+                    }
                 };
 
+                // the native suspended the thread and will be invoked again once it is woken up
+                if !jvm.is_current_thread_runnable() {
+                    return Ok(());
+                }
+
+                jvm.pop_call_stack_frame();
                 let mut csf = jvm.call_stack_frames.borrow_mut();
-                csf.pop().unwrap();
-                csf.last_mut().expect("stack underflow").state.return_stack_value = return_value;
+                match csf.last_mut() {
+                    Some(caller_frame) => caller_frame.state.return_stack_value = return_value,
+                    None => {
+                        drop(csf);
+                        return Err(jvm.missing_stack_frame());
+                    }
+                }
 
                 return Ok(());
             } else {
-                let mut csf = jvm.call_stack_frames.borrow_mut();
+                let csf = jvm.call_stack_frames.borrow();
                 let depth = csf.len();
-                let frame = csf.last_mut().expect("no stack frame present");
+                let frame = csf.last().ok_or_else(|| jvm.missing_stack_frame())?;
                 let mut state = frame.state.clone();
                 let instruction = frame.instructions[frame.state.instruction_offset];
 
                 let instruction_address = frame.state.instruction_offset;
                 state.instruction_offset += 1;
-
-                if &frame.container_method == "loadClass(Ljava/lang/String;Z)Ljava/lang/Class;" {
-                    println!("0x{:x?}, depth={}, stack={}", instruction, depth, state.stack.jvm_debug(jvm));
+                if let Some(return_value) = state.return_stack_value.take() {
+                    state.stack.push(return_value);
                 }
 
                 let env = InstructionEnvironment {
@@ -154,14 +309,17 @@ impl InstructionExecutor {
         };
 
         let handler = INSTRUCTION_HANDLERS[instruction as usize];
-        let result = handler(&mut env);
-
-        let mut csf = jvm.call_stack_frames.borrow_mut();
-        if csf.len() == depth {
-            let last_frame = csf.last_mut().unwrap();
-            last_frame.state = env.state;
+        match handler(&mut env) {
+            Ok(_) => {
+                let mut csf = jvm.call_stack_frames.borrow_mut();
+                if csf.len() == depth {
+                    let last_frame = csf.last_mut().unwrap();
+                    last_frame.state = env.state;
+                }
+                Ok(())
+            }
+            Err(JavaThrowable::Unhandled(ex)) => Err(jvm.unwind_exception(ex)),
+            Err(err) => Err(err),
         }
-
-        result
     }
 }
@@ -1,5 +1,18 @@
 use crate::model::*;
-use crate::{util::*, Classpath, InvokeType, JniEnv};
+use crate::{
+    exec::{
+        clock::{Clock, RealClock},
+        gc::GcState,
+        handles::HandleTable,
+        identity_hash::IdentityHashGenerator,
+        memory::MemoryArena,
+        stdin::Stdin,
+        vfs::Vfs,
+    },
+    js::{JsHandleTable, PromiseTable},
+    util::*,
+    Classpath, InvokeType, JniEnv, StackTraceElement,
+};
 use classfile_parser::ClassAccessFlags;
 use classfile_parser::{
     attribute_info::code_attribute_parser,
@@ -8,16 +21,35 @@ use classfile_parser::{
     ClassFile,
 };
 use std::fmt::Write;
-use std::{cell::RefCell, collections::HashMap, usize};
+use std::io::Write as _;
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    usize,
+};
 
 use super::interpreter::InstructionExecutor;
+use super::thread::{ThreadScheduler, MAIN_THREAD};
 
 pub struct Jvm {
     pub executor: InstructionExecutor,
     pub classpath: Classpath,
     pub call_stack_frames: RefCell<Vec<CallStackFrame>>,
     pub heap: RefCell<Heap>,
-    pub initialized: bool,
+    pub threads: RefCell<ThreadScheduler>,
+    pub gc: RefCell<GcState>,
+    pub promises: Rc<RefCell<PromiseTable>>,
+    pub js_objects: RefCell<JsHandleTable>,
+    pub handles: RefCell<HandleTable>,
+    pub stdout: RefCell<Box<dyn std::io::Write>>,
+    pub stderr: RefCell<Box<dyn std::io::Write>>,
+    pub stdin: RefCell<Stdin>,
+    pub vfs: RefCell<Vfs>,
+    pub memory: RefCell<MemoryArena>,
+    pub clock: RefCell<Rc<dyn Clock>>,
+    pub initialized: Cell<bool>,
 }
 
 impl Jvm {
@@ -34,19 +66,164 @@ impl Jvm {
                 interned_string_map: HashMap::new(),
                 object_id_offset: 0,
                 main_thread_object: 0,
+                identity_hashes: IdentityHashGenerator::with_random_seed(),
             }),
-            initialized: false,
+            threads: RefCell::new(ThreadScheduler::new()),
+            gc: RefCell::new(GcState::new()),
+            promises: Rc::new(RefCell::new(PromiseTable::new())),
+            js_objects: RefCell::new(JsHandleTable::new()),
+            handles: RefCell::new(HandleTable::new()),
+            stdout: RefCell::new(default_output_sink(false)),
+            stderr: RefCell::new(default_output_sink(true)),
+            stdin: RefCell::new(Stdin::new()),
+            vfs: RefCell::new(Vfs::new()),
+            memory: RefCell::new(MemoryArena::new()),
+            clock: RefCell::new(Rc::new(RealClock::new())),
+            initialized: Cell::new(false),
+        }
+    }
+
+    // without a host to hand control to, the deadlines execution is suspended for are waited for here
+    pub fn execute_main(&self, class_name: &str, args: &[String]) -> RuntimeResult<i32> {
+        let mut result = self.start_main(class_name, args)?;
+        loop {
+            if let Some(status) = result {
+                return Ok(status);
+            }
+
+            let deadline = {
+                let threads = self.threads.borrow();
+                threads.get_next_deadline().filter(|_| !threads.is_awaiting_host())
+            };
+            match deadline {
+                Some(deadline) => {
+                    self.clock.borrow().wait_until(deadline as i64);
+                    result = self.resume()?;
+                }
+                None => {
+                    let error = self.raise(VmError::Internal(String::from(
+                        "execution was suspended waiting for the host, use start_main and resume instead",
+                    )));
+                    self.threads.borrow_mut().terminate_all();
+                    self.call_stack_frames.borrow_mut().clear();
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    // returns None when every thread is waiting for the host or for a deadline of a real time clock,
+    // execution continues with resume
+    pub fn start_main(&self, class_name: &str, args: &[String]) -> RuntimeResult<Option<i32>> {
+        let result = self.run_main(class_name, args).and_then(|_| self.executor.run_threads(self));
+        self.complete_execution(result)
+    }
+
+    pub fn resume(&self) -> RuntimeResult<Option<i32>> {
+        let result = self.executor.run_threads(self);
+        self.complete_execution(result)
+    }
+
+    fn complete_execution(&self, result: RuntimeResult<()>) -> RuntimeResult<Option<i32>> {
+        if let Err(JavaThrowable::Suspended) = result {
+            return Ok(None);
+        }
+
+        let result = match result.and_then(|_| self.destroy()) {
+            Ok(_) => {
+                let threads = self.threads.borrow();
+                Ok(match threads.get_thread(MAIN_THREAD).uncaught_exception {
+                    Some(_) => 1,
+                    None => 0,
+                })
+            }
+            Err(JavaThrowable::Unhandled(ex)) => {
+                self.report_uncaught_exception(ex);
+                Ok(1)
+            }
+            Err(JavaThrowable::Exit(status)) => Ok(status),
+            Err(err) => Err(err),
+        };
+
+        self.threads.borrow_mut().terminate_all();
+        self.call_stack_frames.borrow_mut().clear();
+        self.flush_output();
+        result.map(Some)
+    }
+
+    pub fn set_stdout(&self, sink: Box<dyn std::io::Write>) {
+        self.flush_output();
+        self.stdout.replace(sink);
+    }
+
+    pub fn set_stderr(&self, sink: Box<dyn std::io::Write>) {
+        self.flush_output();
+        self.stderr.replace(sink);
+    }
+
+    // a fixed seed makes identity hash codes, and with them the iteration order of identity based hash
+    // tables, reproducible across runs
+    pub fn set_identity_hash_seed(&self, seed: u64) {
+        self.heap.borrow_mut().identity_hashes = IdentityHashGenerator::new(seed);
+    }
+
+    pub fn set_clock(&self, clock: Rc<dyn Clock>) {
+        self.clock.replace(clock);
+    }
+
+    pub fn flush_output(&self) {
+        let _ = self.stdout.borrow_mut().flush();
+        let _ = self.stderr.borrow_mut().flush();
+    }
+
+    // calls made by the embedder run on the thread calling out to the host if Java code is running,
+    // and on the main thread otherwise
+    pub fn invoke_from_host<T>(&self, call: impl FnOnce(&JniEnv) -> RuntimeResult<T>) -> RuntimeResult<T> {
+        self.ensure_initialized()?;
+        call(&JniEnv::empty(self))
+    }
+
+    fn ensure_initialized(&self) -> RuntimeResult<()> {
+        if self.initialized.get() {
+            self.prepare_main_thread()
+        } else {
+            super::env::initialize(self)
         }
     }
 
+    fn run_main(&self, class_name: &str, args: &[String]) -> RuntimeResult<()> {
+        self.ensure_initialized()?;
+
+        let main_class = match self.classpath.get_classpath_entry(class_name) {
+            Some(file) => file,
+            None => return Err(self.throw_exception("java/lang/NoClassDefFoundError", Some(class_name))),
+        };
+        let main_method = match self.classpath.get_static_method(main_class, "main", "([Ljava/lang/String;)V") {
+            Some((_, method)) => method,
+            None => {
+                return Err(self.throw_exception(
+                    "java/lang/NoSuchMethodError",
+                    Some(&format!("{}.main([Ljava/lang/String;)V", class_name)),
+                ))
+            }
+        };
+
+        let mut frame = self.create_stack_frame(main_class, main_method)?;
+        frame.state.lvt[0] = JavaValue::Array(self.create_string_array(args)?);
+        self.push_call_stack_frame(frame);
+        Ok(())
+    }
+
     pub fn is_instance_of(&self, val: &JavaValue, compare_type: &str, null_is_instance: bool) -> RuntimeResult<bool> {
         let res = match val {
             JavaValue::Object(instance) => match instance {
                 Some(instance_id) => {
                     let class_id = {
                         let heap = self.heap.borrow();
-                        let obj = heap.object_heap_map.get(instance_id).expect("bad object ref");
-                        obj.class_id
+                        match heap.object_heap_map.get(instance_id) {
+                            Some(obj) => obj.class_id,
+                            None => return Err(self.invalid_reference(*instance_id)),
+                        }
                     };
                     self.is_assignable_from(compare_type, class_id)?
                 }
@@ -56,7 +233,9 @@ impl Jvm {
                 // TODO
                 true
             }
-            _ => panic!("invalid object"),
+            value => {
+                return Err(self.raise(VmError::Internal(format!("expecting reference, found {:?}", value))));
+            }
         };
         Ok(res)
     }
@@ -68,9 +247,15 @@ impl Jvm {
             get_constant_string(&cls.const_pool, method.name_index).clone() + container_method_descriptor;
 
         if method.access_flags.contains(MethodAccessFlags::NATIVE) {
-            let container_class = container_class.replace("$", "_00024");
-
-            let md = MethodDescriptor::new(container_method_descriptor).expect("bad method descriptor");
+            let md = match MethodDescriptor::new(container_method_descriptor) {
+                Ok(md) => md,
+                Err(_) => {
+                    return Err(self.raise(VmError::MalformedClassFile {
+                        class_name: container_class,
+                        message: format!("invalid method descriptor {}", container_method_descriptor),
+                    }))
+                }
+            };
             let mut lvt_len = md
                 .argument_types
                 .iter()
@@ -103,6 +288,7 @@ impl Jvm {
                     return_stack_value: None,
                 },
                 metadata: None,
+                monitor: None,
             })
         } else if method.access_flags.contains(MethodAccessFlags::ABSTRACT) {
             return Err(self.throw_exception(
@@ -110,7 +296,20 @@ impl Jvm {
                 Some(&format!("{}.{}", container_class, container_method)),
             ));
         } else {
-            let (_, code_attribute) = code_attribute_parser(&method.attributes[0].info).unwrap();
+            let code_attribute = match method
+                .attributes
+                .iter()
+                .find(|attribute| get_constant_string(&cls.const_pool, attribute.attribute_name_index) == "Code")
+                .and_then(|attribute| code_attribute_parser(&attribute.info).ok())
+            {
+                Some((_, code_attribute)) => code_attribute,
+                None => {
+                    return Err(self.raise(VmError::MalformedClassFile {
+                        class_name: container_class,
+                        message: format!("missing or invalid Code attribute in {}", container_method),
+                    }))
+                }
+            };
             let instructions = code_attribute.code.clone();
 
             Ok(CallStackFrame {
@@ -133,6 +332,7 @@ impl Jvm {
                     return_stack_value: None,
                 },
                 metadata: Some(code_attribute),
+                monitor: None,
             })
         }
     }
@@ -142,6 +342,24 @@ impl Jvm {
         csf.push(frame);
     }
 
+    pub fn pop_call_stack_frame(&self) -> Option<CallStackFrame> {
+        let frame = self.call_stack_frames.borrow_mut().pop();
+        if let Some(object) = frame.as_ref().and_then(|frame| frame.monitor) {
+            self.threads.borrow_mut().exit_monitor(object);
+        }
+        frame
+    }
+
+    pub fn push_unwind_barrier(&self, depth: usize) {
+        let mut threads = self.threads.borrow_mut();
+        threads.get_current_thread_mut().unwind_barriers.push(depth);
+    }
+
+    pub fn pop_unwind_barrier(&self) {
+        let mut threads = self.threads.borrow_mut();
+        threads.get_current_thread_mut().unwind_barriers.pop();
+    }
+
     pub fn get_stack_depth(&self) -> usize {
         let csf = self.call_stack_frames.borrow();
         csf.len()
@@ -172,7 +390,7 @@ impl Jvm {
                 Ok(id)
             }
             None => {
-                let mut loaded_class = match cls.chars().next().unwrap() {
+                let mut loaded_class = match cls.chars().next().unwrap_or_default() {
                     '[' => JavaClass {
                         java_type: String::from(cls),
                         class_id: 0,
@@ -188,19 +406,9 @@ impl Jvm {
                         ],
                         is_initialized: true,
                     },
-                    x => match x {
-                        'B' | 'S' | 'I' | 'J' | 'F' | 'D' | 'C' | 'Z' => JavaClass {
-                            java_type: String::from(match x {
-                                'B' => "byte",
-                                'S' => "short",
-                                'I' => "int",
-                                'J' => "long",
-                                'F' => "float",
-                                'D' => "double",
-                                'C' => "char",
-                                'Z' => "boolean",
-                                _ => panic!(),
-                            }),
+                    x => match get_primitive_type_name(x) {
+                        Some(type_name) if cls.len() == 1 => JavaClass {
+                            java_type: String::from(type_name),
                             class_id: 0,
                             access_flags: ClassAccessFlags::PUBLIC,
                             superclass_id: None,
@@ -236,10 +444,7 @@ impl Jvm {
                             for field in &declared_fields {
                                 static_fields.insert(
                                     get_constant_string(&class_file.const_pool, field.name_index).clone(),
-                                    JavaValue::default(get_constant_string(
-                                        &class_file.const_pool,
-                                        field.descriptor_index,
-                                    )),
+                                    self.get_default_field_value(class_file, field)?,
                                 );
                             }
 
@@ -388,8 +593,75 @@ impl Jvm {
                 break 'l superclass == "java/lang/Object";
             }
             let superclass_name = get_constant_string(&cls.const_pool, cls.super_class);
-            let class_id = heap.loaded_classes_lookup.get(superclass_name).expect("invalid superclass");
-            current_class = &heap.loaded_classes[*class_id];
+            let class_id = match heap.loaded_classes_lookup.get(superclass_name) {
+                Some(class_id) => *class_id,
+                None => {
+                    return Err(self.raise(VmError::Internal(format!("superclass {} is not loaded", superclass_name))))
+                }
+            };
+            current_class = &heap.loaded_classes[class_id];
+        })
+    }
+
+    pub fn get_stack_trace(&self) -> Vec<StackTraceElement> {
+        let csf = self.call_stack_frames.borrow();
+        csf.iter()
+            .rev()
+            .map(|frame| StackTraceElement {
+                class_name: frame.container_class.clone(),
+                method: frame.container_method.clone(),
+            })
+            .collect()
+    }
+
+    // recoverable errors are thrown as the matching Java error, internal ones abort execution
+    pub fn raise(&self, error: VmError) -> JavaThrowable {
+        match error {
+            VmError::Linkage {
+                error_class,
+                message,
+            } => self.throw_exception(&error_class, Some(&message)),
+            VmError::MalformedClassFile {
+                class_name,
+                message,
+            } => self.throw_exception("java/lang/VerifyError", Some(&format!("{}: {}", class_name, message))),
+            VmError::Internal(_) => {
+                log_error(&format!("Fatal error: {}", error));
+                JavaThrowable::Fatal(error, self.get_stack_trace())
+            }
+            // no Java code can run to create an exception, the VM itself is left untouched
+            VmError::IllegalHostCall(_) => JavaThrowable::Fatal(error, Vec::new()),
+        }
+    }
+
+    pub fn invalid_reference(&self, reference: usize) -> JavaThrowable {
+        self.raise(VmError::Internal(format!("invalid heap reference {}", reference)))
+    }
+
+    pub fn missing_stack_frame(&self) -> JavaThrowable {
+        self.raise(VmError::Internal(String::from("no stack frame present")))
+    }
+
+    // drops the JavaScript objects referenced by JSObject instances that have been collected
+    pub fn sweep_js_objects(&self) {
+        let heap = self.heap.borrow();
+        self.js_objects.borrow_mut().sweep(|owner| heap.object_heap_map.contains_key(&owner));
+    }
+
+    pub fn sweep_handles(&self) {
+        let heap = self.heap.borrow();
+        self.handles
+            .borrow_mut()
+            .sweep(|id| heap.object_heap_map.contains_key(&id) || heap.array_heap_map.contains_key(&id));
+    }
+
+    fn get_default_field_value(&self, cls: &ClassFile, field: &FieldInfo) -> RuntimeResult<JavaValue> {
+        let descriptor = get_constant_string(&cls.const_pool, field.descriptor_index);
+        JavaValue::default(descriptor).map_err(|_| {
+            self.raise(VmError::MalformedClassFile {
+                class_name: get_constant_string(&cls.const_pool, cls.this_class).clone(),
+                message: format!("invalid field descriptor {}", descriptor),
+            })
         })
     }
 
@@ -398,11 +670,15 @@ impl Jvm {
     }
 
     pub fn throw_exception_ref(&self, reference: usize) -> JavaThrowable {
-        let (exception_class, exception_class_id) = {
+        let exception_class = {
             let heap = self.heap.borrow();
-            let obj = &heap.object_heap_map.get(&reference).expect("expecting object ref");
-            let cls = &heap.loaded_classes[obj.class_id];
-            (cls.java_type.clone(), obj.class_id)
+            match heap.object_heap_map.get(&reference) {
+                Some(obj) => heap.loaded_classes[obj.class_id].java_type.clone(),
+                None => {
+                    drop(heap);
+                    return self.invalid_reference(reference);
+                }
+            }
         };
 
         log_error(&format!("Exception thrown: {}", exception_class));
@@ -410,8 +686,7 @@ impl Jvm {
         let stacktrace = {
             let csf = self.call_stack_frames.borrow();
             let mut stacktrace = String::new();
-            for i in 0..csf.len() - 1 {
-                let frame = &csf[csf.len() - i - 1];
+            for frame in csf.iter().rev().take(csf.len().saturating_sub(1)) {
                 let source = match frame.is_native_frame {
                     true => "(Native Method)",
                     false => "(Unknown Source)",
@@ -428,57 +703,118 @@ impl Jvm {
             }
             stacktrace
         };
+        {
+            let mut heap = self.heap.borrow_mut();
+            if let Some(obj) = heap.object_heap_map.get_mut(&reference) {
+                if obj.get_internal_metadata("stack_trace").is_none() {
+                    obj.set_internal_metadata("stack_trace", InternalMetadata::Text(stacktrace));
+                }
+            }
+        }
 
-        loop {
-            let mut csf = self.call_stack_frames.borrow_mut();
-            if csf.len() == 1 {
-                break;
+        self.unwind_exception(reference)
+    }
+
+    // pops frames until one of them catches the exception, stopping at the innermost
+    // frame that was entered from native code
+    pub fn unwind_exception(&self, reference: usize) -> JavaThrowable {
+        let exception_class_id = {
+            let heap = self.heap.borrow();
+            match heap.object_heap_map.get(&reference) {
+                Some(obj) => obj.class_id,
+                None => {
+                    drop(heap);
+                    return self.invalid_reference(reference);
+                }
             }
-            let top_frame = csf.last_mut().unwrap();
-            'b: {
-                if let Some(metadata) = top_frame.metadata.as_ref() {
-                    for exception_item in &metadata.exception_table {
-                        if top_frame.state.instruction_offset >= exception_item.start_pc as usize
-                            && top_frame.state.instruction_offset < exception_item.end_pc as usize
-                        {
-                            if exception_item.catch_type == 0 {
-                                // TODO: finally blocks
-                                break 'b;
-                            }
-                            let container_class =
-                                self.classpath.get_classpath_entry(&top_frame.container_class).unwrap();
-                            let catch_type =
-                                get_constant_string(&container_class.const_pool, exception_item.catch_type);
-                            if self.is_assignable_from(catch_type, exception_class_id).unwrap() {
-                                println!(
-                                    "Catching exception in function: {}.{}",
-                                    top_frame.container_class, top_frame.container_method
-                                );
-                                top_frame.state.instruction_offset = exception_item.handler_pc as usize;
-                                return JavaThrowable::Handled(reference);
-                            }
-                        }
+        };
+        // without a barrier the exception may unwind the whole call stack
+        let barrier = {
+            let threads = self.threads.borrow();
+            threads.get_current_thread().unwind_barriers.last().copied().unwrap_or(0)
+        };
+
+        let mut is_top_frame = true;
+        loop {
+            let handlers = {
+                let csf = self.call_stack_frames.borrow();
+                match csf.last() {
+                    Some(top_frame) if csf.len() > barrier => self.get_exception_handlers(top_frame, is_top_frame),
+                    _ => break,
+                }
+            };
+            let handlers = match handlers {
+                Ok(handlers) => handlers,
+                Err(error) => return self.raise(error),
+            };
+
+            for (catch_type, handler_pc) in handlers {
+                let is_caught = match catch_type {
+                    Some(catch_type) => match self.is_assignable_from(&catch_type, exception_class_id) {
+                        Ok(is_caught) => is_caught,
+                        Err(err) => return err,
+                    },
+                    None => true,
+                };
+                if is_caught {
+                    let mut csf = self.call_stack_frames.borrow_mut();
+                    if let Some(top_frame) = csf.last_mut() {
+                        let max_stack = top_frame.metadata.as_ref().map_or(0, |metadata| metadata.max_stack);
+                        top_frame.state.instruction_offset = handler_pc;
+                        top_frame.state.return_stack_value = None;
+                        top_frame.state.stack = JavaValueVec::with_capacity(max_stack as usize);
+                        top_frame.state.stack.push(JavaValue::Object(Some(reference)));
                     }
+                    return JavaThrowable::Handled(reference);
                 }
             }
-            csf.pop().unwrap();
+            is_top_frame = false;
+            self.pop_call_stack_frame();
         }
 
-        let detail_str = {
-            let env = JniEnv::empty(self);
-            let detail_field = env.get_field(reference, "detailMessage");
+        JavaThrowable::Unhandled(reference)
+    }
 
-            detail_field.as_object().unwrap().map(|id| env.get_string(id))
+    // the catch types and handler addresses of the exception table entries covering the current instruction
+    fn get_exception_handlers(
+        &self,
+        frame: &CallStackFrame,
+        is_top_frame: bool,
+    ) -> Result<Vec<(Option<String>, usize)>, VmError> {
+        let metadata = match frame.metadata.as_ref() {
+            Some(metadata) => metadata,
+            None => return Ok(Vec::new()),
+        };
+        let container_class = match self.classpath.get_classpath_entry(&frame.container_class) {
+            Some(class_file) => class_file,
+            None => return Err(VmError::Internal(format!("class {} is not on the classpath", frame.container_class))),
         };
 
-        let exception_class = exception_class.replace("/", ".");
-        if let Some(detail) = detail_str {
-            log_error(&format!("Exception in thread \"main\" {}: {}\n{}", exception_class, detail, stacktrace));
-        } else {
-            log_error(&format!("Exception in thread \"main\" {}\n{}", exception_class, stacktrace));
+        // frames below the top one are suspended just past their invoke instruction
+        let pc = match is_top_frame {
+            true => frame.state.instruction_offset,
+            false => frame.state.instruction_offset.saturating_sub(1),
+        };
+        let mut handlers = Vec::new();
+        for exception_item in &metadata.exception_table {
+            if pc < exception_item.start_pc as usize || pc >= exception_item.end_pc as usize {
+                continue;
+            }
+            let catch_type = match exception_item.catch_type {
+                0 => None,
+                catch_type => match get_class_constant_name(&container_class.const_pool, catch_type) {
+                    Some(catch_type) => Some(catch_type.clone()),
+                    None => {
+                        return Err(VmError::MalformedClassFile {
+                            class_name: frame.container_class.clone(),
+                            message: format!("invalid catch type {}", catch_type),
+                        })
+                    }
+                },
+            };
+            handlers.push((catch_type, exception_item.handler_pc as usize));
         }
-
-        JavaThrowable::Unhandled(reference)
+        Ok(handlers)
     }
 
     pub fn throw_exception(&self, exception_class: &str, message: Option<&str>) -> JavaThrowable {
@@ -488,12 +824,21 @@ impl Jvm {
         }
 
         let env = JniEnv::empty(self);
-        let cid = env.get_class_id(exception_class).unwrap();
-        let ex_ref = env.new_instance(cid).unwrap();
+        let ex_ref = match self.create_exception(&env, exception_class, message) {
+            Ok(ex_ref) => ex_ref,
+            Err(err) => return err,
+        };
+
+        self.throw_exception_ref(ex_ref)
+    }
+
+    fn create_exception(&self, env: &JniEnv, exception_class: &str, message: Option<&str>) -> RuntimeResult<usize> {
+        let cid = env.get_class_id(exception_class)?;
+        let ex_ref = env.new_instance(cid)?;
 
         match message {
             Some(msg_str) => {
-                let message_internal_str = env.new_string(msg_str);
+                let message_internal_str = env.new_string(msg_str)?;
                 env.invoke_instance_method(
                     InvokeType::Special,
                     ex_ref,
@@ -501,19 +846,19 @@ impl Jvm {
                     "<init>",
                     "(Ljava/lang/String;)V",
                     &[JavaValue::Object(Some(message_internal_str))],
-                )
-                .unwrap();
+                )?;
             }
             None => {
-                env.invoke_instance_method(InvokeType::Special, ex_ref, cid, "<init>", "()V", &[]).unwrap();
+                env.invoke_instance_method(InvokeType::Special, ex_ref, cid, "<init>", "()V", &[])?;
             }
         }
 
-        self.throw_exception_ref(ex_ref)
+        Ok(ex_ref)
     }
 
     pub fn new_instance(&self, root_class_id: usize) -> RuntimeResult<JavaObject> {
         let mut instance_fields = HashMap::new();
+        let mut has_hidden_fields = false;
 
         let mut class_name = {
             let heap = self.heap.borrow();
@@ -526,10 +871,13 @@ impl Jvm {
             let declared_fields: Vec<&FieldInfo> =
                 cls.fields.iter().filter(|field| !field.access_flags.contains(FieldAccessFlags::STATIC)).collect();
             for field in &declared_fields {
-                instance_fields.insert(
-                    get_constant_string(&cls.const_pool, field.name_index).clone(),
-                    JavaValue::default(get_constant_string(&cls.const_pool, field.descriptor_index)),
-                );
+                // subclasses are visited first, so a field that already exists is hidden by a subclass field
+                let mut key = get_constant_string(&cls.const_pool, field.name_index).clone();
+                if instance_fields.contains_key(&key) {
+                    key = get_hidden_field_key(class_name, &key);
+                    has_hidden_fields = true;
+                }
+                instance_fields.insert(key, self.get_default_field_value(cls, field)?);
             }
 
             if cls.super_class == 0 {
@@ -543,9 +891,53 @@ impl Jvm {
             class_id: root_class_id,
             instance_fields,
             internal_metadata: HashMap::new(),
+            has_hidden_fields,
+            identity_hash: None,
         })
     }
 
+    // resolves the key of a field referenced through the given class, which is the class itself or one of
+    // its superclasses declaring the field
+    pub fn get_instance_field_key<'a>(&self, instance: &JavaObject, class_name: &str, name: &'a str) -> Cow<'a, str> {
+        if !instance.has_hidden_fields {
+            return Cow::Borrowed(name);
+        }
+
+        let mut cls = self.classpath.get_classpath_entry(class_name);
+        while let Some(class_file) = cls {
+            let is_declared = class_file.fields.iter().any(|field| {
+                !field.access_flags.contains(FieldAccessFlags::STATIC)
+                    && get_constant_string(&class_file.const_pool, field.name_index) == name
+            });
+            if is_declared {
+                return instance
+                    .get_field_key(get_constant_string(&class_file.const_pool, class_file.this_class), name);
+            }
+            cls = match class_file.super_class {
+                0 => None,
+                super_class => {
+                    self.classpath.get_classpath_entry(get_constant_string(&class_file.const_pool, super_class))
+                }
+            };
+        }
+        Cow::Borrowed(name)
+    }
+
+    // the identity hash is generated on first use and kept with the object from then on
+    pub fn get_identity_hash(&self, id: usize) -> RuntimeResult<i32> {
+        let mut heap = self.heap.borrow_mut();
+        let heap = &mut *heap;
+        let identity_hash = match heap.object_heap_map.get_mut(&id) {
+            Some(obj) => &mut obj.identity_hash,
+            None => match heap.array_heap_map.get_mut(&id) {
+                Some(array) => &mut array.identity_hash,
+                None => return Err(self.invalid_reference(id)),
+            },
+        };
+        let identity_hashes = &mut heap.identity_hashes;
+        Ok(*identity_hash.get_or_insert_with(|| identity_hashes.next_hash()))
+    }
+
     pub fn heap_store_instance(&self, instance: JavaObject) -> usize {
         let mut heap = self.heap.borrow_mut();
         let idx = heap.object_id_offset;
@@ -568,6 +960,7 @@ impl Jvm {
         let arr = JavaArray {
             array_type,
             values,
+            identity_hash: None,
         };
 
         self.heap_store_array(arr)
@@ -592,32 +985,58 @@ impl Jvm {
         let arr = JavaArray {
             array_type,
             values,
+            identity_hash: None,
         };
 
         self.heap_store_array(arr)
     }
 
-    pub fn create_string_object(&self, inner: &str, intern: bool) -> usize {
+    pub fn create_string_array(&self, strings: &[String]) -> RuntimeResult<usize> {
+        let string_class = self.ensure_class_loaded("java/lang/String", true)?;
+        let values = strings
+            .iter()
+            .map(|string| Ok(JavaValue::Object(Some(self.create_string_object(string, true)?))))
+            .collect::<RuntimeResult<_>>()?;
+
+        Ok(self.create_constant_array(JavaArrayType::Object(string_class), values))
+    }
+
+    pub fn create_string_object(&self, inner: &str, intern: bool) -> RuntimeResult<usize> {
         // let owned = String::from(inner);
         if intern {
             let heap = self.heap.borrow();
             if let Some(id) = heap.interned_string_map.get(inner) {
-                return *id;
+                return Ok(*id);
             }
         }
 
-        let string_class = self.ensure_class_loaded("java/lang/String", true).unwrap();
-        let mut instance = self.new_instance(string_class).unwrap();
+        let string_class = self.ensure_class_loaded("java/lang/String", true)?;
+        let mut instance = self.new_instance(string_class)?;
 
         let chars: Vec<JavaValue> = inner.encode_utf16().into_iter().map(JavaValue::Char).collect();
         let array_id = self.create_constant_array(JavaArrayType::Char, chars);
-        instance.set_field(self, "value", JavaValue::Array(array_id)).unwrap();
+        instance.set_field(self, "value", JavaValue::Array(array_id))?;
 
         let id = self.heap_store_instance(instance);
         if intern {
             let mut heap = self.heap.borrow_mut();
             heap.interned_string_map.insert(String::from(inner), id);
         }
-        id
+        Ok(id)
+    }
+}
+
+fn get_primitive_type_name(descriptor: char) -> Option<&'static str> {
+    match descriptor {
+        'B' => Some("byte"),
+        'S' => Some("short"),
+        'I' => Some("int"),
+        'J' => Some("long"),
+        'F' => Some("float"),
+        'D' => Some("double"),
+        'C' => Some("char"),
+        'Z' => Some("boolean"),
+        'V' => Some("void"),
+        _ => None,
     }
 }
@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, convert::TryFrom};
+
+// the first address handed out, so that small offsets from a null base never resolve to a block
+const BASE_ADDRESS: u64 = 0x1000;
+const ALIGNMENT: u64 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryError {
+    OutOfMemory,
+    InvalidAddress(u64),
+}
+
+// the off-heap memory handed out by sun.misc.Unsafe, addresses are VM-managed and every access is
+// checked against the bounds of the block it falls into
+pub struct MemoryArena {
+    blocks: BTreeMap<u64, Vec<u8>>,
+    next_address: u64,
+}
+
+impl MemoryArena {
+    pub fn new() -> MemoryArena {
+        MemoryArena {
+            blocks: BTreeMap::new(),
+            next_address: BASE_ADDRESS,
+        }
+    }
+
+    pub fn allocate(&mut self, size: u64) -> Result<u64, MemoryError> {
+        let length = usize::try_from(size).map_err(|_| MemoryError::OutOfMemory)?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(length).map_err(|_| MemoryError::OutOfMemory)?;
+        data.resize(length, 0);
+
+        // blocks are separated by at least one unused aligned slot so that overruns never reach the next one
+        let address = self.next_address;
+        self.next_address = size
+            .checked_add(2 * ALIGNMENT - 1)
+            .map(|size| size / ALIGNMENT * ALIGNMENT)
+            .and_then(|size| address.checked_add(size))
+            .ok_or(MemoryError::OutOfMemory)?;
+        self.blocks.insert(address, data);
+        Ok(address)
+    }
+
+    // the contents are preserved up to the smaller of the old and new size
+    pub fn reallocate(&mut self, address: u64, size: u64) -> Result<u64, MemoryError> {
+        if !self.blocks.contains_key(&address) {
+            return Err(MemoryError::InvalidAddress(address));
+        }
+        let new_address = self.allocate(size)?;
+        let old = self.blocks.remove(&address).unwrap();
+        let new = self.blocks.get_mut(&new_address).unwrap();
+        let length = old.len().min(new.len());
+        new[..length].copy_from_slice(&old[..length]);
+        Ok(new_address)
+    }
+
+    pub fn free(&mut self, address: u64) -> Result<(), MemoryError> {
+        match self.blocks.remove(&address) {
+            Some(_) => Ok(()),
+            None => Err(MemoryError::InvalidAddress(address)),
+        }
+    }
+
+    fn get_block_range(&mut self, address: u64, length: usize) -> Result<&mut [u8], MemoryError> {
+        let (start, data) = match self.blocks.range_mut(..=address).next_back() {
+            Some(block) => block,
+            None => return Err(MemoryError::InvalidAddress(address)),
+        };
+        let offset = (address - start) as usize;
+        match offset.checked_add(length).and_then(move |end| data.get_mut(offset..end)) {
+            Some(range) => Ok(range),
+            None => Err(MemoryError::InvalidAddress(address)),
+        }
+    }
+
+    pub fn read(&mut self, address: u64, buf: &mut [u8]) -> Result<(), MemoryError> {
+        if !buf.is_empty() {
+            buf.copy_from_slice(self.get_block_range(address, buf.len())?);
+        }
+        Ok(())
+    }
+
+    pub fn write(&mut self, address: u64, data: &[u8]) -> Result<(), MemoryError> {
+        if !data.is_empty() {
+            self.get_block_range(address, data.len())?.copy_from_slice(data);
+        }
+        Ok(())
+    }
+
+    pub fn fill(&mut self, address: u64, length: usize, value: u8) -> Result<(), MemoryError> {
+        if length > 0 {
+            self.get_block_range(address, length)?.fill(value);
+        }
+        Ok(())
+    }
+
+    // the ranges may overlap and may lie in different blocks
+    pub fn copy(&mut self, from: u64, to: u64, length: usize) -> Result<(), MemoryError> {
+        if length > 0 {
+            let data = self.get_block_range(from, length)?.to_vec();
+            self.write(to, &data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryArena, MemoryError};
+
+    #[test]
+    fn rejects_accesses_outside_of_blocks() {
+        let mut arena = MemoryArena::new();
+        let first = arena.allocate(8).unwrap();
+        let second = arena.allocate(8).unwrap();
+        assert!(second > first + 8);
+
+        arena.write(first, &[1; 8]).unwrap();
+        assert_eq!(arena.write(first + 4, &[1; 8]), Err(MemoryError::InvalidAddress(first + 4)));
+        assert_eq!(arena.write(first + 8, &[1]), Err(MemoryError::InvalidAddress(first + 8)));
+        assert_eq!(arena.read(first - 1, &mut [0]), Err(MemoryError::InvalidAddress(first - 1)));
+        assert_eq!(arena.fill(second, 9, 0), Err(MemoryError::InvalidAddress(second)));
+        assert_eq!(arena.copy(first, second, 16), Err(MemoryError::InvalidAddress(first)));
+
+        let mut buf = [0; 8];
+        arena.read(second, &mut buf).unwrap();
+        assert_eq!(buf, [0; 8]);
+
+        arena.free(first).unwrap();
+        assert_eq!(arena.read(first, &mut buf), Err(MemoryError::InvalidAddress(first)));
+        assert_eq!(arena.free(first), Err(MemoryError::InvalidAddress(first)));
+        assert_eq!(arena.allocate(u64::MAX), Err(MemoryError::OutOfMemory));
+    }
+
+    #[test]
+    fn reallocate_preserves_contents() {
+        let mut arena = MemoryArena::new();
+        let address = arena.allocate(4).unwrap();
+        arena.write(address, &[1, 2, 3, 4]).unwrap();
+
+        let grown = arena.reallocate(address, 6).unwrap();
+        let mut buf = [0xff; 6];
+        arena.read(grown, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4, 0, 0]);
+        assert_eq!(arena.read(address, &mut [0]), Err(MemoryError::InvalidAddress(address)));
+
+        let shrunk = arena.reallocate(grown, 2).unwrap();
+        let mut buf = [0; 2];
+        arena.read(shrunk, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(arena.read(shrunk, &mut [0; 3]), Err(MemoryError::InvalidAddress(shrunk)));
+        assert_eq!(arena.reallocate(address, 8), Err(MemoryError::InvalidAddress(address)));
+    }
+}
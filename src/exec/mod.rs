@@ -1,3 +1,11 @@
+pub mod clock;
 pub mod env;
+pub mod gc;
+pub mod handles;
+pub mod identity_hash;
 pub mod interpreter;
 pub mod jvm;
+pub mod memory;
+pub mod stdin;
+pub mod thread;
+pub mod vfs;
@@ -0,0 +1,162 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+};
+
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::js;
+
+// the most bytes a single read takes from the source, however many the caller asked for
+pub const MAX_READ_LENGTH: usize = 8192;
+
+pub enum StdinRead {
+    Data(Vec<u8>),
+    Eof,
+    // no input is available yet, the promise settles with more input
+    Pending(js_sys::Promise),
+}
+
+// the source of System.in, either a Rust reader, a JavaScript callback pulled for more input or a
+// queue the embedder pushes input into
+pub struct Stdin {
+    buffer: VecDeque<u8>,
+    is_closed: bool,
+    reader: Option<Box<dyn Read>>,
+    callback: Option<js_sys::Function>,
+    // resolvers of the promises readers waiting for input are parked on
+    waiters: Vec<js_sys::Function>,
+}
+
+impl Stdin {
+    pub fn new() -> Stdin {
+        Stdin {
+            buffer: VecDeque::new(),
+            is_closed: false,
+            #[cfg(target_arch = "wasm32")]
+            reader: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            reader: Some(Box::new(io::stdin())),
+            callback: None,
+            waiters: Vec::new(),
+        }
+    }
+
+    pub fn set_reader(&mut self, reader: Box<dyn Read>) {
+        self.reader = Some(reader);
+        self.callback = None;
+        self.is_closed = false;
+    }
+
+    // the callback is invoked with the maximum number of bytes wanted and returns a string, bytes, null
+    // at the end of input or a promise of one of these
+    pub fn set_callback(&mut self, callback: js_sys::Function) {
+        self.callback = Some(callback);
+        self.reader = None;
+        self.is_closed = false;
+        self.wake();
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend(data);
+        self.wake();
+    }
+
+    pub fn close(&mut self) {
+        self.is_closed = true;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        for resolve in self.waiters.drain(..) {
+            let _ = resolve.call1(&JsValue::null(), &js_sys::Uint8Array::new_with_length(0));
+        }
+    }
+
+    pub fn available(&self) -> usize {
+        self.buffer.len()
+    }
+
+    // adds the value a callback or its promise produced to the buffered input
+    pub fn accept(&mut self, value: &JsValue) {
+        if value.is_null() || value.is_undefined() {
+            self.is_closed = true;
+        } else if let Some(str) = value.as_string() {
+            self.buffer.extend(str.as_bytes());
+        } else {
+            self.buffer.extend(js_sys::Uint8Array::new(value).to_vec());
+        }
+    }
+
+    pub fn read(&mut self, max_length: usize) -> Result<StdinRead, String> {
+        let max_length = max_length.min(MAX_READ_LENGTH);
+        loop {
+            if !self.buffer.is_empty() || max_length == 0 {
+                let length = max_length.min(self.buffer.len());
+                return Ok(StdinRead::Data(self.buffer.drain(..length).collect()));
+            }
+            if self.is_closed {
+                return Ok(StdinRead::Eof);
+            }
+
+            if let Some(reader) = &mut self.reader {
+                let mut buf = vec![0; max_length];
+                match reader.read(&mut buf) {
+                    Ok(0) => self.is_closed = true,
+                    Ok(length) => self.buffer.extend(&buf[..length]),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                    Err(err) => return Err(err.to_string()),
+                }
+            } else if let Some(callback) = &self.callback {
+                let value = match callback.call1(&JsValue::null(), &JsValue::from(max_length as u32)) {
+                    Ok(value) => value,
+                    Err(err) => return Err(js::get_error_message(&err)),
+                };
+                match value.dyn_into::<js_sys::Promise>() {
+                    Ok(promise) => return Ok(StdinRead::Pending(promise)),
+                    Err(value) => self.accept(&value),
+                }
+            } else {
+                let promise = js_sys::Promise::new(&mut |resolve, _| self.waiters.push(resolve));
+                return Ok(StdinRead::Pending(promise));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read_data(stdin: &mut Stdin, max_length: usize) -> Vec<u8> {
+        match stdin.read(max_length) {
+            Ok(StdinRead::Data(data)) => data,
+            _ => panic!("expected data"),
+        }
+    }
+
+    #[test]
+    fn read_is_bounded() {
+        let mut stdin = Stdin::new();
+        stdin.set_reader(Box::new(Cursor::new(vec![7; MAX_READ_LENGTH * 2 + 1])));
+
+        assert_eq!(read_data(&mut stdin, usize::MAX).len(), MAX_READ_LENGTH);
+        assert_eq!(read_data(&mut stdin, 16), vec![7; 16]);
+        assert_eq!(read_data(&mut stdin, usize::MAX).len(), MAX_READ_LENGTH - 15);
+        assert!(matches!(stdin.read(usize::MAX), Ok(StdinRead::Eof)));
+    }
+
+    #[test]
+    fn pushed_input_is_read_in_order() {
+        let mut stdin = Stdin::new();
+        stdin.set_reader(Box::new(Cursor::new(Vec::new())));
+        stdin.push(b"hello");
+        stdin.push(b" world");
+
+        assert_eq!(stdin.available(), 11);
+        assert_eq!(read_data(&mut stdin, 5), b"hello");
+        assert_eq!(read_data(&mut stdin, usize::MAX), b" world");
+        assert!(matches!(stdin.read(1), Ok(StdinRead::Eof)));
+    }
+}
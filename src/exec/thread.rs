@@ -0,0 +1,582 @@
+use super::{env::JniEnv, jvm::Jvm};
+use crate::{
+    model::{CallStackFrame, InternalMetadata, JavaThrowable, JavaValue, RuntimeResult, VmError},
+    util::log_error,
+    InvokeType,
+};
+use std::{collections::HashMap, io::Write};
+
+pub const MAIN_THREAD: usize = 0;
+
+// the JVMTI thread state bits stored in Thread.threadStatus and mapped by sun.misc.VM.toThreadState
+const JVMTI_THREAD_STATE_ALIVE: i32 = 0x0001;
+const JVMTI_THREAD_STATE_TERMINATED: i32 = 0x0002;
+const JVMTI_THREAD_STATE_RUNNABLE: i32 = 0x0004;
+const JVMTI_THREAD_STATE_WAITING_INDEFINITELY: i32 = 0x0010;
+const JVMTI_THREAD_STATE_WAITING_WITH_TIMEOUT: i32 = 0x0020;
+const JVMTI_THREAD_STATE_SLEEPING: i32 = 0x0040;
+const JVMTI_THREAD_STATE_WAITING: i32 = 0x0080;
+const JVMTI_THREAD_STATE_IN_OBJECT_WAIT: i32 = 0x0100;
+const JVMTI_THREAD_STATE_PARKED: i32 = 0x0200;
+const JVMTI_THREAD_STATE_BLOCKED_ON_MONITOR_ENTER: i32 = 0x0400;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThreadState {
+    Runnable,
+    Blocked(usize),
+    Waiting {
+        object: usize,
+        deadline: Option<u64>,
+    },
+    Sleeping(u64),
+    Parked(Option<u64>),
+    AwaitingHost(usize),
+    Terminated,
+}
+
+impl ThreadState {
+    pub fn get_thread_status(&self) -> i32 {
+        let timeout = |deadline: &Option<u64>| match deadline {
+            Some(_) => JVMTI_THREAD_STATE_WAITING_WITH_TIMEOUT,
+            None => JVMTI_THREAD_STATE_WAITING_INDEFINITELY,
+        };
+        let waiting = JVMTI_THREAD_STATE_ALIVE | JVMTI_THREAD_STATE_WAITING;
+        match self {
+            ThreadState::Runnable | ThreadState::AwaitingHost(_) => {
+                JVMTI_THREAD_STATE_ALIVE | JVMTI_THREAD_STATE_RUNNABLE
+            }
+            ThreadState::Blocked(_) => JVMTI_THREAD_STATE_ALIVE | JVMTI_THREAD_STATE_BLOCKED_ON_MONITOR_ENTER,
+            ThreadState::Waiting {
+                deadline,
+                ..
+            } => waiting | timeout(deadline) | JVMTI_THREAD_STATE_IN_OBJECT_WAIT,
+            ThreadState::Sleeping(_) => waiting | JVMTI_THREAD_STATE_WAITING_WITH_TIMEOUT | JVMTI_THREAD_STATE_SLEEPING,
+            ThreadState::Parked(deadline) => waiting | timeout(deadline) | JVMTI_THREAD_STATE_PARKED,
+            ThreadState::Terminated => JVMTI_THREAD_STATE_TERMINATED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PendingWait {
+    pub object: usize,
+    pub entry_count: usize,
+}
+
+pub struct JavaThread {
+    pub thread_object: usize,
+    pub name: String,
+    pub is_daemon: bool,
+    pub state: ThreadState,
+    pub interrupted: bool,
+    pub call_stack_frames: Vec<CallStackFrame>,
+    pub unwind_barriers: Vec<usize>,
+    pub is_suspended_in_native: bool,
+    pub pending_wait: Option<PendingWait>,
+    pub pending_sleep: bool,
+    pub pending_park: bool,
+    pub park_permit: bool,
+    pub pending_host_call: Option<usize>,
+    pub uncaught_exception: Option<usize>,
+    // the status last written to threadStatus by the scheduler
+    published_status: Option<i32>,
+}
+
+impl JavaThread {
+    pub fn new(
+        thread_object: usize,
+        name: String,
+        is_daemon: bool,
+        call_stack_frames: Vec<CallStackFrame>,
+    ) -> JavaThread {
+        JavaThread {
+            thread_object,
+            name,
+            is_daemon,
+            state: ThreadState::Runnable,
+            interrupted: false,
+            call_stack_frames,
+            unwind_barriers: vec![1],
+            is_suspended_in_native: false,
+            pending_wait: None,
+            pending_sleep: false,
+            pending_park: false,
+            park_permit: false,
+            pending_host_call: None,
+            uncaught_exception: None,
+            published_status: None,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.state != ThreadState::Terminated
+    }
+
+    pub fn is_runnable(&self) -> bool {
+        self.state == ThreadState::Runnable
+    }
+}
+
+struct Monitor {
+    owner: usize,
+    entry_count: usize,
+}
+
+pub struct ThreadScheduler {
+    threads: Vec<JavaThread>,
+    current: usize,
+    monitors: HashMap<usize, Monitor>,
+    yield_requested: bool,
+}
+
+impl ThreadScheduler {
+    pub fn new() -> ThreadScheduler {
+        ThreadScheduler {
+            threads: vec![JavaThread::new(0, String::from("main"), false, Vec::new())],
+            current: MAIN_THREAD,
+            monitors: HashMap::new(),
+            yield_requested: false,
+        }
+    }
+
+    pub fn get_current_thread_id(&self) -> usize {
+        self.current
+    }
+
+    pub fn get_current_thread(&self) -> &JavaThread {
+        &self.threads[self.current]
+    }
+
+    pub fn get_current_thread_mut(&mut self) -> &mut JavaThread {
+        &mut self.threads[self.current]
+    }
+
+    pub fn get_thread(&self, thread_id: usize) -> &JavaThread {
+        &self.threads[thread_id]
+    }
+
+    pub fn get_thread_mut(&mut self, thread_id: usize) -> &mut JavaThread {
+        &mut self.threads[thread_id]
+    }
+
+    pub fn get_threads(&self) -> &[JavaThread] {
+        &self.threads
+    }
+
+    pub fn find_thread(&self, thread_object: usize) -> Option<usize> {
+        self.threads.iter().position(|thread| thread.thread_object == thread_object)
+    }
+
+    pub fn spawn(&mut self, thread: JavaThread) -> usize {
+        self.threads.push(thread);
+        self.threads.len() - 1
+    }
+
+    pub fn set_current_thread(&mut self, thread_id: usize) {
+        self.current = thread_id;
+    }
+
+    pub fn has_live_non_daemon_threads(&self) -> bool {
+        self.threads.iter().any(|thread| thread.is_alive() && !thread.is_daemon)
+    }
+
+    pub fn request_yield(&mut self) {
+        self.yield_requested = true;
+    }
+
+    pub fn take_yield_request(&mut self) -> bool {
+        std::mem::replace(&mut self.yield_requested, false)
+    }
+
+    pub fn terminate_all(&mut self) {
+        for thread in &mut self.threads {
+            thread.state = ThreadState::Terminated;
+            thread.call_stack_frames.clear();
+        }
+        self.monitors.clear();
+    }
+
+    // picks the next runnable thread after the current one in round-robin order
+    pub fn select_next_thread(&mut self, include_current: bool) -> Option<usize> {
+        let count = self.threads.len();
+        (1..=count).map(|offset| (self.current + offset) % count).find(|id| {
+            let thread = &self.threads[*id];
+            thread.is_runnable() && !thread.is_suspended_in_native && (include_current || *id != self.current)
+        })
+    }
+
+    pub fn wake_expired_threads(&mut self, now: u64) {
+        for thread in &mut self.threads {
+            match thread.state {
+                ThreadState::Waiting {
+                    deadline: Some(deadline),
+                    ..
+                }
+                | ThreadState::Sleeping(deadline)
+                | ThreadState::Parked(Some(deadline))
+                    if deadline <= now =>
+                {
+                    thread.state = ThreadState::Runnable
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub fn get_next_deadline(&self) -> Option<u64> {
+        self.threads
+            .iter()
+            .filter_map(|thread| match thread.state {
+                ThreadState::Waiting {
+                    deadline,
+                    ..
+                } => deadline,
+                ThreadState::Sleeping(deadline) => Some(deadline),
+                ThreadState::Parked(deadline) => deadline,
+                _ => None,
+            })
+            .min()
+    }
+
+    // parks the current thread until the host reports the call as completed
+    pub fn await_host_call(&mut self, call_id: usize) {
+        let thread = self.get_current_thread_mut();
+        thread.state = ThreadState::AwaitingHost(call_id);
+        thread.pending_host_call = Some(call_id);
+    }
+
+    pub fn complete_host_call(&mut self, call_id: usize) {
+        for thread in &mut self.threads {
+            if thread.state == ThreadState::AwaitingHost(call_id) {
+                thread.state = ThreadState::Runnable;
+            }
+        }
+    }
+
+    pub fn is_awaiting_host(&self) -> bool {
+        self.threads.iter().any(|thread| matches!(thread.state, ThreadState::AwaitingHost(_)))
+    }
+
+    pub fn try_enter_monitor(&mut self, object: usize) -> bool {
+        let current = self.current;
+        let monitor = self.monitors.entry(object).or_insert(Monitor {
+            owner: current,
+            entry_count: 0,
+        });
+        if monitor.owner != current {
+            return false;
+        }
+        monitor.entry_count += 1;
+        true
+    }
+
+    pub fn exit_monitor(&mut self, object: usize) -> bool {
+        match self.monitors.get_mut(&object) {
+            Some(monitor) if monitor.owner == self.current => {
+                monitor.entry_count -= 1;
+                if monitor.entry_count == 0 {
+                    self.release_monitor(object);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn holds_monitor(&self, object: usize) -> bool {
+        matches!(self.monitors.get(&object), Some(monitor) if monitor.owner == self.current)
+    }
+
+    // fully releases a monitor held by the current thread, returning its recursive entry count
+    pub fn release_monitor_fully(&mut self, object: usize) -> Option<usize> {
+        if !self.holds_monitor(object) {
+            return None;
+        }
+        let entry_count = self.monitors[&object].entry_count;
+        self.release_monitor(object);
+        Some(entry_count)
+    }
+
+    pub fn restore_monitor(&mut self, object: usize, entry_count: usize) -> bool {
+        if !self.try_enter_monitor(object) {
+            return false;
+        }
+        self.monitors.get_mut(&object).unwrap().entry_count = entry_count;
+        true
+    }
+
+    fn release_monitor(&mut self, object: usize) {
+        self.monitors.remove(&object);
+        for thread in &mut self.threads {
+            if thread.state == ThreadState::Blocked(object) {
+                thread.state = ThreadState::Runnable;
+            }
+        }
+    }
+
+    pub fn notify(&mut self, object: usize, all: bool) {
+        for thread in &mut self.threads {
+            if let ThreadState::Waiting {
+                object: waiting_object,
+                ..
+            } = thread.state
+            {
+                if waiting_object == object {
+                    thread.state = ThreadState::Runnable;
+                    if !all {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn interrupt(&mut self, thread_id: usize) {
+        let thread = &mut self.threads[thread_id];
+        thread.interrupted = true;
+        if let ThreadState::Waiting {
+            ..
+        }
+        | ThreadState::Sleeping(_)
+        | ThreadState::Parked(_) = thread.state
+        {
+            thread.state = ThreadState::Runnable;
+        }
+    }
+
+    // wakes the thread if it is parked, otherwise makes the permit available to its next park
+    pub fn unpark(&mut self, thread_id: usize) {
+        let thread = &mut self.threads[thread_id];
+        match thread.state {
+            ThreadState::Parked(_) => thread.state = ThreadState::Runnable,
+            _ => thread.park_permit = true,
+        }
+    }
+}
+
+impl Jvm {
+    pub fn is_current_thread_runnable(&self) -> bool {
+        self.threads.borrow().get_current_thread().is_runnable()
+    }
+
+    pub fn switch_to_thread(&self, thread_id: usize) {
+        let mut threads = self.threads.borrow_mut();
+        let current = threads.get_current_thread_id();
+        if current == thread_id {
+            return;
+        }
+
+        let mut csf = self.call_stack_frames.borrow_mut();
+        threads.get_thread_mut(current).call_stack_frames = std::mem::take(&mut *csf);
+        *csf = std::mem::take(&mut threads.get_thread_mut(thread_id).call_stack_frames);
+        threads.set_current_thread(thread_id);
+        drop(threads);
+
+        self.update_thread_statuses();
+    }
+
+    // Thread.getState reads threadStatus, so the states of all threads are published whenever another
+    // thread gets to run and could observe them, only the statuses which changed are written
+    fn update_thread_statuses(&self) {
+        let mut threads = self.threads.borrow_mut();
+        let mut heap = None;
+        for thread in threads.threads.iter_mut() {
+            let thread_status = thread.state.get_thread_status();
+            if thread.published_status == Some(thread_status) {
+                continue;
+            }
+            thread.published_status = Some(thread_status);
+
+            let heap = heap.get_or_insert_with(|| self.heap.borrow_mut());
+            if let Some(status) = heap
+                .object_heap_map
+                .get_mut(&thread.thread_object)
+                .and_then(|obj| obj.instance_fields.get_mut("threadStatus"))
+            {
+                *status = JavaValue::Int(thread_status);
+            }
+        }
+    }
+
+    pub fn wake_expired_threads(&self) {
+        let now = self.clock.borrow().current_time_millis();
+        self.threads.borrow_mut().wake_expired_threads(now as u64);
+    }
+
+    pub fn wake_completed_host_calls(&self) {
+        let completed = self.promises.borrow().get_settled();
+        let mut threads = self.threads.borrow_mut();
+        for call_id in completed {
+            threads.complete_host_call(call_id);
+        }
+    }
+
+    // only the outermost scheduler loop can hand control back to the host, nested loops still
+    // have native code on the Rust stack
+    pub fn idle_until_next_deadline(&self, can_suspend: bool) -> RuntimeResult<()> {
+        let (deadline, is_awaiting_host) = {
+            let threads = self.threads.borrow();
+            (threads.get_next_deadline(), threads.is_awaiting_host())
+        };
+        let deadline = match deadline {
+            _ if is_awaiting_host && can_suspend => return Err(JavaThrowable::Suspended),
+            Some(deadline) => deadline as i64,
+            None if is_awaiting_host => {
+                return Err(self.raise(VmError::Internal(String::from(
+                    "cannot wait for the host while native code is on the stack",
+                ))))
+            }
+            None => {
+                log_error("All threads are blocked, terminating the JVM");
+                return Err(JavaThrowable::Exit(1));
+            }
+        };
+
+        // waiting on a real clock would block the host, the outermost loop leaves it to the host instead
+        let clock = self.clock.borrow();
+        if can_suspend && clock.is_real_time() {
+            return Err(JavaThrowable::Suspended);
+        }
+        clock.wait_until(deadline);
+        Ok(())
+    }
+
+    pub fn start_thread(&self, thread_object: usize) -> RuntimeResult<()> {
+        let env = JniEnv::empty(self);
+        let class_name = env.get_object_type_name(thread_object)?;
+        let class_file = self.classpath.get_classpath_entry(&class_name).unwrap();
+        let (method_class, method) = match self.classpath.get_method(InvokeType::Virtual, class_file, "run", "()V") {
+            Some(method) => method,
+            None => {
+                return Err(self.throw_exception("java/lang/NoSuchMethodError", Some(&format!("{}.run()V", class_name))))
+            }
+        };
+        let mut run_frame = self.create_stack_frame(method_class, method)?;
+        run_frame.state.lvt[0] = JavaValue::Object(Some(thread_object));
+
+        let name = match env.invoke_instance_method(
+            InvokeType::Virtual,
+            thread_object,
+            0,
+            "getName",
+            "()Ljava/lang/String;",
+            &[],
+        )? {
+            Some(JavaValue::Object(Some(name))) => env.get_string(name)?,
+            _ => String::new(),
+        };
+        let is_daemon = env.get_field(thread_object, "daemon")?.as_boolean().unwrap();
+
+        let frames = vec![CallStackFrame::new_virtual("webjvm/lang/Thread", "start()V"), run_frame];
+        self.threads.borrow_mut().spawn(JavaThread::new(thread_object, name, is_daemon, frames));
+        self.set_thread_status(thread_object, true)?;
+
+        Ok(())
+    }
+
+    pub fn terminate_current_thread(&self) -> RuntimeResult<()> {
+        let thread_object = self.threads.borrow().get_current_thread().thread_object;
+
+        let env = JniEnv::empty(self);
+        let thread_class_id = self.ensure_class_loaded("java/lang/Thread", true)?;
+        let thread_class = self.classpath.get_classpath_entry("java/lang/Thread").unwrap();
+        if self.classpath.get_method(InvokeType::Special, thread_class, "exit", "()V").is_some() {
+            match env.invoke_instance_method(InvokeType::Special, thread_object, thread_class_id, "exit", "()V", &[]) {
+                Err(JavaThrowable::Unhandled(ex)) => self.report_uncaught_exception(ex),
+                Err(err) => return Err(err),
+                Ok(_) => (),
+            }
+        }
+
+        self.set_thread_status(thread_object, false)?;
+        let mut threads = self.threads.borrow_mut();
+        threads.get_current_thread_mut().state = ThreadState::Terminated;
+        threads.notify(thread_object, true);
+
+        Ok(())
+    }
+
+    fn set_thread_status(&self, thread_object: usize, is_alive: bool) -> RuntimeResult<()> {
+        let env = JniEnv::empty(self);
+        env.set_internal_metadata(thread_object, "is_alive", InternalMetadata::Numeric(is_alive as usize))?;
+
+        let mut heap = self.heap.borrow_mut();
+        let obj = heap.object_heap_map.get_mut(&thread_object).unwrap();
+        if obj.instance_fields.contains_key("threadStatus") {
+            let state = match is_alive {
+                true => ThreadState::Runnable,
+                false => ThreadState::Terminated,
+            };
+            obj.instance_fields.insert(String::from("threadStatus"), JavaValue::Int(state.get_thread_status()));
+        }
+
+        Ok(())
+    }
+
+    pub fn report_uncaught_exception(&self, reference: usize) {
+        let name = {
+            let mut threads = self.threads.borrow_mut();
+            let thread = threads.get_current_thread_mut();
+            thread.uncaught_exception = Some(reference);
+            thread.name.clone()
+        };
+
+        let stacktrace = match JniEnv::empty(self).get_internal_metadata(reference, "stack_trace") {
+            Ok(Some(InternalMetadata::Text(stacktrace))) => stacktrace,
+            _ => String::new(),
+        };
+        let message =
+            format!("Exception in thread \"{}\" {}\n{}\n", name, self.describe_exception(reference), stacktrace);
+        let _ = self.stderr.borrow_mut().write_all(message.as_bytes());
+    }
+
+    pub fn describe_exception(&self, reference: usize) -> String {
+        let env = JniEnv::empty(self);
+        let exception_class = env.get_object_type_name(reference).unwrap_or_default().replace("/", ".");
+        match env.get_field(reference, "detailMessage") {
+            Ok(JavaValue::Object(Some(id))) => {
+                format!("{}: {}", exception_class, env.get_string(id).unwrap_or_default())
+            }
+            _ => exception_class,
+        }
+    }
+
+    // the current thread is only runnable while Java code runs, in which case the call is nested like a call
+    // from a native method. otherwise the main thread runs the call with a base frame that calls from the host
+    // run on top of, unless it is suspended in the middle of its execution
+    pub fn prepare_main_thread(&self) -> RuntimeResult<()> {
+        if !self.threads.borrow().get_current_thread().is_runnable() {
+            self.switch_to_thread(MAIN_THREAD);
+            let mut threads = self.threads.borrow_mut();
+            let main_thread = threads.get_current_thread_mut();
+            match main_thread.state {
+                ThreadState::Runnable => (),
+                ThreadState::Terminated => main_thread.state = ThreadState::Runnable,
+                _ => {
+                    drop(threads);
+                    return Err(self.raise(VmError::IllegalHostCall(String::from(
+                        "cannot call into Java while the main thread is suspended",
+                    ))));
+                }
+            }
+        }
+        if self.get_stack_depth() == 0 {
+            self.push_call_stack_frame(CallStackFrame::new_virtual("webjvm/lang/Main", "main()V"));
+        }
+        Ok(())
+    }
+
+    // runs the shutdown sequence on the main thread once every non-daemon thread has finished
+    pub fn destroy(&self) -> RuntimeResult<()> {
+        self.switch_to_thread(MAIN_THREAD);
+        self.threads.borrow_mut().get_current_thread_mut().state = ThreadState::Runnable;
+
+        if self.classpath.get_classpath_entry("java/lang/Shutdown").is_some() {
+            let env = JniEnv::empty(self);
+            let shutdown_class_id = env.get_class_id("java/lang/Shutdown")?;
+            env.invoke_static_method(shutdown_class_id, "shutdown", "()V", &[])?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,547 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    io::{self, Cursor, Read, Write},
+};
+
+use crate::util::current_time_millis;
+
+pub struct FileMetadata {
+    pub is_directory: bool,
+    pub length: u64,
+    pub last_modified: i64,
+}
+
+// the file system backing java.io, paths passed to it are normalized and absolute
+pub trait FileSystem {
+    fn metadata(&self, path: &str) -> Option<FileMetadata>;
+
+    fn list(&self, path: &str) -> Option<Vec<String>>;
+
+    // returns false if the path already exists
+    fn create_file(&mut self, path: &str) -> io::Result<bool>;
+
+    fn create_directory(&mut self, path: &str) -> io::Result<bool>;
+
+    fn delete(&mut self, path: &str) -> io::Result<bool>;
+
+    fn rename(&mut self, from: &str, to: &str) -> io::Result<bool>;
+
+    fn set_last_modified(&mut self, path: &str, last_modified: i64) -> io::Result<bool>;
+
+    fn read_at(&self, path: &str, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    fn write_at(&mut self, path: &str, offset: u64, data: &[u8]) -> io::Result<()>;
+
+    fn set_length(&mut self, path: &str, length: u64) -> io::Result<()>;
+}
+
+enum Entry {
+    File {
+        data: Vec<u8>,
+        last_modified: i64,
+    },
+    Directory {
+        last_modified: i64,
+    },
+}
+
+pub struct MemoryFileSystem {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> MemoryFileSystem {
+        let mut entries = BTreeMap::new();
+        entries.insert(
+            String::from("/"),
+            Entry::Directory {
+                last_modified: current_time_millis(),
+            },
+        );
+        MemoryFileSystem {
+            entries,
+        }
+    }
+
+    fn get_file_mut(&mut self, path: &str) -> io::Result<&mut Vec<u8>> {
+        match self.entries.get_mut(path) {
+            Some(Entry::File {
+                data,
+                last_modified,
+            }) => {
+                *last_modified = current_time_millis();
+                Ok(data)
+            }
+            Some(Entry::Directory {
+                ..
+            }) => Err(io::Error::other("Is a directory")),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory")),
+        }
+    }
+
+    fn is_directory(&self, path: &str) -> bool {
+        matches!(self.entries.get(path), Some(Entry::Directory { .. }))
+    }
+
+    fn get_children<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a String> + 'a {
+        let prefix = if path == "/" {
+            String::from("/")
+        } else {
+            format!("{}/", path)
+        };
+        let prefix_length = prefix.len();
+        self.entries
+            .range(prefix.clone()..)
+            .map(|(child, _)| child)
+            .take_while(move |child| child.starts_with(&prefix))
+            // the root is its own prefix
+            .filter(move |child| child.len() > prefix_length && !child[prefix_length..].contains('/'))
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn metadata(&self, path: &str) -> Option<FileMetadata> {
+        Some(match self.entries.get(path)? {
+            Entry::File {
+                data,
+                last_modified,
+            } => FileMetadata {
+                is_directory: false,
+                length: data.len() as u64,
+                last_modified: *last_modified,
+            },
+            Entry::Directory {
+                last_modified,
+            } => FileMetadata {
+                is_directory: true,
+                length: 0,
+                last_modified: *last_modified,
+            },
+        })
+    }
+
+    fn list(&self, path: &str) -> Option<Vec<String>> {
+        if !self.is_directory(path) {
+            return None;
+        }
+        Some(self.get_children(path).map(|child| String::from(get_file_name(child))).collect())
+    }
+
+    fn create_file(&mut self, path: &str) -> io::Result<bool> {
+        if self.entries.contains_key(path) {
+            return Ok(false);
+        }
+        if !self.is_directory(get_parent(path)) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory"));
+        }
+        self.entries.insert(
+            String::from(path),
+            Entry::File {
+                data: Vec::new(),
+                last_modified: current_time_millis(),
+            },
+        );
+        Ok(true)
+    }
+
+    fn create_directory(&mut self, path: &str) -> io::Result<bool> {
+        if self.entries.contains_key(path) || !self.is_directory(get_parent(path)) {
+            return Ok(false);
+        }
+        self.entries.insert(
+            String::from(path),
+            Entry::Directory {
+                last_modified: current_time_millis(),
+            },
+        );
+        Ok(true)
+    }
+
+    fn delete(&mut self, path: &str) -> io::Result<bool> {
+        if path == "/" || self.get_children(path).next().is_some() {
+            return Ok(false);
+        }
+        Ok(self.entries.remove(path).is_some())
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> io::Result<bool> {
+        if from == "/" || !self.entries.contains_key(from) || !self.is_directory(get_parent(to)) {
+            return Ok(false);
+        }
+        if to.starts_with(&format!("{}/", from)) {
+            return Ok(false);
+        }
+        match self.entries.get(to) {
+            Some(Entry::Directory {
+                ..
+            }) if self.get_children(to).next().is_some() => return Ok(false),
+            _ => (),
+        }
+
+        // a renamed directory takes its contents along
+        let prefix = format!("{}/", from);
+        let mut moved = vec![String::from(from)];
+        moved.extend(
+            self.entries
+                .range(prefix.clone()..)
+                .map(|(path, _)| path.clone())
+                .take_while(|path| path.starts_with(&prefix)),
+        );
+        for path in moved {
+            let entry = self.entries.remove(&path).unwrap();
+            self.entries.insert(format!("{}{}", to, &path[from.len()..]), entry);
+        }
+        Ok(true)
+    }
+
+    fn set_last_modified(&mut self, path: &str, time: i64) -> io::Result<bool> {
+        match self.entries.get_mut(path) {
+            Some(Entry::File {
+                last_modified,
+                ..
+            })
+            | Some(Entry::Directory {
+                last_modified,
+            }) => {
+                *last_modified = time;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn read_at(&self, path: &str, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        match self.entries.get(path) {
+            Some(Entry::File {
+                data,
+                ..
+            }) => {
+                // an offset beyond the address space is beyond the end of any file
+                let start = usize::try_from(offset).unwrap_or(usize::MAX).min(data.len());
+                let length = buf.len().min(data.len() - start);
+                buf[..length].copy_from_slice(&data[start..start + length]);
+                Ok(length)
+            }
+            Some(Entry::Directory {
+                ..
+            }) => Err(io::Error::other("Is a directory")),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory")),
+        }
+    }
+
+    fn write_at(&mut self, path: &str, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let data = self.get_file_mut(path)?;
+        let start = usize::try_from(offset).map_err(|_| file_too_large())?;
+        let end = start.checked_add(buf.len()).ok_or_else(file_too_large)?;
+        if data.len() < end {
+            resize_file(data, end)?;
+        }
+        data[start..end].copy_from_slice(buf);
+        Ok(())
+    }
+
+    fn set_length(&mut self, path: &str, length: u64) -> io::Result<()> {
+        let length = usize::try_from(length).map_err(|_| file_too_large())?;
+        resize_file(self.get_file_mut(path)?, length)
+    }
+}
+
+fn file_too_large() -> io::Error {
+    io::Error::other("File too large")
+}
+
+// files are limited by the memory available to the VM, growing them fails instead of aborting
+fn resize_file(data: &mut Vec<u8>, length: usize) -> io::Result<()> {
+    if length > data.len() {
+        data.try_reserve_exact(length - data.len()).map_err(|_| file_too_large())?;
+    }
+    data.resize(length, 0);
+    Ok(())
+}
+
+// resolves . and .. against the root, since the working directory is always /
+pub fn normalize_path(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    format!("/{}", components.join("/"))
+}
+
+pub fn get_parent(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+pub fn get_file_name(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(index) => &path[index + 1..],
+        None => path,
+    }
+}
+
+// copies the files and directories of a zip archive into the file system, creating parent directories
+pub fn import_zip(fs: &mut dyn FileSystem, zip_bytes: &[u8]) -> Result<(), String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(zip_bytes)).map_err(|err| err.to_string())?;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(|err| err.to_string())?;
+        let path = normalize_path(file.name());
+        let directory = if file.is_dir() {
+            path.as_str()
+        } else {
+            get_parent(&path)
+        };
+
+        let mut parent = String::new();
+        for component in directory.split('/').filter(|component| !component.is_empty()) {
+            parent = format!("{}/{}", parent, component);
+            fs.create_directory(&parent).map_err(|err| err.to_string())?;
+        }
+
+        if !file.is_dir() {
+            let mut bytes = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut bytes).map_err(|err| err.to_string())?;
+            fs.create_file(&path).map_err(|err| err.to_string())?;
+            fs.set_length(&path, 0).map_err(|err| err.to_string())?;
+            fs.write_at(&path, 0, &bytes).map_err(|err| err.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// writes the whole file system to a zip archive, so that the host can persist it
+pub fn export_zip(fs: &dyn FileSystem) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let mut pending = vec![String::from("/")];
+    while let Some(directory) = pending.pop() {
+        for name in fs.list(&directory).unwrap_or_default() {
+            let path = if directory == "/" {
+                format!("/{}", name)
+            } else {
+                format!("{}/{}", directory, name)
+            };
+            let metadata = match fs.metadata(&path) {
+                Some(metadata) => metadata,
+                None => continue,
+            };
+
+            let options = zip::write::FileOptions::default();
+            if metadata.is_directory {
+                zip.add_directory(&path[1..], options).map_err(|err| err.to_string())?;
+                pending.push(path);
+            } else {
+                let length = usize::try_from(metadata.length).map_err(|_| file_too_large().to_string())?;
+                let mut bytes = vec![0; length];
+                fs.read_at(&path, 0, &mut bytes).map_err(|err| err.to_string())?;
+                zip.start_file(&path[1..], options).map_err(|err| err.to_string())?;
+                zip.write_all(&bytes).map_err(|err| err.to_string())?;
+            }
+        }
+    }
+    Ok(zip.finish().map_err(|err| err.to_string())?.into_inner())
+}
+
+pub struct OpenOptions {
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub create: bool,
+    pub truncate: bool,
+}
+
+struct OpenFile {
+    path: String,
+    position: u64,
+    options: OpenOptions,
+}
+
+// file descriptors opened by java.io on top of a file system, 0 to 2 are the standard streams
+pub struct Vfs {
+    fs: Box<dyn FileSystem>,
+    open_files: HashMap<i32, OpenFile>,
+    next_fd: i32,
+}
+
+impl Vfs {
+    pub fn new() -> Vfs {
+        Vfs {
+            fs: Box::new(MemoryFileSystem::new()),
+            open_files: HashMap::new(),
+            next_fd: 3,
+        }
+    }
+
+    // descriptors opened on the previous file system are closed
+    pub fn set_file_system(&mut self, fs: Box<dyn FileSystem>) {
+        self.fs = fs;
+        self.open_files.clear();
+    }
+
+    pub fn get_file_system(&self) -> &dyn FileSystem {
+        self.fs.as_ref()
+    }
+
+    pub fn get_file_system_mut(&mut self) -> &mut dyn FileSystem {
+        self.fs.as_mut()
+    }
+
+    fn get_open_file(&mut self, fd: i32) -> io::Result<&mut OpenFile> {
+        match self.open_files.get_mut(&fd) {
+            Some(file) => Ok(file),
+            None => Err(io::Error::other("Stream Closed")),
+        }
+    }
+
+    pub fn open(&mut self, path: &str, options: OpenOptions) -> io::Result<i32> {
+        let path = normalize_path(path);
+        match self.fs.metadata(&path) {
+            Some(metadata) if metadata.is_directory => return Err(io::Error::other("Is a directory")),
+            Some(_) if options.truncate => self.fs.set_length(&path, 0)?,
+            Some(_) => (),
+            None if options.create => {
+                self.fs.create_file(&path)?;
+            }
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "No such file or directory")),
+        }
+
+        let fd = self.next_fd;
+        self.next_fd += 1;
+        self.open_files.insert(
+            fd,
+            OpenFile {
+                path,
+                position: 0,
+                options,
+            },
+        );
+        Ok(fd)
+    }
+
+    // returns 0 at the end of the file
+    pub fn read(&mut self, fd: i32, buf: &mut [u8]) -> io::Result<usize> {
+        let file = self.open_files.get_mut(&fd);
+        let file = match file {
+            Some(file) if file.options.read => file,
+            Some(_) => return Err(io::Error::other("Bad file descriptor")),
+            None => return Err(io::Error::other("Stream Closed")),
+        };
+        let length = self.fs.read_at(&file.path, file.position, buf)?;
+        file.position += length as u64;
+        Ok(length)
+    }
+
+    pub fn write(&mut self, fd: i32, data: &[u8]) -> io::Result<()> {
+        let file = self.open_files.get_mut(&fd);
+        let file = match file {
+            Some(file) if file.options.write => file,
+            Some(_) => return Err(io::Error::other("Bad file descriptor")),
+            None => return Err(io::Error::other("Stream Closed")),
+        };
+        if file.options.append {
+            file.position = self.fs.metadata(&file.path).map_or(0, |metadata| metadata.length);
+        }
+        self.fs.write_at(&file.path, file.position, data)?;
+        file.position += data.len() as u64;
+        Ok(())
+    }
+
+    pub fn get_position(&mut self, fd: i32) -> io::Result<u64> {
+        Ok(self.get_open_file(fd)?.position)
+    }
+
+    pub fn seek(&mut self, fd: i32, position: u64) -> io::Result<()> {
+        self.get_open_file(fd)?.position = position;
+        Ok(())
+    }
+
+    pub fn get_length(&mut self, fd: i32) -> io::Result<u64> {
+        let path = self.get_open_file(fd)?.path.clone();
+        Ok(self.fs.metadata(&path).map_or(0, |metadata| metadata.length))
+    }
+
+    pub fn set_length(&mut self, fd: i32, length: u64) -> io::Result<()> {
+        let file = self.get_open_file(fd)?;
+        file.position = file.position.min(length);
+        let path = file.path.clone();
+        self.fs.set_length(&path, length)
+    }
+
+    pub fn available(&mut self, fd: i32) -> io::Result<u64> {
+        let position = self.get_position(fd)?;
+        Ok(self.get_length(fd)?.saturating_sub(position))
+    }
+
+    pub fn close(&mut self, fd: i32) {
+        self.open_files.remove(&fd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileSystem, MemoryFileSystem};
+
+    fn read_file(fs: &MemoryFileSystem, path: &str) -> Vec<u8> {
+        let mut buf = vec![0; fs.metadata(path).unwrap().length as usize];
+        assert_eq!(fs.read_at(path, 0, &mut buf).unwrap(), buf.len());
+        buf
+    }
+
+    #[test]
+    fn lists_renames_and_deletes_entries() {
+        let mut fs = MemoryFileSystem::new();
+        assert!(fs.create_directory("/data").unwrap());
+        assert!(fs.create_directory("/data/sub").unwrap());
+        assert!(fs.create_file("/data/a").unwrap());
+        assert!(!fs.create_file("/data/a").unwrap());
+        assert!(fs.create_file("/missing/a").is_err());
+        fs.write_at("/data/sub/b", 0, b"x").unwrap_err();
+        assert!(fs.create_file("/data/sub/b").unwrap());
+        fs.write_at("/data/sub/b", 2, b"hi").unwrap();
+        assert!(fs.create_file("/database").unwrap());
+
+        assert_eq!(fs.list("/").unwrap(), ["data", "database"]);
+        assert_eq!(fs.list("/data").unwrap(), ["a", "sub"]);
+        assert_eq!(fs.list("/data/a"), None);
+
+        // directories are moved with their contents, but never into themselves
+        assert!(!fs.rename("/data", "/data/sub/data").unwrap());
+        assert!(!fs.rename("/data", "/missing/data").unwrap());
+        assert!(fs.rename("/data", "/moved").unwrap());
+        assert!(fs.metadata("/data").is_none());
+        assert!(fs.metadata("/database").is_some());
+        assert_eq!(fs.list("/moved/sub").unwrap(), ["b"]);
+        assert_eq!(read_file(&fs, "/moved/sub/b"), b"\0\0hi");
+
+        // directories have to be empty to be deleted or replaced
+        assert!(!fs.delete("/moved/sub").unwrap());
+        assert!(!fs.rename("/database", "/moved").unwrap());
+        assert!(fs.delete("/moved/sub/b").unwrap());
+        assert!(!fs.delete("/moved/sub/b").unwrap());
+        assert!(fs.delete("/moved/sub").unwrap());
+        assert!(!fs.delete("/").unwrap());
+        assert_eq!(fs.list("/moved").unwrap(), ["a"]);
+    }
+
+    #[test]
+    fn rejects_oversized_writes() {
+        let mut fs = MemoryFileSystem::new();
+        fs.create_file("/a").unwrap();
+        fs.write_at("/a", 0, b"abc").unwrap();
+        assert!(fs.write_at("/a", u64::MAX, b"x").is_err());
+        assert!(fs.set_length("/a", u64::MAX).is_err());
+
+        let mut buf = [0; 4];
+        assert_eq!(fs.read_at("/a", u64::MAX, &mut buf).unwrap(), 0);
+        fs.set_length("/a", 2).unwrap();
+        assert_eq!(read_file(&fs, "/a"), b"ab");
+    }
+}
@@ -0,0 +1,2227 @@
+// a port of fdlibm 5.3, which StrictMath is specified to match bit for bit, so the operations and their
+// order follow the C sources exactly, including expressions like x - x that produce NaN and constants spelled
+// out with more digits than a double holds
+#![allow(clippy::approx_constant, clippy::eq_op, clippy::excessive_precision)]
+
+const ZERO: f64 = 0.0;
+const HALF: f64 = 0.5;
+const ONE: f64 = 1.0;
+const TWO: f64 = 2.0;
+const HUGE: f64 = 1.0e300;
+const TINY: f64 = 1.0e-300;
+const TWO24: f64 = 1.67772160000000000000e+07;
+const TWO_N24: f64 = 5.96046447753906250000e-08;
+const TWO53: f64 = 9007199254740992.0;
+const TWO54: f64 = 1.80143985094819840000e+16;
+const TWO_N54: f64 = 5.55111512312578270212e-17;
+const TWO_N1000: f64 = 9.33263618503218878990e-302;
+
+const LN2_HI: f64 = 6.93147180369123816490e-01;
+const LN2_LO: f64 = 1.90821492927058770002e-10;
+const INV_LN2: f64 = 1.44269504088896338700e+00;
+const EXP_OVERFLOW_THRESHOLD: f64 = 7.09782712893383973096e+02;
+const EXP_UNDERFLOW_THRESHOLD: f64 = -7.45133219101941108420e+02;
+
+const PI: f64 = 3.14159265358979311600e+00;
+const PI_LO: f64 = 1.2246467991473531772e-16;
+const PIO2_HI: f64 = 1.57079632679489655800e+00;
+const PIO2_LO: f64 = 6.12323399573676603587e-17;
+const PIO4_HI: f64 = 7.85398163397448278999e-01;
+const PIO4_LO: f64 = 3.06161699786838301793e-17;
+
+fn get_high_word(x: f64) -> i32 {
+    (x.to_bits() >> 32) as i32
+}
+
+fn get_low_word(x: f64) -> u32 {
+    x.to_bits() as u32
+}
+
+fn from_words(high: i32, low: u32) -> f64 {
+    f64::from_bits(((high as u32 as u64) << 32) | low as u64)
+}
+
+fn set_high_word(x: f64, high: i32) -> f64 {
+    from_words(high, get_low_word(x))
+}
+
+fn set_low_word(x: f64, low: u32) -> f64 {
+    from_words(get_high_word(x), low)
+}
+
+fn scalbn(x: f64, n: i32) -> f64 {
+    let mut x = x;
+    let mut hx = get_high_word(x);
+    let lx = get_low_word(x);
+    let mut k = (hx & 0x7ff00000) >> 20;
+    if k == 0 {
+        if (lx | (hx & 0x7fffffff) as u32) == 0 {
+            return x;
+        }
+        x *= TWO54;
+        hx = get_high_word(x);
+        k = ((hx & 0x7ff00000) >> 20) - 54;
+        if n < -50000 {
+            return TINY * x;
+        }
+    }
+    if k == 0x7ff {
+        return x + x;
+    }
+    k = k.wrapping_add(n);
+    if k > 0x7fe {
+        return HUGE * HUGE.copysign(x);
+    }
+    if k > 0 {
+        return set_high_word(x, (hx & 0x800fffffu32 as i32) | (k << 20));
+    }
+    if k <= -54 {
+        if n > 50000 {
+            return HUGE * HUGE.copysign(x);
+        }
+        return TINY * TINY.copysign(x);
+    }
+    k += 54;
+    set_high_word(x, (hx & 0x800fffffu32 as i32) | (k << 20)) * TWO_N54
+}
+
+// the bits of 2/pi in chunks of 24
+const TWO_OVER_PI: [i32; 66] = [
+    0xA2F983, 0x6E4E44, 0x1529FC, 0x2757D1, 0xF534DD, 0xC0DB62, 0x95993C, 0x439041, 0xFE5163, 0xABDEBB, 0xC561B7,
+    0x246E3A, 0x424DD2, 0xE00649, 0x2EEA09, 0xD1921C, 0xFE1DEB, 0x1CB129, 0xA73EE8, 0x8235F5, 0x2EBB44, 0x84E99C,
+    0x7026B4, 0x5F7E41, 0x3991D6, 0x398353, 0x39F49C, 0x845F8B, 0xBDF928, 0x3B1FF8, 0x97FFDE, 0x05980F, 0xEF2F11,
+    0x8B5A0A, 0x6D1F6D, 0x367ECF, 0x27CB09, 0xB74F46, 0x3F669E, 0x5FEA2D, 0x7527BA, 0xC7EBE5, 0xF17B3D, 0x0739F7,
+    0x8A5292, 0xEA6BFB, 0x5FB11F, 0x8D5D08, 0x560330, 0x46FC7B, 0x6BABF0, 0xCFBC20, 0x9AF436, 0x1DA9E3, 0x91615E,
+    0xE61B08, 0x659985, 0x5F14A0, 0x68408D, 0xFFD880, 0x4D7327, 0x310606, 0x1556CA, 0x73A8C9, 0x60E27B, 0xC08C6B,
+];
+
+// pi/2 split into pieces of 24 bits
+const PIO2: [f64; 8] = [
+    1.57079625129699707031e+00,
+    7.54978941586159635335e-08,
+    5.39030252995776476554e-15,
+    3.28200341580791294123e-22,
+    1.27065575308067607349e-29,
+    1.22933308981111328932e-36,
+    2.73370053816464559624e-44,
+    2.16741683877804819444e-51,
+];
+
+// reduces x, given as up to three 24 bit chunks scaled by 2^e0, modulo pi/2 into y[0] + y[1] with the
+// precision of __kernel_rem_pio2 for doubles, returns the last three bits of the quotient
+fn kernel_rem_pio2(x: &[f64], y: &mut [f64; 2], e0: i32) -> i32 {
+    const JK: usize = 4;
+    const JP: usize = JK;
+
+    let jx = x.len() - 1;
+    let jv = ((e0 - 3) / 24).max(0);
+    let mut q0 = e0 - 24 * (jv + 1);
+    let jv = jv as usize;
+
+    let mut f = [0.0; 20];
+    let mut q = [0.0; 20];
+    let mut fq = [0.0; 20];
+    let mut iq = [0i32; 20];
+
+    for (i, value) in f.iter_mut().enumerate().take(jx + JK + 1) {
+        *value = match (jv + i).checked_sub(jx) {
+            Some(j) => TWO_OVER_PI[j] as f64,
+            None => ZERO,
+        };
+    }
+    for i in 0..=JK {
+        let mut fw = 0.0;
+        for j in 0..=jx {
+            fw += x[j] * f[jx + i - j];
+        }
+        q[i] = fw;
+    }
+
+    let mut jz = JK;
+    loop {
+        // distill q into iq reversingly
+        let mut z = q[jz];
+        let mut j = jz;
+        for value in iq.iter_mut().take(jz) {
+            let fw = ((TWO_N24 * z) as i32) as f64;
+            *value = (z - TWO24 * fw) as i32;
+            z = q[j - 1] + fw;
+            j -= 1;
+        }
+
+        z = scalbn(z, q0);
+        z -= 8.0 * (z * 0.125).floor();
+        let mut n = z as i32;
+        z -= n as f64;
+        let mut ih = 0;
+        if q0 > 0 {
+            let i = iq[jz - 1] >> (24 - q0);
+            n += i;
+            iq[jz - 1] -= i << (24 - q0);
+            ih = iq[jz - 1] >> (23 - q0);
+        } else if q0 == 0 {
+            ih = iq[jz - 1] >> 23;
+        } else if z >= 0.5 {
+            ih = 2;
+        }
+
+        if ih > 0 {
+            n += 1;
+            let mut carry = 0;
+            for value in iq.iter_mut().take(jz) {
+                let j = *value;
+                if carry == 0 {
+                    if j != 0 {
+                        carry = 1;
+                        *value = 0x1000000 - j;
+                    }
+                } else {
+                    *value = 0xffffff - j;
+                }
+            }
+            match q0 {
+                1 => iq[jz - 1] &= 0x7fffff,
+                2 => iq[jz - 1] &= 0x3fffff,
+                _ => {}
+            }
+            if ih == 2 {
+                z = ONE - z;
+                if carry != 0 {
+                    z -= scalbn(ONE, q0);
+                }
+            }
+        }
+
+        // more terms of 2/pi are needed if the result cancelled out
+        if z == ZERO && iq[JK..jz].iter().all(|value| *value == 0) {
+            let mut k = 1;
+            while iq[JK - k] == 0 {
+                k += 1;
+            }
+            for i in jz + 1..=jz + k {
+                f[jx + i] = TWO_OVER_PI[jv + i] as f64;
+                let mut fw = 0.0;
+                for j in 0..=jx {
+                    fw += x[j] * f[jx + i - j];
+                }
+                q[i] = fw;
+            }
+            jz += k;
+            continue;
+        }
+
+        // chop off zero terms
+        if z == 0.0 {
+            jz -= 1;
+            q0 -= 24;
+            while iq[jz] == 0 {
+                jz -= 1;
+                q0 -= 24;
+            }
+        } else {
+            z = scalbn(z, -q0);
+            if z >= TWO24 {
+                let fw = ((TWO_N24 * z) as i32) as f64;
+                iq[jz] = (z - TWO24 * fw) as i32;
+                jz += 1;
+                q0 += 24;
+                iq[jz] = fw as i32;
+            } else {
+                iq[jz] = z as i32;
+            }
+        }
+
+        let mut fw = scalbn(ONE, q0);
+        for i in (0..=jz).rev() {
+            q[i] = fw * iq[i] as f64;
+            fw *= TWO_N24;
+        }
+
+        for i in (0..=jz).rev() {
+            let mut fw = 0.0;
+            let mut k = 0;
+            while k <= JP && k <= jz - i {
+                fw += PIO2[k] * q[i + k];
+                k += 1;
+            }
+            fq[jz - i] = fw;
+        }
+
+        let mut fw = 0.0;
+        for value in fq[..=jz].iter().rev() {
+            fw += value;
+        }
+        y[0] = if ih == 0 {
+            fw
+        } else {
+            -fw
+        };
+        fw = fq[0] - fw;
+        for value in &fq[1..=jz] {
+            fw += value;
+        }
+        y[1] = if ih == 0 {
+            fw
+        } else {
+            -fw
+        };
+        return n & 7;
+    }
+}
+
+// the high words of n*pi/2 for n = 1 to 32
+const NPIO2_HW: [i32; 32] = [
+    0x3FF921FB, 0x400921FB, 0x4012D97C, 0x401921FB, 0x401F6A7A, 0x4022D97C, 0x4025FDBB, 0x402921FB, 0x402C463A,
+    0x402F6A7A, 0x4031475C, 0x4032D97C, 0x40346B9C, 0x4035FDBB, 0x40378FDB, 0x403921FB, 0x403AB41B, 0x403C463A,
+    0x403DD85A, 0x403F6A7A, 0x40407E4C, 0x4041475C, 0x4042106C, 0x4042D97C, 0x4043A28C, 0x40446B9C, 0x404534AC,
+    0x4045FDBB, 0x4046C6CB, 0x40478FDB, 0x404858EB, 0x404921FB,
+];
+
+const INV_PIO2: f64 = 6.36619772367581382433e-01;
+const PIO2_1: f64 = 1.57079632673412561417e+00;
+const PIO2_1T: f64 = 6.07710050650619224932e-11;
+const PIO2_2: f64 = 6.07710050630396597660e-11;
+const PIO2_2T: f64 = 2.02226624879595063154e-21;
+const PIO2_3: f64 = 2.02226624871116645580e-21;
+const PIO2_3T: f64 = 8.47842766036889956997e-32;
+
+// returns x modulo pi/2 as y[0] + y[1] and the quotient
+fn rem_pio2(x: f64, y: &mut [f64; 2]) -> i32 {
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        y[0] = x;
+        y[1] = 0.0;
+        return 0;
+    }
+
+    // |x| < 3pi/4, special case with n = +-1
+    if ix < 0x4002d97c {
+        if hx > 0 {
+            let mut z = x - PIO2_1;
+            if ix != 0x3ff921fb {
+                y[0] = z - PIO2_1T;
+                y[1] = (z - y[0]) - PIO2_1T;
+            } else {
+                z -= PIO2_2;
+                y[0] = z - PIO2_2T;
+                y[1] = (z - y[0]) - PIO2_2T;
+            }
+            return 1;
+        } else {
+            let mut z = x + PIO2_1;
+            if ix != 0x3ff921fb {
+                y[0] = z + PIO2_1T;
+                y[1] = (z - y[0]) + PIO2_1T;
+            } else {
+                z += PIO2_2;
+                y[0] = z + PIO2_2T;
+                y[1] = (z - y[0]) + PIO2_2T;
+            }
+            return -1;
+        }
+    }
+
+    // |x| <= 2^19 * pi/2, medium size
+    if ix <= 0x413921fb {
+        let t = x.abs();
+        let n = (t * INV_PIO2 + HALF) as i32;
+        let fn_ = n as f64;
+        let mut r = t - fn_ * PIO2_1;
+        let mut w = fn_ * PIO2_1T;
+        if n < 32 && ix != NPIO2_HW[n as usize - 1] {
+            y[0] = r - w;
+        } else {
+            let j = ix >> 20;
+            y[0] = r - w;
+            let i = j - ((get_high_word(y[0]) >> 20) & 0x7ff);
+            if i > 16 {
+                let t = r;
+                w = fn_ * PIO2_2;
+                r = t - w;
+                w = fn_ * PIO2_2T - ((t - r) - w);
+                y[0] = r - w;
+                let i = j - ((get_high_word(y[0]) >> 20) & 0x7ff);
+                if i > 49 {
+                    let t = r;
+                    w = fn_ * PIO2_3;
+                    r = t - w;
+                    w = fn_ * PIO2_3T - ((t - r) - w);
+                    y[0] = r - w;
+                }
+            }
+        }
+        y[1] = (r - y[0]) - w;
+        if hx < 0 {
+            y[0] = -y[0];
+            y[1] = -y[1];
+            return -n;
+        }
+        return n;
+    }
+
+    if ix >= 0x7ff00000 {
+        y[0] = x - x;
+        y[1] = y[0];
+        return 0;
+    }
+
+    // large arguments are split into 24 bit chunks
+    let e0 = (ix >> 20) - 1046;
+    let mut z = from_words(ix - (e0 << 20), get_low_word(x));
+    let mut tx = [0.0; 3];
+    for value in tx.iter_mut().take(2) {
+        *value = (z as i32) as f64;
+        z = (z - *value) * TWO24;
+    }
+    tx[2] = z;
+    let mut nx = 3;
+    while tx[nx - 1] == ZERO {
+        nx -= 1;
+    }
+    let n = kernel_rem_pio2(&tx[..nx], y, e0);
+    if hx < 0 {
+        y[0] = -y[0];
+        y[1] = -y[1];
+        return -n;
+    }
+    n
+}
+
+const S1: f64 = -1.66666666666666324348e-01;
+const S2: f64 = 8.33333333332248946124e-03;
+const S3: f64 = -1.98412698298579493134e-04;
+const S4: f64 = 2.75573137070700676789e-06;
+const S5: f64 = -2.50507602534068634195e-08;
+const S6: f64 = 1.58969099521155010221e-10;
+
+// sin on [-pi/4, pi/4], y is the tail of x and iy tells whether y is zero
+fn kernel_sin(x: f64, y: f64, iy: i32) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix < 0x3e400000 && x as i32 == 0 {
+        return x;
+    }
+    let z = x * x;
+    let v = z * x;
+    let r = S2 + z * (S3 + z * (S4 + z * (S5 + z * S6)));
+    if iy == 0 {
+        x + v * (S1 + z * r)
+    } else {
+        x - ((z * (HALF * y - v * r) - y) - v * S1)
+    }
+}
+
+const C1: f64 = 4.16666666666666019037e-02;
+const C2: f64 = -1.38888888888741095749e-03;
+const C3: f64 = 2.48015872894767294178e-05;
+const C4: f64 = -2.75573143513906633035e-07;
+const C5: f64 = 2.08757232129817482790e-09;
+const C6: f64 = -1.13596475577881948265e-11;
+
+fn kernel_cos(x: f64, y: f64) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix < 0x3e400000 && x as i32 == 0 {
+        return ONE;
+    }
+    let z = x * x;
+    let r = z * (C1 + z * (C2 + z * (C3 + z * (C4 + z * (C5 + z * C6)))));
+    if ix < 0x3FD33333 {
+        ONE - (0.5 * z - (z * r - x * y))
+    } else {
+        let qx = match ix > 0x3fe90000 {
+            true => 0.28125,
+            false => from_words(ix - 0x00200000, 0),
+        };
+        let hz = 0.5 * z - qx;
+        let a = ONE - qx;
+        a - (hz - (z * r - x * y))
+    }
+}
+
+const T: [f64; 13] = [
+    3.33333333333334091986e-01,
+    1.33333333333201242699e-01,
+    5.39682539762260521377e-02,
+    2.18694882948595424599e-02,
+    8.86323982359930005737e-03,
+    3.59207910759131235356e-03,
+    1.45620945432529025516e-03,
+    5.88041240820264096874e-04,
+    2.46463134818469906812e-04,
+    7.81794442939557092300e-05,
+    7.14072491382608190305e-05,
+    -1.85586374855275456654e-05,
+    2.59073051863633712884e-05,
+];
+
+// tan on [-pi/4, pi/4], returns -1/tan if iy is -1
+fn kernel_tan(x: f64, y: f64, iy: i32) -> f64 {
+    let mut x = x;
+    let mut y = y;
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix < 0x3e300000 && x as i32 == 0 {
+        if ((ix as u32 | get_low_word(x)) | (iy + 1) as u32) == 0 {
+            return ONE / x.abs();
+        } else if iy == 1 {
+            return x;
+        } else {
+            // compute -1 / (x+y) carefully
+            let w = x + y;
+            let z = set_low_word(w, 0);
+            let v = y - (z - x);
+            let a = -ONE / w;
+            let t = set_low_word(a, 0);
+            let s = ONE + t * z;
+            return t + a * (s + t * v);
+        }
+    }
+    if ix >= 0x3FE59428 {
+        if hx < 0 {
+            x = -x;
+            y = -y;
+        }
+        let z = PIO4_HI - x;
+        let w = PIO4_LO - y;
+        x = z + w;
+        y = 0.0;
+    }
+    let z = x * x;
+    let w = z * z;
+    let r = T[1] + w * (T[3] + w * (T[5] + w * (T[7] + w * (T[9] + w * T[11]))));
+    let v = z * (T[2] + w * (T[4] + w * (T[6] + w * (T[8] + w * (T[10] + w * T[12])))));
+    let s = z * x;
+    let r = y + z * (s * (r + v) + y);
+    let r = r + T[0] * s;
+    let w = x + r;
+    if ix >= 0x3FE59428 {
+        let v = iy as f64;
+        return (1 - ((hx >> 30) & 2)) as f64 * (v - 2.0 * (x - (w * w / (w + v) - r)));
+    }
+    if iy == 1 {
+        w
+    } else {
+        // compute -1.0 / (x+r) accurately
+        let z = set_low_word(w, 0);
+        let v = r - (z - x);
+        let a = -1.0 / w;
+        let t = set_low_word(a, 0);
+        let s = 1.0 + t * z;
+        t + a * (s + t * v)
+    }
+}
+
+pub fn sin(x: f64) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_sin(x, ZERO, 0);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let mut y = [0.0; 2];
+    match rem_pio2(x, &mut y) & 3 {
+        0 => kernel_sin(y[0], y[1], 1),
+        1 => kernel_cos(y[0], y[1]),
+        2 => -kernel_sin(y[0], y[1], 1),
+        _ => -kernel_cos(y[0], y[1]),
+    }
+}
+
+pub fn cos(x: f64) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_cos(x, ZERO);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let mut y = [0.0; 2];
+    match rem_pio2(x, &mut y) & 3 {
+        0 => kernel_cos(y[0], y[1]),
+        1 => -kernel_sin(y[0], y[1], 1),
+        2 => -kernel_cos(y[0], y[1]),
+        _ => kernel_sin(y[0], y[1], 1),
+    }
+}
+
+pub fn tan(x: f64) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix <= 0x3fe921fb {
+        return kernel_tan(x, ZERO, 1);
+    }
+    if ix >= 0x7ff00000 {
+        return x - x;
+    }
+    let mut y = [0.0; 2];
+    let n = rem_pio2(x, &mut y);
+    kernel_tan(y[0], y[1], 1 - ((n & 1) << 1))
+}
+
+const PS0: f64 = 1.66666666666666657415e-01;
+const PS1: f64 = -3.25565818622400915405e-01;
+const PS2: f64 = 2.01212532134862925881e-01;
+const PS3: f64 = -4.00555345006794114027e-02;
+const PS4: f64 = 7.91534994289814532176e-04;
+const PS5: f64 = 3.47933107596021167570e-05;
+const QS1: f64 = -2.40339491173441421878e+00;
+const QS2: f64 = 2.02094576023350569471e+00;
+const QS3: f64 = -6.88283971605453293030e-01;
+const QS4: f64 = 7.70381505559019352791e-02;
+
+pub fn asin(x: f64) -> f64 {
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix >= 0x3ff00000 {
+        if ((ix - 0x3ff00000) as u32 | get_low_word(x)) == 0 {
+            return x * PIO2_HI + x * PIO2_LO;
+        }
+        return (x - x) / (x - x);
+    } else if ix < 0x3fe00000 {
+        if ix < 0x3e400000 && HUGE + x > ONE {
+            return x;
+        }
+        let t = x * x;
+        let p = t * (PS0 + t * (PS1 + t * (PS2 + t * (PS3 + t * (PS4 + t * PS5)))));
+        let q = ONE + t * (QS1 + t * (QS2 + t * (QS3 + t * QS4)));
+        let w = p / q;
+        return x + x * w;
+    }
+
+    let w = ONE - x.abs();
+    let mut t = w * 0.5;
+    let mut p = t * (PS0 + t * (PS1 + t * (PS2 + t * (PS3 + t * (PS4 + t * PS5)))));
+    let mut q = ONE + t * (QS1 + t * (QS2 + t * (QS3 + t * QS4)));
+    let s = t.sqrt();
+    if ix >= 0x3FEF3333 {
+        let w = p / q;
+        t = PIO2_HI - (2.0 * (s + s * w) - PIO2_LO);
+    } else {
+        let w = set_low_word(s, 0);
+        let c = (t - w * w) / (s + w);
+        let r = p / q;
+        p = 2.0 * s * r - (PIO2_LO - 2.0 * c);
+        q = PIO4_HI - 2.0 * w;
+        t = PIO4_HI - (p - q);
+    }
+    if hx > 0 {
+        t
+    } else {
+        -t
+    }
+}
+
+pub fn acos(x: f64) -> f64 {
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    if ix >= 0x3ff00000 {
+        if ((ix - 0x3ff00000) as u32 | get_low_word(x)) == 0 {
+            if hx > 0 {
+                return 0.0;
+            }
+            return PI + 2.0 * PIO2_LO;
+        }
+        return (x - x) / (x - x);
+    }
+    if ix < 0x3fe00000 {
+        if ix <= 0x3c600000 {
+            return PIO2_HI + PIO2_LO;
+        }
+        let z = x * x;
+        let p = z * (PS0 + z * (PS1 + z * (PS2 + z * (PS3 + z * (PS4 + z * PS5)))));
+        let q = ONE + z * (QS1 + z * (QS2 + z * (QS3 + z * QS4)));
+        let r = p / q;
+        PIO2_HI - (x - (PIO2_LO - x * r))
+    } else if hx < 0 {
+        let z = (ONE + x) * 0.5;
+        let p = z * (PS0 + z * (PS1 + z * (PS2 + z * (PS3 + z * (PS4 + z * PS5)))));
+        let q = ONE + z * (QS1 + z * (QS2 + z * (QS3 + z * QS4)));
+        let s = z.sqrt();
+        let r = p / q;
+        let w = r * s - PIO2_LO;
+        PI - 2.0 * (s + w)
+    } else {
+        let z = (ONE - x) * 0.5;
+        let s = z.sqrt();
+        let df = set_low_word(s, 0);
+        let c = (z - df * df) / (s + df);
+        let p = z * (PS0 + z * (PS1 + z * (PS2 + z * (PS3 + z * (PS4 + z * PS5)))));
+        let q = ONE + z * (QS1 + z * (QS2 + z * (QS3 + z * QS4)));
+        let r = p / q;
+        let w = r * s + c;
+        2.0 * (df + w)
+    }
+}
+
+const ATAN_HI: [f64; 4] =
+    [4.63647609000806093515e-01, 7.85398163397448278999e-01, 9.82793723247329054082e-01, 1.57079632679489655800e+00];
+const ATAN_LO: [f64; 4] =
+    [2.26987774529616870924e-17, 3.06161699786838301793e-17, 1.39033110312309984516e-17, 6.12323399573676603587e-17];
+const AT: [f64; 11] = [
+    3.33333333333329318027e-01,
+    -1.99999999998764832476e-01,
+    1.42857142725034663711e-01,
+    -1.11111104054623557880e-01,
+    9.09088713343650656196e-02,
+    -7.69187620504482999495e-02,
+    6.66107313738753120669e-02,
+    -5.83357013379057348645e-02,
+    4.97687799461593236017e-02,
+    -3.65315727442169155270e-02,
+    1.62858201153657823623e-02,
+];
+
+pub fn atan(x: f64) -> f64 {
+    let mut x = x;
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    let id;
+    if ix >= 0x44100000 {
+        if ix > 0x7ff00000 || (ix == 0x7ff00000 && get_low_word(x) != 0) {
+            return x + x;
+        }
+        if hx > 0 {
+            return ATAN_HI[3] + ATAN_LO[3];
+        }
+        return -ATAN_HI[3] - ATAN_LO[3];
+    }
+    if ix < 0x3fdc0000 {
+        if ix < 0x3e200000 && HUGE + x > ONE {
+            return x;
+        }
+        id = -1;
+    } else {
+        x = x.abs();
+        if ix < 0x3ff30000 {
+            if ix < 0x3fe60000 {
+                id = 0;
+                x = (2.0 * x - ONE) / (2.0 + x);
+            } else {
+                id = 1;
+                x = (x - ONE) / (x + ONE);
+            }
+        } else if ix < 0x40038000 {
+            id = 2;
+            x = (x - 1.5) / (ONE + 1.5 * x);
+        } else {
+            id = 3;
+            x = -1.0 / x;
+        }
+    }
+
+    let z = x * x;
+    let w = z * z;
+    let s1 = z * (AT[0] + w * (AT[2] + w * (AT[4] + w * (AT[6] + w * (AT[8] + w * AT[10])))));
+    let s2 = w * (AT[1] + w * (AT[3] + w * (AT[5] + w * (AT[7] + w * AT[9]))));
+    if id < 0 {
+        return x - x * (s1 + s2);
+    }
+    let id = id as usize;
+    let z = ATAN_HI[id] - ((x * (s1 + s2) - ATAN_LO[id]) - x);
+    if hx < 0 {
+        -z
+    } else {
+        z
+    }
+}
+
+pub fn atan2(y: f64, x: f64) -> f64 {
+    const PI_O_4: f64 = 7.8539816339744827900E-01;
+    const PI_O_2: f64 = 1.5707963267948965580E+00;
+
+    let hx = get_high_word(x);
+    let ix = hx & 0x7fffffff;
+    let lx = get_low_word(x);
+    let hy = get_high_word(y);
+    let iy = hy & 0x7fffffff;
+    let ly = get_low_word(y);
+    if (ix as u32 | ((lx | lx.wrapping_neg()) >> 31)) > 0x7ff00000
+        || (iy as u32 | ((ly | ly.wrapping_neg()) >> 31)) > 0x7ff00000
+    {
+        return x + y;
+    }
+    if (hx.wrapping_sub(0x3ff00000) as u32 | lx) == 0 {
+        return atan(y);
+    }
+    let m = ((hy >> 31) & 1) | ((hx >> 30) & 2);
+
+    if (iy as u32 | ly) == 0 {
+        return match m {
+            0 | 1 => y,
+            2 => PI + TINY,
+            _ => -PI - TINY,
+        };
+    }
+    if (ix as u32 | lx) == 0 {
+        return if hy < 0 {
+            -PI_O_2 - TINY
+        } else {
+            PI_O_2 + TINY
+        };
+    }
+    if ix == 0x7ff00000 {
+        if iy == 0x7ff00000 {
+            return match m {
+                0 => PI_O_4 + TINY,
+                1 => -PI_O_4 - TINY,
+                2 => 3.0 * PI_O_4 + TINY,
+                _ => -3.0 * PI_O_4 - TINY,
+            };
+        } else {
+            return match m {
+                0 => ZERO,
+                1 => -ZERO,
+                2 => PI + TINY,
+                _ => -PI - TINY,
+            };
+        }
+    }
+    if iy == 0x7ff00000 {
+        return if hy < 0 {
+            -PI_O_2 - TINY
+        } else {
+            PI_O_2 + TINY
+        };
+    }
+
+    let k = (iy - ix) >> 20;
+    let z = if k > 60 {
+        PI_O_2 + 0.5 * PI_LO
+    } else if hx < 0 && k < -60 {
+        0.0
+    } else {
+        atan((y / x).abs())
+    };
+    match m {
+        0 => z,
+        1 => -z,
+        2 => PI - (z - PI_LO),
+        _ => (z - PI_LO) - PI,
+    }
+}
+
+const P1: f64 = 1.66666666666666019037e-01;
+const P2: f64 = -2.77777777770155933842e-03;
+const P3: f64 = 6.61375632143793436117e-05;
+const P4: f64 = -1.65339022054652515390e-06;
+const P5: f64 = 4.13813679705723846039e-08;
+
+pub fn exp(x: f64) -> f64 {
+    const HALF_SIGNED: [f64; 2] = [0.5, -0.5];
+    const LN2_HI_SIGNED: [f64; 2] = [LN2_HI, -LN2_HI];
+    const LN2_LO_SIGNED: [f64; 2] = [LN2_LO, -LN2_LO];
+
+    let mut x = x;
+    let mut hi = 0.0;
+    let mut lo = 0.0;
+    let mut k = 0;
+    let mut hx = get_high_word(x) as u32;
+    let xsb = ((hx >> 31) & 1) as usize;
+    hx &= 0x7fffffff;
+
+    if hx >= 0x40862E42 {
+        if hx >= 0x7ff00000 {
+            if ((hx & 0xfffff) | get_low_word(x)) != 0 {
+                return x + x;
+            }
+            return if xsb == 0 {
+                x
+            } else {
+                0.0
+            };
+        }
+        if x > EXP_OVERFLOW_THRESHOLD {
+            return HUGE * HUGE;
+        }
+        if x < EXP_UNDERFLOW_THRESHOLD {
+            return TWO_N1000 * TWO_N1000;
+        }
+    }
+
+    if hx > 0x3fd62e42 {
+        if hx < 0x3FF0A2B2 {
+            hi = x - LN2_HI_SIGNED[xsb];
+            lo = LN2_LO_SIGNED[xsb];
+            k = 1 - xsb as i32 - xsb as i32;
+        } else {
+            k = (INV_LN2 * x + HALF_SIGNED[xsb]) as i32;
+            let t = k as f64;
+            hi = x - t * LN2_HI_SIGNED[0];
+            lo = t * LN2_LO_SIGNED[0];
+        }
+        x = hi - lo;
+    } else if hx < 0x3e300000 {
+        if HUGE + x > ONE {
+            return ONE + x;
+        }
+    } else {
+        k = 0;
+    }
+
+    let t = x * x;
+    let c = x - t * (P1 + t * (P2 + t * (P3 + t * (P4 + t * P5))));
+    if k == 0 {
+        return ONE - ((x * c) / (c - 2.0) - x);
+    }
+    let y = ONE - ((lo - (x * c) / (2.0 - c)) - hi);
+    if k >= -1021 {
+        set_high_word(y, get_high_word(y).wrapping_add(k << 20))
+    } else {
+        set_high_word(y, get_high_word(y).wrapping_add((k + 1000) << 20)) * TWO_N1000
+    }
+}
+
+const LG1: f64 = 6.666666666666735130e-01;
+const LG2: f64 = 3.999999999940941908e-01;
+const LG3: f64 = 2.857142874366239149e-01;
+const LG4: f64 = 2.222219843214978396e-01;
+const LG5: f64 = 1.818357216161805012e-01;
+const LG6: f64 = 1.531383769920937332e-01;
+const LG7: f64 = 1.479819860511658591e-01;
+
+pub fn log(x: f64) -> f64 {
+    let mut x = x;
+    let mut hx = get_high_word(x);
+    let lx = get_low_word(x);
+
+    let mut k = 0;
+    if hx < 0x00100000 {
+        if ((hx & 0x7fffffff) as u32 | lx) == 0 {
+            return -TWO54 / ZERO;
+        }
+        if hx < 0 {
+            return (x - x) / ZERO;
+        }
+        k -= 54;
+        x *= TWO54;
+        hx = get_high_word(x);
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    k += (hx >> 20) - 1023;
+    hx &= 0x000fffff;
+    let i = (hx + 0x95f64) & 0x100000;
+    x = set_high_word(x, hx | (i ^ 0x3ff00000));
+    k += i >> 20;
+    let f = x - 1.0;
+    if (0x000fffff & (2 + hx)) < 3 {
+        if f == ZERO {
+            if k == 0 {
+                return ZERO;
+            }
+            let dk = k as f64;
+            return dk * LN2_HI + dk * LN2_LO;
+        }
+        let r = f * f * (0.5 - 0.33333333333333333 * f);
+        if k == 0 {
+            return f - r;
+        }
+        let dk = k as f64;
+        return dk * LN2_HI - ((r - dk * LN2_LO) - f);
+    }
+    let s = f / (2.0 + f);
+    let dk = k as f64;
+    let z = s * s;
+    let mut i = hx - 0x6147a;
+    let w = z * z;
+    let j = 0x6b851 - hx;
+    let t1 = w * (LG2 + w * (LG4 + w * LG6));
+    let t2 = z * (LG1 + w * (LG3 + w * (LG5 + w * LG7)));
+    i |= j;
+    let r = t2 + t1;
+    if i > 0 {
+        let hfsq = 0.5 * f * f;
+        if k == 0 {
+            f - (hfsq - s * (hfsq + r))
+        } else {
+            dk * LN2_HI - ((hfsq - (s * (hfsq + r) + dk * LN2_LO)) - f)
+        }
+    } else if k == 0 {
+        f - s * (f - r)
+    } else {
+        dk * LN2_HI - ((s * (f - r) - dk * LN2_LO) - f)
+    }
+}
+
+pub fn log10(x: f64) -> f64 {
+    const IVLN10: f64 = 4.34294481903251816668e-01;
+    const LOG10_2HI: f64 = 3.01029995663611771306e-01;
+    const LOG10_2LO: f64 = 3.69423907715893078616e-13;
+
+    let mut x = x;
+    let mut hx = get_high_word(x);
+    let lx = get_low_word(x);
+
+    let mut k = 0;
+    if hx < 0x00100000 {
+        if ((hx & 0x7fffffff) as u32 | lx) == 0 {
+            return -TWO54 / ZERO;
+        }
+        if hx < 0 {
+            return (x - x) / ZERO;
+        }
+        k -= 54;
+        x *= TWO54;
+        hx = get_high_word(x);
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    k += (hx >> 20) - 1023;
+    let i = ((k as u32 & 0x80000000) >> 31) as i32;
+    hx = (hx & 0x000fffff) | ((0x3ff - i) << 20);
+    let y = (k + i) as f64;
+    x = set_high_word(x, hx);
+    let z = y * LOG10_2LO + IVLN10 * log(x);
+    z + y * LOG10_2HI
+}
+
+// the square root is correctly rounded by IEEE 754, which is also what fdlibm computes
+pub fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+pub fn cbrt(x: f64) -> f64 {
+    const B1: u32 = 715094163;
+    const B2: u32 = 696219795;
+    const C: f64 = 5.42857142857142815906e-01;
+    const D: f64 = -7.05306122448979611050e-01;
+    const E: f64 = 1.41428571428571436819e+00;
+    const F: f64 = 1.60714285714285720630e+00;
+    const G: f64 = 3.57142857142857150787e-01;
+
+    let mut hx = get_high_word(x) as u32;
+    let sign = hx & 0x80000000;
+    hx ^= sign;
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    if (hx | get_low_word(x)) == 0 {
+        return x;
+    }
+
+    let x = set_high_word(x, hx as i32);
+    // rough cbrt to 5 bits
+    let mut t = if hx < 0x00100000 {
+        let t = from_words(0x43500000, 0) * x;
+        set_high_word(t, (get_high_word(t) as u32 / 3 + B2) as i32)
+    } else {
+        from_words((hx / 3 + B1) as i32, 0)
+    };
+
+    // new cbrt to 23 bits
+    let r = t * t / x;
+    let s = C + r * t;
+    t *= G + F / (s + E + D / s);
+
+    // chopped to 20 bits and made larger than cbrt(x)
+    t = from_words(get_high_word(t) + 1, 0);
+
+    // one step newton iteration to 53 bits with error less than 0.667 ulps
+    let s = t * t;
+    let r = x / s;
+    let w = t + t;
+    let r = (r - t) / (w + r);
+    t += t * r;
+
+    set_high_word(t, (get_high_word(t) as u32 | sign) as i32)
+}
+
+pub fn remainder(x: f64, p: f64) -> f64 {
+    let mut x = x;
+    let mut p = p;
+    let mut hx = get_high_word(x);
+    let lx = get_low_word(x);
+    let mut hp = get_high_word(p);
+    let lp = get_low_word(p);
+    let sx = hx as u32 & 0x80000000;
+    hp &= 0x7fffffff;
+    hx &= 0x7fffffff;
+
+    if (hp as u32 | lp) == 0 {
+        return (x * p) / (x * p);
+    }
+    if hx >= 0x7ff00000 || (hp >= 0x7ff00000 && ((hp - 0x7ff00000) as u32 | lp) != 0) {
+        return (x * p) / (x * p);
+    }
+
+    // fmod is exact, so the remainder operator of Rust gives the same result
+    if hp <= 0x7fdfffff {
+        x %= p + p;
+    }
+    if ((hx - hp) as u32 | lx.wrapping_sub(lp)) == 0 {
+        return ZERO * x;
+    }
+    x = x.abs();
+    p = p.abs();
+    if hp < 0x00200000 {
+        if x + x > p {
+            x -= p;
+            if x + x >= p {
+                x -= p;
+            }
+        }
+    } else {
+        let p_half = 0.5 * p;
+        if x > p_half {
+            x -= p;
+            if x >= p_half {
+                x -= p;
+            }
+        }
+    }
+    set_high_word(x, (get_high_word(x) as u32 ^ sx) as i32)
+}
+
+pub fn pow(x: f64, y: f64) -> f64 {
+    const BP: [f64; 2] = [1.0, 1.5];
+    const DP_H: [f64; 2] = [0.0, 5.84962487220764160156e-01];
+    const DP_L: [f64; 2] = [0.0, 1.35003920212974897128e-08];
+    const L1: f64 = 5.99999999999994648725e-01;
+    const L2: f64 = 4.28571428578550184252e-01;
+    const L3: f64 = 3.33333329818377432918e-01;
+    const L4: f64 = 2.72728123808534006489e-01;
+    const L5: f64 = 2.30660745775561754067e-01;
+    const L6: f64 = 2.06975017800338417784e-01;
+    const LG2: f64 = 6.93147180559945286227e-01;
+    const LG2_H: f64 = 6.93147182464599609375e-01;
+    const LG2_L: f64 = -1.90465429995776804525e-09;
+    const OVT: f64 = 8.0085662595372944372e-17;
+    const CP: f64 = 9.61796693925975554329e-01;
+    const CP_H: f64 = 9.61796700954437255859e-01;
+    const CP_L: f64 = -7.02846165095275826516e-09;
+    const IVLN2: f64 = 1.44269504088896338700e+00;
+    const IVLN2_H: f64 = 1.44269502162933349609e+00;
+    const IVLN2_L: f64 = 1.92596299112661746887e-08;
+
+    let hx = get_high_word(x);
+    let lx = get_low_word(x);
+    let hy = get_high_word(y);
+    let ly = get_low_word(y);
+    let mut ix = hx & 0x7fffffff;
+    let iy = hy & 0x7fffffff;
+
+    // y is zero
+    if (iy as u32 | ly) == 0 {
+        return ONE;
+    }
+
+    // x or y is NaN
+    if ix > 0x7ff00000 || (ix == 0x7ff00000 && lx != 0) || iy > 0x7ff00000 || (iy == 0x7ff00000 && ly != 0) {
+        return x + y;
+    }
+
+    // whether y is not an integer (0), an odd integer (1) or an even integer (2) when x is negative
+    let mut yisint = 0;
+    if hx < 0 {
+        if iy >= 0x43400000 {
+            yisint = 2;
+        } else if iy >= 0x3ff00000 {
+            let k = (iy >> 20) - 0x3ff;
+            if k > 20 {
+                let j = ly >> (52 - k);
+                if (j << (52 - k)) == ly {
+                    yisint = 2 - (j & 1) as i32;
+                }
+            } else if ly == 0 {
+                let j = iy >> (20 - k);
+                if (j << (20 - k)) == iy {
+                    yisint = 2 - (j & 1);
+                }
+            }
+        }
+    }
+
+    // special values of y
+    if ly == 0 {
+        if iy == 0x7ff00000 {
+            if ((ix - 0x3ff00000) as u32 | lx) == 0 {
+                return y - y;
+            } else if ix >= 0x3ff00000 {
+                return if hy >= 0 {
+                    y
+                } else {
+                    ZERO
+                };
+            } else {
+                return if hy < 0 {
+                    -y
+                } else {
+                    ZERO
+                };
+            }
+        }
+        if iy == 0x3ff00000 {
+            return if hy < 0 {
+                ONE / x
+            } else {
+                x
+            };
+        }
+        if hy == 0x40000000 {
+            return x * x;
+        }
+        if hy == 0x3fe00000 && hx >= 0 {
+            return x.sqrt();
+        }
+    }
+
+    let mut ax = x.abs();
+    // special values of x
+    if lx == 0 && (ix == 0x7ff00000 || ix == 0 || ix == 0x3ff00000) {
+        let mut z = ax;
+        if hy < 0 {
+            z = ONE / z;
+        }
+        if hx < 0 {
+            if ((ix - 0x3ff00000) | yisint) == 0 {
+                z = (z - z) / (z - z);
+            } else if yisint == 1 {
+                z = -z;
+            }
+        }
+        return z;
+    }
+
+    let mut n = (hx >> 31) + 1;
+
+    // (x<0)**(non-int) is NaN
+    if (n | yisint) == 0 {
+        return (x - x) / (x - x);
+    }
+
+    // the sign of the result
+    let mut s = ONE;
+    if (n | (yisint - 1)) == 0 {
+        s = -ONE;
+    }
+
+    let t1;
+    let t2;
+    if iy > 0x41e00000 {
+        // |y| is huge
+        if iy > 0x43f00000 {
+            if ix <= 0x3fefffff {
+                return if hy < 0 {
+                    HUGE * HUGE
+                } else {
+                    TINY * TINY
+                };
+            }
+            if ix >= 0x3ff00000 {
+                return if hy > 0 {
+                    HUGE * HUGE
+                } else {
+                    TINY * TINY
+                };
+            }
+        }
+        if ix < 0x3fefffff {
+            return if hy < 0 {
+                s * HUGE * HUGE
+            } else {
+                s * TINY * TINY
+            };
+        }
+        if ix > 0x3ff00000 {
+            return if hy > 0 {
+                s * HUGE * HUGE
+            } else {
+                s * TINY * TINY
+            };
+        }
+        // |1-x| is tiny, so log(x) is x-x^2/2+x^3/3-x^4/4
+        let t = ax - ONE;
+        let w = (t * t) * (0.5 - t * (0.3333333333333333333333 - t * 0.25));
+        let u = IVLN2_H * t;
+        let v = t * IVLN2_L - w * IVLN2;
+        t1 = set_low_word(u + v, 0);
+        t2 = v - (t1 - u);
+    } else {
+        n = 0;
+        // subnormal x
+        if ix < 0x00100000 {
+            ax *= TWO53;
+            n -= 53;
+            ix = get_high_word(ax);
+        }
+        n += (ix >> 20) - 0x3ff;
+        let j = ix & 0x000fffff;
+        // determine the interval
+        ix = j | 0x3ff00000;
+        let k = if j <= 0x3988E {
+            0
+        } else if j < 0xBB67A {
+            1
+        } else {
+            n += 1;
+            ix -= 0x00100000;
+            0
+        };
+        ax = set_high_word(ax, ix);
+
+        // compute ss = s_h+s_l = (x-1)/(x+1) or (x-1.5)/(x+1.5)
+        let u = ax - BP[k];
+        let v = ONE / (ax + BP[k]);
+        let ss = u * v;
+        let s_h = set_low_word(ss, 0);
+        // t_h = ax+bp[k] high
+        let t_h = from_words(((ix >> 1) | 0x20000000) + 0x00080000 + ((k as i32) << 18), 0);
+        let t_l = ax - (t_h - BP[k]);
+        let s_l = v * ((u - s_h * t_h) - s_h * t_l);
+        // compute log(ax)
+        let s2 = ss * ss;
+        let mut r = s2 * s2 * (L1 + s2 * (L2 + s2 * (L3 + s2 * (L4 + s2 * (L5 + s2 * L6)))));
+        r += s_l * (s_h + ss);
+        let s2 = s_h * s_h;
+        let t_h = set_low_word(3.0 + s2 + r, 0);
+        let t_l = r - ((t_h - 3.0) - s2);
+        // u+v = ss*(1+...)
+        let u = s_h * t_h;
+        let v = s_l * t_h + t_l * ss;
+        // 2/(3log2)*(ss+...)
+        let p_h = set_low_word(u + v, 0);
+        let p_l = v - (p_h - u);
+        let z_h = CP_H * p_h;
+        let z_l = CP_L * p_h + p_l * CP + DP_L[k];
+        // log2(ax) = (ss+..)*2/(3*log2) = n + dp_h + z_h + z_l
+        let t = n as f64;
+        t1 = set_low_word(((z_h + z_l) + DP_H[k]) + t, 0);
+        t2 = z_l - (((t1 - t) - DP_H[k]) - z_h);
+    }
+
+    // split up y into y1+y2 and compute (y1+y2)*(t1+t2)
+    let y1 = set_low_word(y, 0);
+    let p_l = (y - y1) * t1 + y * t2;
+    let mut p_h = y1 * t1;
+    let z = p_l + p_h;
+    let j = get_high_word(z);
+    let i = get_low_word(z);
+    if j >= 0x40900000 {
+        // z >= 1024
+        if ((j - 0x40900000) as u32 | i) != 0 || p_l + OVT > z - p_h {
+            return s * HUGE * HUGE;
+        }
+    } else if (j & 0x7fffffff) >= 0x4090cc00 {
+        // z <= -1075
+        if ((j as u32).wrapping_sub(0xc090cc00) | i) != 0 || p_l <= z - p_h {
+            return s * TINY * TINY;
+        }
+    }
+
+    // compute 2**(p_h+p_l)
+    let i = j & 0x7fffffff;
+    let mut k = (i >> 20) - 0x3ff;
+    n = 0;
+    if i > 0x3fe00000 {
+        // |z| > 0.5, set n = [z+0.5]
+        n = j + (0x00100000 >> (k + 1));
+        k = ((n & 0x7fffffff) >> 20) - 0x3ff;
+        let t = from_words(n & !(0x000fffff >> k), 0);
+        n = ((n & 0x000fffff) | 0x00100000) >> (20 - k);
+        if j < 0 {
+            n = -n;
+        }
+        p_h -= t;
+    }
+    let t = set_low_word(p_l + p_h, 0);
+    let u = t * LG2_H;
+    let v = (p_l - (t - p_h)) * LG2 + t * LG2_L;
+    let mut z = u + v;
+    let w = v - (z - u);
+    let t = z * z;
+    let t1 = z - t * (P1 + t * (P2 + t * (P3 + t * (P4 + t * P5))));
+    let r = (z * t1) / (t1 - TWO) - (w + z * w);
+    z = ONE - (r - z);
+    let j = get_high_word(z).wrapping_add(n << 20);
+    if (j >> 20) <= 0 {
+        z = scalbn(z, n);
+    } else {
+        z = set_high_word(z, j);
+    }
+    s * z
+}
+
+pub fn sinh(x: f64) -> f64 {
+    const SHUGE: f64 = 1.0e307;
+
+    let jx = get_high_word(x);
+    let ix = jx & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        return x + x;
+    }
+
+    let h = if jx < 0 {
+        -0.5
+    } else {
+        0.5
+    };
+    // |x| in [0,22], return sign(x)*0.5*(E+E/(E+1)))
+    if ix < 0x40360000 {
+        if ix < 0x3e300000 && SHUGE + x > ONE {
+            return x;
+        }
+        let t = expm1(x.abs());
+        if ix < 0x3ff00000 {
+            return h * (2.0 * t - t * t / (t + ONE));
+        }
+        return h * (t + t / (t + ONE));
+    }
+
+    // |x| in [22, log(maxdouble)] return 0.5*exp(|x|)
+    if ix < 0x40862E42 {
+        return h * exp(x.abs());
+    }
+
+    // |x| in [log(maxdouble), overflowthresold]
+    let lx = get_low_word(x);
+    if ix < 0x408633CE || (ix == 0x408633ce && lx <= 0x8fb9f87d) {
+        let w = exp(0.5 * x.abs());
+        let t = h * w;
+        return t * w;
+    }
+
+    x * SHUGE
+}
+
+pub fn cosh(x: f64) -> f64 {
+    let ix = get_high_word(x) & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        return x * x;
+    }
+
+    // |x| in [0,0.5*ln2], return 1+expm1(|x|)^2/(2*exp(|x|))
+    if ix < 0x3fd62e43 {
+        let t = expm1(x.abs());
+        let w = ONE + t;
+        if ix < 0x3c800000 {
+            return w;
+        }
+        return ONE + (t * t) / (w + w);
+    }
+
+    // |x| in [0.5*ln2,22], return (exp(|x|)+1/exp(|x|)/2
+    if ix < 0x40360000 {
+        let t = exp(x.abs());
+        return HALF * t + HALF / t;
+    }
+
+    // |x| in [22, log(maxdouble)] return half*exp(|x|)
+    if ix < 0x40862E42 {
+        return HALF * exp(x.abs());
+    }
+
+    // |x| in [log(maxdouble), overflowthresold]
+    let lx = get_low_word(x);
+    if ix < 0x408633CE || (ix == 0x408633ce && lx <= 0x8fb9f87d) {
+        let w = exp(HALF * x.abs());
+        let t = HALF * w;
+        return t * w;
+    }
+
+    HUGE * HUGE
+}
+
+pub fn tanh(x: f64) -> f64 {
+    let jx = get_high_word(x);
+    let ix = jx & 0x7fffffff;
+    if ix >= 0x7ff00000 {
+        if jx >= 0 {
+            return ONE / x + ONE;
+        }
+        return ONE / x - ONE;
+    }
+
+    let z = if ix < 0x40360000 {
+        if ix < 0x3c800000 {
+            return x * (ONE + x);
+        }
+        if ix >= 0x3ff00000 {
+            let t = expm1(TWO * x.abs());
+            ONE - TWO / (t + TWO)
+        } else {
+            let t = expm1(-TWO * x.abs());
+            -t / (t + TWO)
+        }
+    } else {
+        ONE - TINY
+    };
+    if jx >= 0 {
+        z
+    } else {
+        -z
+    }
+}
+
+pub fn hypot(x: f64, y: f64) -> f64 {
+    let mut ha = get_high_word(x) & 0x7fffffff;
+    let mut hb = get_high_word(y) & 0x7fffffff;
+    let (mut a, mut b) = if hb > ha {
+        std::mem::swap(&mut ha, &mut hb);
+        (y, x)
+    } else {
+        (x, y)
+    };
+    a = set_high_word(a, ha);
+    b = set_high_word(b, hb);
+    // x/y > 2**60
+    if (ha - hb) > 0x3c00000 {
+        return a + b;
+    }
+    let mut k = 0;
+    if ha > 0x5f300000 {
+        // inf or NaN
+        if ha >= 0x7ff00000 {
+            let mut w = a + b;
+            if ((ha & 0xfffff) as u32 | get_low_word(a)) == 0 {
+                w = a;
+            }
+            if ((hb ^ 0x7ff00000) as u32 | get_low_word(b)) == 0 {
+                w = b;
+            }
+            return w;
+        }
+        // scale a and b by 2**-600
+        ha -= 0x25800000;
+        hb -= 0x25800000;
+        k += 600;
+        a = set_high_word(a, ha);
+        b = set_high_word(b, hb);
+    }
+    if hb < 0x20b00000 {
+        if hb <= 0x000fffff {
+            // subnormal b or 0
+            if (hb as u32 | get_low_word(b)) == 0 {
+                return a;
+            }
+            let t1 = from_words(0x7fd00000, 0);
+            b *= t1;
+            a *= t1;
+            k -= 1022;
+        } else {
+            // scale a and b by 2^600
+            ha += 0x25800000;
+            hb += 0x25800000;
+            k -= 600;
+            a = set_high_word(a, ha);
+            b = set_high_word(b, hb);
+        }
+    }
+
+    // medium size a and b
+    let mut w = a - b;
+    if w > b {
+        let t1 = from_words(ha, 0);
+        let t2 = a - t1;
+        w = (t1 * t1 - (b * (-b) - t2 * (a + t1))).sqrt();
+    } else {
+        a += a;
+        let y1 = from_words(hb, 0);
+        let y2 = b - y1;
+        let t1 = from_words(ha + 0x00100000, 0);
+        let t2 = a - t1;
+        w = (t1 * y1 - (w * (-w) - (t1 * y2 + t2 * b))).sqrt();
+    }
+    if k != 0 {
+        let t1 = from_words(0x3ff00000 + (k << 20), 0);
+        t1 * w
+    } else {
+        w
+    }
+}
+
+pub fn expm1(x: f64) -> f64 {
+    const Q1: f64 = -3.33333333333331316428e-02;
+    const Q2: f64 = 1.58730158725481460165e-03;
+    const Q3: f64 = -7.93650757867487942473e-05;
+    const Q4: f64 = 4.00821782732936239552e-06;
+    const Q5: f64 = -2.01099218183624371326e-07;
+
+    let mut x = x;
+    let mut hx = get_high_word(x) as u32;
+    let xsb = hx & 0x80000000;
+    hx &= 0x7fffffff;
+
+    // filter out huge and non-finite arguments
+    if hx >= 0x4043687A {
+        if hx >= 0x40862E42 {
+            if hx >= 0x7ff00000 {
+                if ((hx & 0xfffff) | get_low_word(x)) != 0 {
+                    return x + x;
+                }
+                return if xsb == 0 {
+                    x
+                } else {
+                    -1.0
+                };
+            }
+            if x > EXP_OVERFLOW_THRESHOLD {
+                return HUGE * HUGE;
+            }
+        }
+        // x < -56*ln2, return -1.0 with inexact
+        if xsb != 0 && x + TINY < 0.0 {
+            return TINY - ONE;
+        }
+    }
+
+    // argument reduction
+    let k;
+    let mut c = 0.0;
+    if hx > 0x3fd62e42 {
+        let (hi, lo);
+        if hx < 0x3FF0A2B2 {
+            if xsb == 0 {
+                hi = x - LN2_HI;
+                lo = LN2_LO;
+                k = 1;
+            } else {
+                hi = x + LN2_HI;
+                lo = -LN2_LO;
+                k = -1;
+            }
+        } else {
+            k = (INV_LN2 * x
+                + if xsb == 0 {
+                    0.5
+                } else {
+                    -0.5
+                }) as i32;
+            let t = k as f64;
+            hi = x - t * LN2_HI;
+            lo = t * LN2_LO;
+        }
+        x = hi - lo;
+        c = (hi - x) - lo;
+    } else if hx < 0x3c900000 {
+        let t = HUGE + x;
+        return x - (t - (HUGE + x));
+    } else {
+        k = 0;
+    }
+
+    // x is now in primary range
+    let hfx = 0.5 * x;
+    let hxs = x * hfx;
+    let r1 = ONE + hxs * (Q1 + hxs * (Q2 + hxs * (Q3 + hxs * (Q4 + hxs * Q5))));
+    let t = 3.0 - r1 * hfx;
+    let mut e = hxs * ((r1 - t) / (6.0 - x * t));
+    if k == 0 {
+        return x - (x * e - hxs);
+    }
+    e = x * (e - c) - c;
+    e -= hxs;
+    if k == -1 {
+        return 0.5 * (x - e) - 0.5;
+    }
+    if k == 1 {
+        if x < -0.25 {
+            return -2.0 * (e - (x + 0.5));
+        }
+        return ONE + 2.0 * (x - e);
+    }
+    if k <= -2 || k > 56 {
+        let y = ONE - (e - x);
+        let y = set_high_word(y, get_high_word(y).wrapping_add(k << 20));
+        return y - ONE;
+    }
+    if k < 20 {
+        let t = from_words(0x3ff00000 - (0x200000 >> k), 0);
+        let y = t - (e - x);
+        set_high_word(y, get_high_word(y).wrapping_add(k << 20))
+    } else {
+        let t = from_words((0x3ff - k) << 20, 0);
+        let y = x - (e + t) + ONE;
+        set_high_word(y, get_high_word(y).wrapping_add(k << 20))
+    }
+}
+
+pub fn log1p(x: f64) -> f64 {
+    const LP1: f64 = 6.666666666666735130e-01;
+    const LP2: f64 = 3.999999999940941908e-01;
+    const LP3: f64 = 2.857142874366239149e-01;
+    const LP4: f64 = 2.222219843214978396e-01;
+    const LP5: f64 = 1.818357216161805012e-01;
+    const LP6: f64 = 1.531383769920937332e-01;
+    const LP7: f64 = 1.479819860511658591e-01;
+
+    let hx = get_high_word(x);
+    let ax = hx & 0x7fffffff;
+
+    let mut k = 1;
+    let mut f = 0.0;
+    let mut c = 0.0;
+    let mut hu = 0;
+    if hx < 0x3FDA827A {
+        // x <= -1.0
+        if ax >= 0x3ff00000 {
+            if x == -1.0 {
+                return -TWO54 / ZERO;
+            }
+            return (x - x) / (x - x);
+        }
+        // |x| < 2**-29
+        if ax < 0x3e200000 {
+            if TWO54 + x > ZERO && ax < 0x3c900000 {
+                return x;
+            }
+            return x - x * x * 0.5;
+        }
+        // -0.2929 < x < 0.41422
+        if hx > 0 || hx <= 0xbfd2bec3u32 as i32 {
+            k = 0;
+            f = x;
+            hu = 1;
+        }
+    }
+    if hx >= 0x7ff00000 {
+        return x + x;
+    }
+    if k != 0 {
+        let mut u;
+        if hx < 0x43400000 {
+            u = 1.0 + x;
+            hu = get_high_word(u);
+            k = (hu >> 20) - 1023;
+            // correction term
+            c = if k > 0 {
+                1.0 - (u - x)
+            } else {
+                x - (u - 1.0)
+            };
+            c /= u;
+        } else {
+            u = x;
+            hu = get_high_word(u);
+            k = (hu >> 20) - 1023;
+            c = 0.0;
+        }
+        hu &= 0x000fffff;
+        if hu < 0x6a09e {
+            u = set_high_word(u, hu | 0x3ff00000);
+        } else {
+            k += 1;
+            u = set_high_word(u, hu | 0x3fe00000);
+            hu = (0x00100000 - hu) >> 2;
+        }
+        f = u - 1.0;
+    }
+    let hfsq = 0.5 * f * f;
+    let dk = k as f64;
+    if hu == 0 {
+        // |f| < 2**-20
+        if f == ZERO {
+            if k == 0 {
+                return ZERO;
+            }
+            c += dk * LN2_LO;
+            return dk * LN2_HI + c;
+        }
+        let r = hfsq * (1.0 - 0.66666666666666666 * f);
+        if k == 0 {
+            return f - r;
+        }
+        return dk * LN2_HI - ((r - (dk * LN2_LO + c)) - f);
+    }
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let r = z * (LP1 + z * (LP2 + z * (LP3 + z * (LP4 + z * (LP5 + z * (LP6 + z * LP7))))));
+    if k == 0 {
+        f - (hfsq - s * (hfsq + r))
+    } else {
+        dk * LN2_HI - ((hfsq - (s * (hfsq + r) + (dk * LN2_LO + c))) - f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fdlibm;
+
+    // reference results of java.lang.StrictMath as raw bits, NaN results compare equal to any NaN
+    #[allow(clippy::type_complexity)]
+    const UNARY_REFERENCES: &[(&str, fn(f64) -> f64, &[(u64, u64)])] = &[
+        (
+            "sin",
+            fdlibm::sin as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fdeaee8744b05f0),
+                (0xbfe8000000000000, 0xbfe5cffc16bf8f0d),
+                (0x3ff0000000000000, 0x3feaed548f090cee),
+                (0x4008000000000000, 0x3fc210386db6d55b),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0x3fb33929f64471c2),
+                (0x4480f0cf064dd592, 0xbfeb453ab76bf397),
+                (0x7e37e43c8800759c, 0xbfea2c16b010e385),
+                (0xfe37e43c8800759c, 0x3fea2c16b010e385),
+                (0x400921fb54442d18, 0x3ca1a62633145c07),
+                (0x3ff921fb54442d18, 0x3ff0000000000000),
+                (0x414921fb54442d18, 0xbde1a62633145c07),
+                (0x7506ac5b262ca1ff, 0x3ff0000000000000),
+                (0x402921fb54442d18, 0xbcc1a62633145c07),
+            ],
+        ),
+        (
+            "cos",
+            fdlibm::cos as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x3ff0000000000000),
+                (0x8000000000000000, 0x3ff0000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x3ff0000000000000),
+                (0x3fe0000000000000, 0x3fec1528065b7d50),
+                (0xbfe8000000000000, 0x3fe769fec655211f),
+                (0x3ff0000000000000, 0x3fe14a280fb5068c),
+                (0x4008000000000000, 0xbfefae04be85e5d2),
+                (0x3ddb7cdfd9d7bdbb, 0x3ff0000000000000),
+                (0x4085e40000000000, 0xbfefe8df14616bc1),
+                (0x4480f0cf064dd592, 0x3fe0be2cef01c8f4),
+                (0x7e37e43c8800759c, 0xbfe2699022adc4c1),
+                (0xfe37e43c8800759c, 0xbfe2699022adc4c1),
+                (0x400921fb54442d18, 0xbff0000000000000),
+                (0x3ff921fb54442d18, 0x3c91a62633145c07),
+                (0x414921fb54442d18, 0x3ff0000000000000),
+                (0x7506ac5b262ca1ff, 0xbc214ae72e6ba22f),
+                (0x402921fb54442d18, 0x3ff0000000000000),
+            ],
+        ),
+        (
+            "tan",
+            fdlibm::tan as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fe17b4f5bf3474a),
+                (0xbfe8000000000000, 0xbfedcfa36110eeec),
+                (0x3ff0000000000000, 0x3ff8eb245cbee3a6),
+                (0x4008000000000000, 0xbfc23ef71254b86f),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0xbfb34718e533bb52),
+                (0x4480f0cf064dd592, 0xbffa0f79c1b6b258),
+                (0x7e37e43c8800759c, 0x3ff6be411f37ac77),
+                (0xfe37e43c8800759c, 0xbff6be411f37ac77),
+                (0x400921fb54442d18, 0xbca1a62633145c07),
+                (0x3ff921fb54442d18, 0x434d02967c31cdb5),
+                (0x414921fb54442d18, 0xbde1a62633145c07),
+                (0x7506ac5b262ca1ff, 0xc3bd9ba9a7975636),
+                (0x402921fb54442d18, 0xbcc1a62633145c07),
+            ],
+        ),
+        (
+            "asin",
+            fdlibm::asin as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fe0c152382d7366),
+                (0xbfe8000000000000, 0xbfeb235315c680dc),
+                (0x3ff0000000000000, 0x3ff921fb54442d18),
+                (0x4008000000000000, 0xfff8000000000000),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0xfff8000000000000),
+                (0x3fef5c28f5c28f5c, 0x3ff5ed690583be07),
+                (0xbfe3333333333333, 0xbfe4978fa3269ee1),
+                (0x3ff8000000000000, 0xfff8000000000000),
+            ],
+        ),
+        (
+            "acos",
+            fdlibm::acos as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x3ff921fb54442d18),
+                (0x8000000000000000, 0x3ff921fb54442d18),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x3ff921fb54442d18),
+                (0x3fe0000000000000, 0x3ff0c152382d7366),
+                (0xbfe8000000000000, 0x400359d26f93b6c3),
+                (0x3ff0000000000000, 0x0000000000000000),
+                (0x4008000000000000, 0xfff8000000000000),
+                (0x3ddb7cdfd9d7bdbb, 0x3ff921fb543d4de0),
+                (0x4085e40000000000, 0xfff8000000000000),
+                (0x3fef5c28f5c28f5c, 0x3fc9a49276037887),
+                (0xbfe3333333333333, 0x4001b6e192ebbe44),
+                (0xbff0000000000000, 0x400921fb54442d18),
+            ],
+        ),
+        (
+            "atan",
+            fdlibm::atan as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x3ff921fb54442d18),
+                (0xfff0000000000000, 0xbff921fb54442d18),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fddac670561bb4f),
+                (0xbfe8000000000000, 0xbfe4978fa3269ee1),
+                (0x3ff0000000000000, 0x3fe921fb54442d18),
+                (0x4008000000000000, 0x3ff3fc176b7a8560),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0x3ff91c226f05a0d3),
+                (0x3fe3333333333333, 0x3fe14b1dd5f90ce1),
+                (0x3ff3333333333333, 0x3fec08aae496efa6),
+                (0x4000000000000000, 0x3ff1b6e192ebbe44),
+                (0x4415af1d78b58c40, 0x3ff921fb54442d18),
+            ],
+        ),
+        (
+            "exp",
+            fdlibm::exp as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x3ff0000000000000),
+                (0x8000000000000000, 0x3ff0000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0x0000000000000000),
+                (0x0000000000000001, 0x3ff0000000000000),
+                (0x3fe0000000000000, 0x3ffa61298e1e069c),
+                (0xbfe8000000000000, 0x3fde3b40ebefcd7e),
+                (0x3ff0000000000000, 0x4005bf0a8b14576a),
+                (0x4008000000000000, 0x403415e5bf6fb106),
+                (0x3ddb7cdfd9d7bdbb, 0x3ff000000006df38),
+                (0x4085e40000000000, 0x7f18625c7d4f56c2),
+                (0x3fd3333333333333, 0x3ff599058c8c1a96),
+                (0xc0874c0000000000, 0x0000000000000000),
+                (0x40862f3333333333, 0x7ff0000000000000),
+                (0xc000000000000000, 0x3fc152aaa3bf81cc),
+            ],
+        ),
+        (
+            "log",
+            fdlibm::log as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0xfff0000000000000),
+                (0x8000000000000000, 0xfff0000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0xc0874385446d71c3),
+                (0x3fe0000000000000, 0xbfe62e42fefa39ef),
+                (0xbfe8000000000000, 0xfff8000000000000),
+                (0x3ff0000000000000, 0x0000000000000000),
+                (0x4008000000000000, 0x3ff193ea7aad030a),
+                (0x3ddb7cdfd9d7bdbb, 0xc037069e2aa2aa5b),
+                (0x4085e40000000000, 0x401a3509950c84a7),
+                (0x000012688b70e62b, 0xc0864e69394d9508),
+                (0x3feffffde7210be9, 0xbeb0c6f82d74d230),
+                (0x40c81cd6c8b43958, 0x4022d79559791e31),
+            ],
+        ),
+        (
+            "log10",
+            fdlibm::log10 as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0xfff0000000000000),
+                (0x8000000000000000, 0xfff0000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0xc07434e6420f4374),
+                (0x3fe0000000000000, 0xbfd34413509f79ff),
+                (0xbfe8000000000000, 0xfff8000000000000),
+                (0x3ff0000000000000, 0x0000000000000000),
+                (0x4008000000000000, 0x3fde8927964fd5fd),
+                (0x3ddb7cdfd9d7bdbb, 0xc024000000000000),
+                (0x4085e40000000000, 0x4006c36557bb951a),
+                (0x000012688b70e62b, 0xc073600000000000),
+                (0x408f400000000000, 0x4008000000000000),
+                (0x3f50624dd2f1a9fc, 0xc008000000000000),
+            ],
+        ),
+        (
+            "sqrt",
+            fdlibm::sqrt as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x1e60000000000000),
+                (0x3fe0000000000000, 0x3fe6a09e667f3bcd),
+                (0xbfe8000000000000, 0xfff8000000000000),
+                (0x3ff0000000000000, 0x3ff0000000000000),
+                (0x4008000000000000, 0x3ffbb67ae8584caa),
+                (0x3ddb7cdfd9d7bdbb, 0x3ee4f8b588e368f1),
+                (0x4085e40000000000, 0x403a778ab9c57df2),
+                (0x4000000000000000, 0x3ff6a09e667f3bcd),
+                (0x000012688b70e62b, 0x1fc1297872d9cbae),
+            ],
+        ),
+        (
+            "cbrt",
+            fdlibm::cbrt as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff0000000000000),
+                (0x0000000000000001, 0x2990000000000000),
+                (0x3fe0000000000000, 0x3fe965fea53d6e3d),
+                (0xbfe8000000000000, 0xbfed12ed0af1a27f),
+                (0x3ff0000000000000, 0x3ff0000000000000),
+                (0x4008000000000000, 0x3ff7137449123ef6),
+                (0x3ddb7cdfd9d7bdbb, 0x3f3e6b4b396428e8),
+                (0x4085e40000000000, 0x4021c3268f980ddb),
+                (0xc03b000000000000, 0xc008000000000000),
+                (0x000012688b70e62b, 0x2a7a9d1b0b5d7427),
+                (0x4024000000000000, 0x40013c484138704f),
+            ],
+        ),
+        (
+            "sinh",
+            fdlibm::sinh as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff0000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fe0acd00fe63b97),
+                (0xbfe8000000000000, 0xbfea506b2dd3c690),
+                (0x3ff0000000000000, 0x3ff2cd9fc44eb982),
+                (0x4008000000000000, 0x40240926e70949ae),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0x7f08625c7d4f56c2),
+                (0x4039000000000000, 0x4220c3d3920962c9),
+                (0xc086300000000000, 0xffe3e21a464507fa),
+                (0x4086380000000000, 0x7ff0000000000000),
+                (0x3fc999999999999a, 0x3fc9c560cd35ef82),
+            ],
+        ),
+        (
+            "cosh",
+            fdlibm::cosh as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x3ff0000000000000),
+                (0x8000000000000000, 0x3ff0000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0x7ff0000000000000),
+                (0x0000000000000001, 0x3ff0000000000000),
+                (0x3fe0000000000000, 0x3ff20ac1862ae8d0),
+                (0xbfe8000000000000, 0x3ff4b705d1e5d6a8),
+                (0x3ff0000000000000, 0x3ff8b07551d9f551),
+                (0x4008000000000000, 0x402422a497d6185e),
+                (0x3ddb7cdfd9d7bdbb, 0x3ff0000000000000),
+                (0x4085e40000000000, 0x7f08625c7d4f56c2),
+                (0x4039000000000000, 0x4220c3d3920962c9),
+                (0xc086300000000000, 0x7fe3e21a464507fa),
+                (0x4086380000000000, 0x7ff0000000000000),
+                (0x3fc999999999999a, 0x3ff0523184b1ee9d),
+            ],
+        ),
+        (
+            "tanh",
+            fdlibm::tanh as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x3ff0000000000000),
+                (0xfff0000000000000, 0xbff0000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fdd9353d7568af3),
+                (0xbfe8000000000000, 0xbfe45323e552f228),
+                (0x3ff0000000000000, 0x3fe85efab514f394),
+                (0x4008000000000000, 0x3fefd77d111a0b00),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d7bdbb),
+                (0x4085e40000000000, 0x3ff0000000000000),
+                (0x4039000000000000, 0x3ff0000000000000),
+                (0xbfc999999999999a, 0xbfc9439830b3a590),
+                (0x4000000000000000, 0x3feed9505e1bc3d4),
+            ],
+        ),
+        (
+            "expm1",
+            fdlibm::expm1 as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xbff0000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fe4c2531c3c0d38),
+                (0xbfe8000000000000, 0xbfe0e25f8a081941),
+                (0x3ff0000000000000, 0x3ffb7e151628aed2),
+                (0x4008000000000000, 0x403315e5bf6fb106),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9dda4e3),
+                (0x4085e40000000000, 0x7f18625c7d4f56c2),
+                (0x3fc999999999999a, 0x3fcc56ecf2c56468),
+                (0xc044000000000000, 0xbff0000000000000),
+                (0x403e000000000000, 0x42a370470aec26ed),
+                (0x404e000000000000, 0x45579dbc9dc53c66),
+            ],
+        ),
+        (
+            "log1p",
+            fdlibm::log1p as fn(f64) -> f64,
+            &[
+                (0x0000000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x8000000000000000),
+                (0x7ff8000000000000, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0x0000000000000001),
+                (0x3fe0000000000000, 0x3fd9f323ecbf984c),
+                (0xbfe8000000000000, 0xbff62e42fefa39ef),
+                (0x3ff0000000000000, 0x3fe62e42fefa39ef),
+                (0x4008000000000000, 0x3ff62e42fefa39ef),
+                (0x3ddb7cdfd9d7bdbb, 0x3ddb7cdfd9d1d693),
+                (0x4085e40000000000, 0x401a367f8a1bfec7),
+                (0xbfd3333333333333, 0xbfd6d3c324e13f4e),
+                (0x3fd999999999999a, 0x3fd588c2d9133490),
+                (0x43abc16d674ec800, 0x4044b927f32bffb8),
+                (0xbff0000000000000, 0xfff0000000000000),
+            ],
+        ),
+    ];
+
+    #[allow(clippy::type_complexity)]
+    const BINARY_REFERENCES: &[(&str, fn(f64, f64) -> f64, &[(u64, u64, u64)])] = &[
+        (
+            "remainder",
+            fdlibm::remainder as fn(f64, f64) -> f64,
+            &[
+                (0x0000000000000000, 0xbff0000000000000, 0x0000000000000000),
+                (0x8000000000000000, 0x7ff8000000000000, 0x7ff8000000000000),
+                (0x3ff0000000000000, 0x8000000000000000, 0xfff8000000000000),
+                (0xbff0000000000000, 0x3fe0000000000000, 0x8000000000000000),
+                (0x4004000000000000, 0x0000000000000001, 0x0000000000000000),
+                (0xc008000000000000, 0x4004000000000000, 0xbfe0000000000000),
+                (0x3fe0000000000000, 0x7ff0000000000000, 0x3fe0000000000000),
+                (0x7e37e43c8800759c, 0x3ff0000000000000, 0x0000000000000000),
+                (0x7ff8000000000000, 0x7e37e43c8800759c, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x0000000000000000, 0xfff8000000000000),
+                (0xfff0000000000000, 0xc008000000000000, 0xfff8000000000000),
+                (0x0000000000000001, 0xfff0000000000000, 0x0000000000000001),
+                (0x4024000000000000, 0x4008000000000000, 0x3ff0000000000000),
+                (0x4026000000000000, 0x4000000000000000, 0xbff0000000000000),
+                (0xc01e000000000000, 0x4000000000000000, 0x3fe0000000000000),
+                (0x7e37e43c8800759c, 0x4008000000000000, 0x0000000000000000),
+                (0x0000000000000001, 0x0000000000000002, 0x0000000000000001),
+            ],
+        ),
+        (
+            "atan2",
+            fdlibm::atan2 as fn(f64, f64) -> f64,
+            &[
+                (0x0000000000000000, 0xbff0000000000000, 0x400921fb54442d18),
+                (0x8000000000000000, 0x7ff8000000000000, 0x7ff8000000000000),
+                (0x3ff0000000000000, 0x8000000000000000, 0x3ff921fb54442d18),
+                (0xbff0000000000000, 0x3fe0000000000000, 0xbff1b6e192ebbe44),
+                (0x4004000000000000, 0x0000000000000001, 0x3ff921fb54442d18),
+                (0xc008000000000000, 0x4004000000000000, 0xbfec08aae496efa6),
+                (0x3fe0000000000000, 0x7ff0000000000000, 0x0000000000000000),
+                (0x7e37e43c8800759c, 0x3ff0000000000000, 0x3ff921fb54442d18),
+                (0x7ff8000000000000, 0x7e37e43c8800759c, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x0000000000000000, 0x3ff921fb54442d18),
+                (0xfff0000000000000, 0xc008000000000000, 0xbff921fb54442d18),
+                (0x0000000000000001, 0xfff0000000000000, 0x400921fb54442d18),
+                (0x3ff0000000000000, 0x4000000000000000, 0x3fddac670561bb4f),
+                (0xc008000000000000, 0xc010000000000000, 0xc003fc176b7a8560),
+                (0x6974e718d7d7625a, 0x16687e92154ef7ac, 0x3ff921fb54442d18),
+                (0x4000000000000000, 0xd4b249ad2594c37d, 0x400921fb54442d18),
+            ],
+        ),
+        (
+            "pow",
+            fdlibm::pow as fn(f64, f64) -> f64,
+            &[
+                (0x0000000000000000, 0xbff0000000000000, 0x7ff0000000000000),
+                (0x8000000000000000, 0x7ff8000000000000, 0x7ff8000000000000),
+                (0x3ff0000000000000, 0x8000000000000000, 0x3ff0000000000000),
+                (0xbff0000000000000, 0x3fe0000000000000, 0xfff8000000000000),
+                (0x4004000000000000, 0x0000000000000001, 0x3ff0000000000000),
+                (0xc008000000000000, 0x4004000000000000, 0xfff8000000000000),
+                (0x3fe0000000000000, 0x7ff0000000000000, 0x0000000000000000),
+                (0x7e37e43c8800759c, 0x3ff0000000000000, 0x7e37e43c8800759c),
+                (0x7ff8000000000000, 0x7e37e43c8800759c, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x0000000000000000, 0x3ff0000000000000),
+                (0xfff0000000000000, 0xc008000000000000, 0x8000000000000000),
+                (0x0000000000000001, 0xfff0000000000000, 0x7ff0000000000000),
+                (0x4000000000000000, 0x3fe0000000000000, 0x3ff6a09e667f3bcd),
+                (0xc000000000000000, 0x4008000000000000, 0xc020000000000000),
+                (0xc000000000000000, 0x3fe0000000000000, 0xfff8000000000000),
+                (0x3ff000001ad7f29b, 0x4202a05f20000000, 0x7ff0000000000000),
+                (0x4024000000000000, 0xc074000000000000, 0x00000000000007e8),
+                (0x4000000000000000, 0x408fff3333333333, 0x7feddb680117aa8e),
+                (0x3fe0000000000000, 0x4090ca0000000000, 0x0000000000000001),
+                (0xc020000000000000, 0x3fd5555555555555, 0xfff8000000000000),
+                (0x000012688b70e62b, 0x3fd3333333333333, 0x2ca0aff95cc5b0b3),
+            ],
+        ),
+        (
+            "hypot",
+            fdlibm::hypot as fn(f64, f64) -> f64,
+            &[
+                (0x0000000000000000, 0xbff0000000000000, 0x3ff0000000000000),
+                (0x8000000000000000, 0x7ff8000000000000, 0x7ff8000000000000),
+                (0x3ff0000000000000, 0x8000000000000000, 0x3ff0000000000000),
+                (0xbff0000000000000, 0x3fe0000000000000, 0x3ff1e3779b97f4a8),
+                (0x4004000000000000, 0x0000000000000001, 0x4004000000000000),
+                (0xc008000000000000, 0x4004000000000000, 0x400f3db2174e7468),
+                (0x3fe0000000000000, 0x7ff0000000000000, 0x7ff0000000000000),
+                (0x7e37e43c8800759c, 0x3ff0000000000000, 0x7e37e43c8800759c),
+                (0x7ff8000000000000, 0x7e37e43c8800759c, 0x7ff8000000000000),
+                (0x7ff0000000000000, 0x0000000000000000, 0x7ff0000000000000),
+                (0xfff0000000000000, 0xc008000000000000, 0x7ff0000000000000),
+                (0x0000000000000001, 0xfff0000000000000, 0x7ff0000000000000),
+                (0x4008000000000000, 0x4010000000000000, 0x4014000000000000),
+                (0x7e37e43c8800759c, 0x7e37e43c8800759c, 0x7e40e4d50f99b211),
+                (0x000012688b70e62b, 0x000024d116e1cc56, 0x0000292991fdae04),
+                (0x3ff0000000000000, 0x3bc79ca10c924223, 0x3ff0000000000000),
+                (0x4014000000000000, 0x4014000006b5fca7, 0x401c48c604ddd79a),
+            ],
+        ),
+    ];
+
+    fn matches(result: f64, expected: u64) -> bool {
+        result.to_bits() == expected || (result.is_nan() && f64::from_bits(expected).is_nan())
+    }
+
+    #[test]
+    fn conforms_to_strict_math() {
+        for (name, function, cases) in UNARY_REFERENCES {
+            for (x, expected) in cases.iter() {
+                let result = function(f64::from_bits(*x));
+                assert!(matches(result, *expected), "{}({:e}) returned {:e}", name, f64::from_bits(*x), result);
+            }
+        }
+        for (name, function, cases) in BINARY_REFERENCES {
+            for (x, y, expected) in cases.iter() {
+                let result = function(f64::from_bits(*x), f64::from_bits(*y));
+                assert!(
+                    matches(result, *expected),
+                    "{}({:e}, {:e}) returned {:e}",
+                    name,
+                    f64::from_bits(*x),
+                    f64::from_bits(*y),
+                    result
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,596 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    io::{self, Write},
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    exec::env::JniEnv,
+    model::{InternalMetadata, JavaArrayType, JavaValue, MethodDescriptor, RuntimeResult, VmError},
+    NativeMethod,
+};
+
+type SettleCallback = Closure<dyn FnMut(JsValue)>;
+
+// promises returned by JavaScript natives, the threads awaiting them become runnable once they settle
+pub struct PromiseTable {
+    next_id: usize,
+    callbacks: HashMap<usize, (SettleCallback, SettleCallback)>,
+    settled: HashMap<usize, Result<JsValue, JsValue>>,
+    waker: Option<js_sys::Function>,
+}
+
+impl PromiseTable {
+    pub fn new() -> PromiseTable {
+        PromiseTable {
+            next_id: 0,
+            callbacks: HashMap::new(),
+            settled: HashMap::new(),
+            waker: None,
+        }
+    }
+
+    pub fn track(table: &Rc<RefCell<PromiseTable>>, promise: &js_sys::Promise) -> usize {
+        let id = {
+            let mut table = table.borrow_mut();
+            table.next_id += 1;
+            table.next_id
+        };
+
+        let on_resolve = PromiseTable::create_settle_callback(Rc::downgrade(table), id, Ok);
+        let on_reject = PromiseTable::create_settle_callback(Rc::downgrade(table), id, Err);
+        let _ = promise.then2(&on_resolve, &on_reject);
+        table.borrow_mut().callbacks.insert(id, (on_resolve, on_reject));
+
+        id
+    }
+
+    fn create_settle_callback(
+        table: Weak<RefCell<PromiseTable>>,
+        id: usize,
+        settle: fn(JsValue) -> Result<JsValue, JsValue>,
+    ) -> SettleCallback {
+        Closure::wrap(Box::new(move |value: JsValue| {
+            if let Some(table) = table.upgrade() {
+                table.borrow_mut().settled.insert(id, settle(value));
+                table.borrow_mut().wake();
+            }
+        }) as Box<dyn FnMut(JsValue)>)
+    }
+
+    pub fn get_settled(&self) -> Vec<usize> {
+        self.settled.keys().copied().collect()
+    }
+
+    pub fn take_result(&mut self, id: usize) -> Option<Result<JsValue, JsValue>> {
+        let result = self.settled.remove(&id);
+        if result.is_some() {
+            self.callbacks.remove(&id);
+        }
+        result
+    }
+
+    // called once the next promise settles, so that the embedder can resume execution
+    pub fn set_waker(&mut self, waker: js_sys::Function) {
+        self.waker = Some(waker);
+    }
+
+    pub fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            set_timeout(&waker, 0);
+        }
+    }
+}
+
+pub enum HostCallResult {
+    None,
+    Pending,
+    Settled(Result<JsValue, JsValue>),
+}
+
+// natives that parked their thread on a promise are invoked again once it is woken up, and take the
+// result of the promise here
+pub fn take_host_call_result(env: &JniEnv) -> HostCallResult {
+    let pending_host_call = env.jvm.threads.borrow().get_current_thread().pending_host_call;
+    let call_id = match pending_host_call {
+        Some(call_id) => call_id,
+        None => return HostCallResult::None,
+    };
+
+    let result = env.jvm.promises.borrow_mut().take_result(call_id);
+    match result {
+        Some(result) => {
+            env.jvm.threads.borrow_mut().get_current_thread_mut().pending_host_call = None;
+            HostCallResult::Settled(result)
+        }
+        None => {
+            env.jvm.threads.borrow_mut().await_host_call(call_id);
+            HostCallResult::Pending
+        }
+    }
+}
+
+pub fn await_promise(env: &JniEnv, promise: &js_sys::Promise) {
+    let call_id = PromiseTable::track(&env.jvm.promises, promise);
+    env.jvm.threads.borrow_mut().await_host_call(call_id);
+}
+
+// JavaScript objects referenced from Java, each entry belongs to the JSObject instance wrapping it
+pub struct JsHandleTable {
+    next_handle: usize,
+    entries: HashMap<usize, (JsValue, usize)>,
+}
+
+impl JsHandleTable {
+    pub fn new() -> JsHandleTable {
+        JsHandleTable {
+            next_handle: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, value: JsValue, owner: usize) -> usize {
+        self.next_handle += 1;
+        self.entries.insert(self.next_handle, (value, owner));
+        self.next_handle
+    }
+
+    pub fn get(&self, handle: usize) -> Option<JsValue> {
+        self.entries.get(&handle).map(|(value, _)| value.clone())
+    }
+
+    // releases the JavaScript objects whose owning JSObject is no longer on the heap
+    pub fn sweep(&mut self, is_live: impl Fn(usize) -> bool) {
+        self.entries.retain(|_, (_, owner)| is_live(*owner));
+    }
+}
+
+// writes complete lines to the browser console, a trailing partial line is kept until it is completed or
+// flushed so that multi-byte characters split across writes are decoded correctly
+pub struct ConsoleSink {
+    is_error: bool,
+    buffer: Vec<u8>,
+}
+
+impl ConsoleSink {
+    pub fn new(is_error: bool) -> ConsoleSink {
+        ConsoleSink {
+            is_error,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn log(&self, line: &[u8]) {
+        let line = JsValue::from(String::from_utf8_lossy(line).as_ref());
+        if self.is_error {
+            web_sys::console::error_1(&line);
+        } else {
+            web_sys::console::log_1(&line);
+        }
+    }
+}
+
+impl Write for ConsoleSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if let Some(end) = self.buffer.iter().rposition(|b| *b == b'\n') {
+            let rest = self.buffer.split_off(end + 1);
+            let lines = std::mem::replace(&mut self.buffer, rest);
+            for line in lines[..end].split(|b| *b == b'\n') {
+                self.log(line);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.log(&line);
+        }
+        Ok(())
+    }
+}
+
+// passes every write to a JavaScript callback as a Uint8Array
+pub struct CallbackSink {
+    callback: js_sys::Function,
+}
+
+impl CallbackSink {
+    pub fn new(callback: js_sys::Function) -> CallbackSink {
+        CallbackSink {
+            callback,
+        }
+    }
+}
+
+impl Write for CallbackSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.callback.call1(&JsValue::null(), &js_sys::Uint8Array::from(buf)) {
+            Ok(_) => Ok(buf.len()),
+            Err(err) => Err(io::Error::other(get_error_message(&err))),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn wrap_js_object(env: &JniEnv, value: JsValue) -> RuntimeResult<usize> {
+    let class_id = env.get_class_id("webjvm/js/JSObject")?;
+    let instance = env.new_instance(class_id)?;
+    let handle = env.jvm.js_objects.borrow_mut().insert(value, instance);
+    env.set_internal_metadata(instance, "js_handle", InternalMetadata::Numeric(handle))?;
+
+    Ok(instance)
+}
+
+pub fn get_js_object(env: &JniEnv, instance: usize) -> RuntimeResult<Option<JsValue>> {
+    match env.get_internal_metadata(instance, "js_handle")? {
+        Some(InternalMetadata::Numeric(handle)) => Ok(env.jvm.js_objects.borrow().get(handle)),
+        _ => Ok(None),
+    }
+}
+
+pub fn set_timeout(callback: &js_sys::Function, delay: i32) {
+    if let Ok(set_timeout) = js_sys::Reflect::get(&js_sys::global(), &JsValue::from("setTimeout")) {
+        if let Some(set_timeout) = set_timeout.dyn_ref::<js_sys::Function>() {
+            let _ = set_timeout.call2(&JsValue::null(), callback, &JsValue::from(delay));
+        }
+    }
+}
+
+// performance.now() is monotonic, unlike Date.now()
+pub fn performance_now() -> f64 {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from("performance"))
+        .ok()
+        .and_then(|performance| {
+            let now = js_sys::Reflect::get(&performance, &JsValue::from("now")).ok()?;
+            now.dyn_ref::<js_sys::Function>()?.call0(&performance).ok()?.as_f64()
+        })
+        .unwrap_or_else(js_sys::Date::now)
+}
+
+pub fn get_error_message(err: &JsValue) -> String {
+    match err.dyn_ref::<js_sys::Error>() {
+        Some(err) => String::from(err.message()),
+        None => match err.as_string() {
+            Some(message) => message,
+            None => format!("{:?}", err),
+        },
+    }
+}
+
+impl NativeMethod for js_sys::Function {
+    fn invoke(&self, env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+        let descriptor = MethodDescriptor::new(&env.container_method).map_err(|_| {
+            env.jvm.raise(VmError::MalformedClassFile {
+                class_name: env.container_class.clone(),
+                message: format!("bad method descriptor {}", env.container_method),
+            })
+        })?;
+
+        // the native is invoked again once the promise it returned has settled
+        match take_host_call_result(env) {
+            HostCallResult::None => (),
+            HostCallResult::Pending => return Ok(None),
+            HostCallResult::Settled(Ok(value)) => return convert_return_value(env, &descriptor, &value),
+            HostCallResult::Settled(Err(err)) => {
+                return Err(env.throw_exception("java/io/IOException", Some(&get_error_message(&err))))
+            }
+        }
+
+        let arguments = js_sys::Array::new();
+        let mut index = 0;
+        if !env.is_static {
+            arguments.push(&to_js_value(env, &env.parameters[0], "Ljava/lang/Object;")?);
+            index += 1;
+        }
+        for argument_type in descriptor.argument_types.iter() {
+            arguments.push(&to_js_value(env, &env.parameters[index], argument_type)?);
+            index += match argument_type.as_str() {
+                "J" | "D" => 2,
+                _ => 1,
+            };
+        }
+
+        let result = match self.apply(&JsValue::null(), &arguments) {
+            Ok(result) => result,
+            Err(err) => return Err(env.throw_exception("java/lang/RuntimeException", Some(&get_error_message(&err)))),
+        };
+
+        if let Some(promise) = result.dyn_ref::<js_sys::Promise>() {
+            await_promise(env, promise);
+            return Ok(None);
+        }
+
+        convert_return_value(env, &descriptor, &result)
+    }
+
+    fn get_name(&self) -> String {
+        self.name().into()
+    }
+}
+
+pub fn parse_method_descriptor(env: &JniEnv, method_descriptor: &str) -> RuntimeResult<MethodDescriptor> {
+    MethodDescriptor::new(method_descriptor).map_err(|_| {
+        env.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some(&format!("invalid method descriptor {}", method_descriptor)),
+        )
+    })
+}
+
+pub fn from_js_arguments(
+    env: &JniEnv,
+    descriptor: &MethodDescriptor,
+    args: &[JsValue],
+) -> RuntimeResult<Vec<JavaValue>> {
+    if args.len() != descriptor.argument_types.len() {
+        return Err(env.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some(&format!("expected {} arguments, got {}", descriptor.argument_types.len(), args.len())),
+        ));
+    }
+    args.iter()
+        .zip(descriptor.argument_types.iter())
+        .map(|(arg, java_type)| from_js_value(env, arg, java_type))
+        .collect()
+}
+
+pub fn to_js_return_value(
+    env: &JniEnv,
+    descriptor: &MethodDescriptor,
+    value: Option<JavaValue>,
+) -> RuntimeResult<JsValue> {
+    match value {
+        Some(value) if descriptor.return_type != "V" => to_js_value(env, &value, &descriptor.return_type),
+        _ => Ok(JsValue::undefined()),
+    }
+}
+
+fn convert_return_value(
+    env: &JniEnv,
+    descriptor: &MethodDescriptor,
+    value: &JsValue,
+) -> RuntimeResult<Option<JavaValue>> {
+    match descriptor.return_type.as_str() {
+        "V" => Ok(None),
+        return_type => Ok(Some(from_js_value(env, value, return_type)?)),
+    }
+}
+
+// objects and arrays are passed to JavaScript as opaque handles from the runtime's handle table
+pub fn to_js_value(env: &JniEnv, value: &JavaValue, java_type: &str) -> RuntimeResult<JsValue> {
+    Ok(match value {
+        JavaValue::Byte(x) => JsValue::from(*x),
+        JavaValue::Short(x) => JsValue::from(*x),
+        JavaValue::Int(x) => JsValue::from(*x),
+        JavaValue::Long(x) => JsValue::from(*x),
+        JavaValue::Float(x) => JsValue::from(*x),
+        JavaValue::Double(x) => JsValue::from(*x),
+        JavaValue::Char(x) => JsValue::from(*x),
+        JavaValue::Boolean(x) => JsValue::from(*x),
+        JavaValue::Object(None) => JsValue::null(),
+        JavaValue::Object(Some(id)) => match java_type {
+            "Ljava/lang/String;" => JsValue::from(env.get_string(*id)?),
+            _ => match get_js_object(env, *id)? {
+                Some(value) => value,
+                None => to_js_handle(env, value),
+            },
+        },
+        JavaValue::Array(_) => to_js_handle(env, value),
+        JavaValue::Internal {
+            ..
+        } => JsValue::undefined(),
+    })
+}
+
+pub fn to_js_handle(env: &JniEnv, reference: &JavaValue) -> JsValue {
+    match env.jvm.handles.borrow_mut().get_or_insert(reference.clone()) {
+        Some(handle) => JsValue::from(handle as u32),
+        None => JsValue::null(),
+    }
+}
+
+pub fn from_js_handle(env: &JniEnv, value: &JsValue) -> Option<JavaValue> {
+    let handle = value.as_f64()?;
+    if handle.fract() != 0.0 || handle < 0.0 {
+        return None;
+    }
+    env.jvm.handles.borrow().get(handle as usize)
+}
+
+pub fn from_js_value(env: &JniEnv, value: &JsValue, java_type: &str) -> RuntimeResult<JavaValue> {
+    let converted = match java_type {
+        "Z" => value.as_bool().map(JavaValue::Boolean),
+        "B" => value.as_f64().map(|x| JavaValue::Byte(x as i8)),
+        "S" => value.as_f64().map(|x| JavaValue::Short(x as i16)),
+        "I" => value.as_f64().map(|x| JavaValue::Int(x as i32)),
+        "C" => value.as_f64().map(|x| JavaValue::Char(x as u16)),
+        "F" => value.as_f64().map(|x| JavaValue::Float(x as f32)),
+        "D" => value.as_f64().map(JavaValue::Double),
+        "J" => match value.as_f64() {
+            Some(x) => Some(JavaValue::Long(x as i64)),
+            None => i64::try_from(value.clone()).ok().map(JavaValue::Long),
+        },
+        _ if value.is_null() || value.is_undefined() => Some(JavaValue::Object(None)),
+        "Ljava/lang/String;" => match value.as_string() {
+            Some(str) => Some(JavaValue::Object(Some(env.new_string(&str)?))),
+            None => None,
+        },
+        "Lwebjvm/js/JSObject;" => Some(JavaValue::Object(Some(wrap_js_object(env, value.clone())?))),
+        _ => match from_js_handle(env, value) {
+            Some(reference) => match (&reference, java_type.starts_with('[')) {
+                (JavaValue::Array(_), true) | (JavaValue::Object(_), false) => Some(reference),
+                _ => None,
+            },
+            None if value.as_f64().is_some() => {
+                return Err(env.throw_exception(
+                    "java/lang/IllegalArgumentException",
+                    Some(&format!("invalid handle {:?} for {}", value, java_type)),
+                ))
+            }
+            None => None,
+        },
+    };
+
+    match converted {
+        Some(value) => Ok(value),
+        None => Err(env.throw_exception(
+            "java/lang/ClassCastException",
+            Some(&format!("cannot convert JavaScript value {:?} to {}", value, java_type)),
+        )),
+    }
+}
+
+fn get_array_type(env: &JniEnv, array_id: usize) -> RuntimeResult<JavaArrayType> {
+    let array_type = env.jvm.heap.borrow().array_heap_map.get(&array_id).map(|array| array.array_type.clone());
+    match array_type {
+        Some(array_type) => Ok(array_type),
+        None => Err(env.jvm.invalid_reference(array_id)),
+    }
+}
+
+fn get_big_int64_array_constructor() -> Option<js_sys::Function> {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from("BigInt64Array")).ok()?.dyn_into().ok()
+}
+
+fn is_big_int64_array(value: &JsValue) -> bool {
+    match value.dyn_ref::<js_sys::Object>() {
+        Some(obj) => obj.constructor().name() == "BigInt64Array",
+        None => false,
+    }
+}
+
+// the Java array type that a typed array is copied into, unsigned bytes are kept bitwise as Java bytes
+pub fn get_typed_array_type(value: &JsValue) -> Option<JavaArrayType> {
+    if value.is_instance_of::<js_sys::Int8Array>()
+        || value.is_instance_of::<js_sys::Uint8Array>()
+        || value.is_instance_of::<js_sys::Uint8ClampedArray>()
+    {
+        Some(JavaArrayType::Byte)
+    } else if value.is_instance_of::<js_sys::Int16Array>() {
+        Some(JavaArrayType::Short)
+    } else if value.is_instance_of::<js_sys::Uint16Array>() {
+        Some(JavaArrayType::Char)
+    } else if value.is_instance_of::<js_sys::Int32Array>() {
+        Some(JavaArrayType::Int)
+    } else if value.is_instance_of::<js_sys::Float32Array>() {
+        Some(JavaArrayType::Float)
+    } else if value.is_instance_of::<js_sys::Float64Array>() {
+        Some(JavaArrayType::Double)
+    } else if is_big_int64_array(value) {
+        Some(JavaArrayType::Long)
+    } else {
+        None
+    }
+}
+
+pub fn get_typed_array_length(value: &JsValue) -> Option<usize> {
+    let length = js_sys::Reflect::get(value, &JsValue::from("length")).ok()?;
+    length.as_f64().map(|length| length as usize)
+}
+
+// copies a region of a primitive array into a new typed array matching its element type
+pub fn to_typed_array(env: &JniEnv, array_id: usize, start: usize, length: usize) -> RuntimeResult<JsValue> {
+    Ok(match get_array_type(env, array_id)? {
+        JavaArrayType::Byte => {
+            let mut buf = vec![0; length];
+            env.get_byte_array_region(array_id, start, &mut buf)?;
+            js_sys::Int8Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Short => {
+            let mut buf = vec![0; length];
+            env.get_short_array_region(array_id, start, &mut buf)?;
+            js_sys::Int16Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Char => {
+            let mut buf = vec![0; length];
+            env.get_char_array_region(array_id, start, &mut buf)?;
+            js_sys::Uint16Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Int => {
+            let mut buf = vec![0; length];
+            env.get_int_array_region(array_id, start, &mut buf)?;
+            js_sys::Int32Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Boolean => {
+            let mut buf = vec![false; length];
+            env.get_boolean_array_region(array_id, start, &mut buf)?;
+            let buf: Vec<u8> = buf.into_iter().map(u8::from).collect();
+            js_sys::Uint8Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Float => {
+            let mut buf = vec![0.0; length];
+            env.get_float_array_region(array_id, start, &mut buf)?;
+            js_sys::Float32Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Double => {
+            let mut buf = vec![0.0; length];
+            env.get_double_array_region(array_id, start, &mut buf)?;
+            js_sys::Float64Array::from(&buf[..]).into()
+        }
+        JavaArrayType::Long => {
+            let mut buf = vec![0; length];
+            env.get_long_array_region(array_id, start, &mut buf)?;
+            let values: js_sys::Array = buf.into_iter().map(JsValue::from).collect();
+            let constructor = match get_big_int64_array_constructor() {
+                Some(constructor) => constructor,
+                None => return Ok(values.into()),
+            };
+            match js_sys::Reflect::construct(&constructor, &js_sys::Array::of1(&values)) {
+                Ok(typed_array) => typed_array,
+                Err(err) => {
+                    return Err(env.throw_exception("java/lang/RuntimeException", Some(&get_error_message(&err))))
+                }
+            }
+        }
+        JavaArrayType::Object(_) | JavaArrayType::Array(_) => {
+            return Err(env.throw_exception("java/lang/IllegalArgumentException", Some("not a primitive array")))
+        }
+    })
+}
+
+// copies the elements of a typed array, or any array-like of numbers, into a primitive array
+pub fn from_typed_array(env: &JniEnv, array_id: usize, start: usize, value: &JsValue) -> RuntimeResult<()> {
+    match get_array_type(env, array_id)? {
+        JavaArrayType::Byte => env.set_byte_array_region(array_id, start, &js_sys::Int8Array::new(value).to_vec()),
+        JavaArrayType::Short => env.set_short_array_region(array_id, start, &js_sys::Int16Array::new(value).to_vec()),
+        JavaArrayType::Char => env.set_char_array_region(array_id, start, &js_sys::Uint16Array::new(value).to_vec()),
+        JavaArrayType::Int => env.set_int_array_region(array_id, start, &js_sys::Int32Array::new(value).to_vec()),
+        JavaArrayType::Boolean => {
+            let buf: Vec<bool> = js_sys::Uint8Array::new(value).to_vec().into_iter().map(|x| x != 0).collect();
+            env.set_boolean_array_region(array_id, start, &buf)
+        }
+        JavaArrayType::Float => env.set_float_array_region(array_id, start, &js_sys::Float32Array::new(value).to_vec()),
+        JavaArrayType::Double => {
+            env.set_double_array_region(array_id, start, &js_sys::Float64Array::new(value).to_vec())
+        }
+        JavaArrayType::Long => {
+            let buf = js_sys::Array::from(value)
+                .iter()
+                .map(|x| match x.as_f64() {
+                    Some(x) => Ok(x as i64),
+                    None => i64::try_from(x),
+                })
+                .collect::<Result<Vec<i64>, JsValue>>();
+            match buf {
+                Ok(buf) => env.set_long_array_region(array_id, start, &buf),
+                Err(err) => Err(env.throw_exception(
+                    "java/lang/ClassCastException",
+                    Some(&format!("cannot convert JavaScript value {:?} to J", err)),
+                )),
+            }
+        }
+        JavaArrayType::Object(_) | JavaArrayType::Array(_) => {
+            Err(env.throw_exception("java/lang/IllegalArgumentException", Some("not a primitive array")))
+        }
+    }
+}
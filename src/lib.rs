@@ -6,51 +6,37 @@
 extern crate lazy_static;
 
 pub mod exec;
+pub mod fdlibm;
+pub mod js;
+pub mod manifest;
 pub mod model;
 pub mod native;
 pub mod util;
 
-use std::{collections::HashMap, io::Cursor};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    rc::Rc,
+};
 
 use classfile_parser::{
     field_info::FieldInfo,
     method_info::{MethodAccessFlags, MethodInfo},
     *,
 };
-use exec::{env::JniEnv, jvm::*};
+use exec::{env::JniEnv, jvm::*, vfs};
+use manifest::JarManifest;
 use model::*;
 use util::*;
 use wasm_bindgen::prelude::*;
 
+#[derive(Debug, Clone)]
 pub struct StackTraceElement {
     pub class_name: String,
     pub method: String,
 }
 
-impl NativeMethod for js_sys::Function {
-    fn invoke(&self, env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-        let res = self.call0(&JsValue::null()).expect("error invoking JavaScript function");
-        if res.is_string() {
-            let str: String = res.as_string().unwrap();
-            return Ok(Some(JavaValue::Object(Some(env.new_string(&str)))));
-        } else if res.is_null() {
-            return Ok(Some(JavaValue::Object(None)));
-        } else if let Some(double) = res.as_f64() {
-            return Ok(Some(JavaValue::Double(double)));
-        } else if let Some(bool) = res.as_bool() {
-            return Ok(Some(JavaValue::Boolean(bool)));
-        } else if !res.is_undefined() {
-            panic!("Invalid returned value from native JavaScript method");
-        }
-
-        Ok(None)
-    }
-
-    fn get_name(&self) -> String {
-        self.name().into()
-    }
-}
-
 pub trait NativeMethod {
     fn invoke(&self, env: &JniEnv) -> RuntimeResult<Option<JavaValue>>;
 
@@ -63,53 +49,237 @@ pub enum InvokeType {
     Special,
 }
 
+struct JarContents {
+    class_files: Vec<ClassFile>,
+    resources: Vec<(String, Vec<u8>)>,
+}
+
 pub struct Classpath {
     class_files: HashMap<String, ClassFile>,
+    resources: HashMap<String, Vec<Vec<u8>>>,
+    registered_jars: HashMap<String, Vec<u8>>,
+    loaded_jars: HashSet<String>,
     native_methods: HashMap<String, Box<dyn NativeMethod>>,
+    native_overrides: HashMap<String, Vec<(String, String)>>,
 }
 
 impl Classpath {
     pub fn new() -> Classpath {
         Classpath {
             class_files: HashMap::new(),
+            resources: HashMap::new(),
+            registered_jars: HashMap::new(),
+            loaded_jars: HashSet::new(),
             native_methods: HashMap::new(),
+            native_overrides: HashMap::new(),
         }
     }
 
-    pub fn add_native_method(&mut self, method: Box<dyn NativeMethod>) {
-        self.native_methods.insert(method.get_name(), method);
+    // a native registered without a descriptor is bound to every overload of the method
+    pub fn add_native_method(
+        &mut self,
+        class_name: &str,
+        method_name: &str,
+        method_descriptor: Option<&str>,
+        method: Box<dyn NativeMethod>,
+    ) {
+        let jni_name = match method_descriptor {
+            Some(method_descriptor) => get_jni_long_name(class_name, method_name, method_descriptor),
+            None => get_jni_short_name(class_name, method_name),
+        };
+        self.add_jni_native_method(&jni_name, method);
     }
 
-    pub fn add_classpath_entry(&mut self, class_bytes: &[u8]) {
-        let cls = classfile_parser::parse_class_bytes(class_bytes).unwrap();
-        self.class_files.insert(get_constant_string(&cls.const_pool, cls.this_class).clone(), cls);
+    pub fn add_jni_native_method(&mut self, jni_name: &str, method: Box<dyn NativeMethod>) {
+        self.native_methods.insert(String::from(jni_name), method);
     }
 
-    pub fn add_classpath_jar(&mut self, jar_bytes: &[u8]) {
+    // marks a method implemented in bytecode as native, so that its invocations are dispatched to
+    // the native method registered under its JNI name instead
+    pub fn add_native_override(&mut self, class_name: &str, method_name: &str, method_descriptor: &str) {
+        if let Some(cls) = self.class_files.get_mut(class_name) {
+            Classpath::apply_native_override(cls, method_name, method_descriptor);
+        }
+        self.native_overrides
+            .entry(String::from(class_name))
+            .or_default()
+            .push((String::from(method_name), String::from(method_descriptor)));
+    }
+
+    fn apply_native_override(cls: &mut ClassFile, method_name: &str, method_descriptor: &str) {
+        let const_pool = &cls.const_pool;
+        if let Some(method) = cls.methods.iter_mut().find(|method| {
+            get_constant_string(const_pool, method.name_index) == method_name
+                && get_constant_string(const_pool, method.descriptor_index) == method_descriptor
+        }) {
+            method.access_flags.insert(MethodAccessFlags::NATIVE);
+        }
+    }
+
+    pub fn add_classpath_entry(&mut self, class_bytes: &[u8]) -> Result<(), String> {
+        let cls = Classpath::parse_class_file(class_bytes)?;
+        self.add_class_file(cls);
+        Ok(())
+    }
+
+    fn parse_class_file(class_bytes: &[u8]) -> Result<ClassFile, String> {
+        let cls = classfile_parser::parse_class_bytes(class_bytes)?;
+        validate_class_file(&cls)?;
+        Ok(cls)
+    }
+
+    fn add_class_file(&mut self, mut cls: ClassFile) {
+        let class_name = get_constant_string(&cls.const_pool, cls.this_class).clone();
+        if let Some(overrides) = self.native_overrides.get(&class_name) {
+            for (method_name, method_descriptor) in overrides {
+                Classpath::apply_native_override(&mut cls, method_name, method_descriptor);
+            }
+        }
+        self.class_files.insert(class_name, cls);
+    }
+
+    pub fn add_classpath_resource(&mut self, name: &str, resource_bytes: &[u8]) {
+        self.resources.entry(String::from(name)).or_default().push(resource_bytes.to_vec());
+    }
+
+    pub fn add_classpath_jar(&mut self, jar_bytes: &[u8]) -> Result<(), String> {
+        let contents = Classpath::read_jar(jar_bytes)?;
+        self.add_jar_contents(contents);
+        Ok(())
+    }
+
+    // the whole jar is read and validated before any of it is added to the classpath
+    fn read_jar(jar_bytes: &[u8]) -> Result<JarContents, String> {
         use std::io::prelude::*;
         use zip::*;
 
+        let mut contents = JarContents {
+            class_files: Vec::new(),
+            resources: Vec::new(),
+        };
         let mut cursor = Cursor::new(jar_bytes);
-        let mut zip = ZipArchive::new(&mut cursor).expect("invalid zip archive");
+        let mut zip = ZipArchive::new(&mut cursor).map_err(|err| format!("invalid zip archive: {}", err))?;
         for i in 0..zip.len() {
-            let mut file = zip.by_index(i).expect("invalid zip file content");
+            let mut file = zip.by_index(i).map_err(|err| format!("invalid zip file content: {}", err))?;
+            if file.is_dir() {
+                continue;
+            }
+
+            let mut bytes = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut bytes).map_err(|err| format!("error reading {}: {}", file.name(), err))?;
             if file.name().ends_with(".class") {
-                let mut bytes = Vec::with_capacity(file.size() as usize);
-                file.read_to_end(&mut bytes).expect("error reading zip");
-                self.add_classpath_entry(bytes.as_slice());
+                let cls =
+                    Classpath::parse_class_file(bytes.as_slice()).map_err(|err| format!("{}: {}", file.name(), err))?;
+                contents.class_files.push(cls);
+            } else {
+                contents.resources.push((String::from(file.name()), bytes));
+            }
+        }
+        Ok(contents)
+    }
+
+    fn add_jar_contents(&mut self, contents: JarContents) {
+        for cls in contents.class_files {
+            self.add_class_file(cls);
+        }
+        for (name, bytes) in contents.resources {
+            self.add_classpath_resource(&name, bytes.as_slice());
+        }
+    }
+
+    // registered jars are only added to the classpath once they are referenced by the Class-Path
+    // attribute of another jar's manifest
+    pub fn register_jar(&mut self, name: &str, jar_bytes: &[u8]) {
+        self.registered_jars.insert(String::from(name), jar_bytes.to_vec());
+    }
+
+    fn resolve_registered_jar(&self, class_path_entry: &str) -> Option<String> {
+        if self.registered_jars.contains_key(class_path_entry) || self.loaded_jars.contains(class_path_entry) {
+            return Some(String::from(class_path_entry));
+        }
+
+        let file_name = |path: &str| String::from(&path[path.rfind('/').map(|x| x + 1).unwrap_or(0)..]);
+        let entry_file_name = file_name(class_path_entry);
+        self.registered_jars
+            .keys()
+            .chain(self.loaded_jars.iter())
+            .find(|name| file_name(name) == entry_file_name)
+            .cloned()
+    }
+
+    // the jar and all of its dependencies are read before any of them is added, so that an error
+    // leaves the classpath unchanged
+    pub fn add_classpath_jar_with_dependencies(&mut self, jar_bytes: &[u8]) -> Result<Option<JarManifest>, String> {
+        let manifest = JarManifest::from_jar(jar_bytes)?;
+        let mut jars = vec![Classpath::read_jar(jar_bytes)?];
+        let mut dependencies = Vec::new();
+        if let Some(manifest) = &manifest {
+            self.read_dependencies(manifest, &mut dependencies, &mut jars)?;
+        }
+
+        for jar_name in dependencies {
+            self.registered_jars.remove(&jar_name);
+            self.loaded_jars.insert(jar_name);
+        }
+        for contents in jars {
+            self.add_jar_contents(contents);
+        }
+
+        Ok(manifest)
+    }
+
+    fn read_dependencies(
+        &self,
+        manifest: &JarManifest,
+        dependencies: &mut Vec<String>,
+        jars: &mut Vec<JarContents>,
+    ) -> Result<(), String> {
+        for class_path_entry in manifest.get_class_path() {
+            let jar_name = match self.resolve_registered_jar(class_path_entry) {
+                Some(name) => name,
+                None => {
+                    log_error(&format!("Class-Path entry not registered: {}", class_path_entry));
+                    continue;
+                }
+            };
+            if dependencies.contains(&jar_name) {
+                continue;
+            }
+            if let Some(dependency) = self.registered_jars.get(&jar_name) {
+                dependencies.push(jar_name);
+                jars.push(Classpath::read_jar(dependency)?);
+                if let Some(manifest) = JarManifest::from_jar(dependency)? {
+                    self.read_dependencies(&manifest, dependencies, jars)?;
+                }
             }
         }
+
+        Ok(())
     }
 
     #[allow(clippy::borrowed_box)]
-    pub fn get_native_method(&self, name: &str) -> Option<&Box<dyn NativeMethod>> {
-        self.native_methods.get(name)
+    pub fn get_native_method(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        method_descriptor: &str,
+    ) -> Option<&Box<dyn NativeMethod>> {
+        self.native_methods
+            .get(&get_jni_long_name(class_name, method_name, method_descriptor))
+            .or_else(|| self.native_methods.get(&get_jni_short_name(class_name, method_name)))
     }
 
     pub fn get_classpath_entry(&self, name: &str) -> Option<&ClassFile> {
         self.class_files.get(name)
     }
 
+    pub fn get_classpath_resources(&self, name: &str) -> &[Vec<u8>] {
+        match self.resources.get(name) {
+            Some(resources) => resources.as_slice(),
+            None => &[],
+        }
+    }
+
     pub fn get_field<'a>(
         &self,
         declaring_class: &'a ClassFile,
@@ -156,7 +326,7 @@ impl Classpath {
                 None
             } else {
                 let superclass_name = get_constant_string(&declaring_class.const_pool, declaring_class.super_class);
-                let superclass = self.get_classpath_entry(superclass_name).expect("class not found");
+                let superclass = self.get_classpath_entry(superclass_name)?;
                 self.get_virtual_method(superclass, method_name, method_descriptor)
             }
         })
@@ -173,7 +343,7 @@ impl Classpath {
                 None
             } else {
                 let superclass_name = get_constant_string(&declaring_class.const_pool, declaring_class.super_class);
-                let superclass = self.get_classpath_entry(superclass_name).expect("class not found");
+                let superclass = self.get_classpath_entry(superclass_name)?;
                 self.get_static_method(superclass, method_name, method_descriptor)
             }
         })
@@ -226,29 +396,46 @@ impl WebJvmClasspath {
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmClasspath", js_name = addNativeMethod)]
-    pub fn add_native_method(&mut self, native_method: js_sys::Function) -> Result<(), JsValue> {
-        if native_method.name() == "anonymous" {
-            return Err("anonymous functions cannot be bound to the JNI".into());
-        }
-
-        self.classpath.add_native_method(Box::new(native_method));
-        Ok(())
+    pub fn add_native_method(
+        &mut self,
+        class_name: &str,
+        method_name: &str,
+        method_descriptor: Option<String>,
+        native_method: js_sys::Function,
+    ) {
+        self.classpath.add_native_method(
+            &class_name.replace(".", "/"),
+            method_name,
+            method_descriptor.as_deref(),
+            Box::new(native_method),
+        );
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmClasspath", js_name = addClasspathEntry)]
-    pub fn add_classpath_entry(&mut self, class_bytes: &[u8]) {
-        self.classpath.add_classpath_entry(class_bytes);
+    pub fn add_classpath_entry(&mut self, class_bytes: &[u8]) -> Result<(), JsValue> {
+        self.classpath.add_classpath_entry(class_bytes).map_err(|err| js_sys::Error::new(&err).into())
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmClasspath", js_name = addClasspathJar)]
-    pub fn add_classpath_jar(&mut self, jar_bytes: &[u8]) {
-        self.classpath.add_classpath_jar(jar_bytes);
+    pub fn add_classpath_jar(&mut self, jar_bytes: &[u8]) -> Result<(), JsValue> {
+        self.classpath.add_classpath_jar(jar_bytes).map_err(|err| js_sys::Error::new(&err).into())
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmClasspath", js_name = registerJar)]
+    pub fn register_jar(&mut self, name: &str, jar_bytes: &[u8]) {
+        self.classpath.register_jar(name, jar_bytes);
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmClasspath", js_name = addClasspathResource)]
+    pub fn add_classpath_resource(&mut self, name: &str, resource_bytes: &[u8]) {
+        self.classpath.add_classpath_resource(name, resource_bytes);
     }
 }
 
 #[wasm_bindgen]
 pub struct WebJvmRuntime {
-    jvm: Jvm,
+    jvm: Rc<RefCell<Jvm>>,
+    virtual_clock: RefCell<Option<Rc<exec::clock::VirtualClock>>>,
 }
 
 #[wasm_bindgen]
@@ -258,61 +445,434 @@ impl WebJvmRuntime {
         let mut classpath = wc.classpath;
         native::initialize(&mut classpath);
         WebJvmRuntime {
-            jvm: Jvm::new(classpath),
+            jvm: Rc::new(RefCell::new(Jvm::new(classpath))),
+            virtual_clock: RefCell::new(None),
+        }
+    }
+
+    // the callbacks receive the raw bytes of every write as a Uint8Array
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = setStdout)]
+    pub fn set_stdout(&self, callback: js_sys::Function) {
+        self.jvm.borrow().set_stdout(Box::new(js::CallbackSink::new(callback)));
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = setStderr)]
+    pub fn set_stderr(&self, callback: js_sys::Function) {
+        self.jvm.borrow().set_stderr(Box::new(js::CallbackSink::new(callback)));
+    }
+
+    // makes identity hash codes reproducible, has to be called before any hash code is generated
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = setIdentityHashSeed)]
+    pub fn set_identity_hash_seed(&self, seed: u32) {
+        self.jvm.borrow().set_identity_hash_seed(seed as u64);
+    }
+
+    // replaces the real clock with one that starts at the given time and only jumps ahead when every thread
+    // waits, so sleeps and timeouts finish immediately and reproducibly
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = useVirtualClock)]
+    pub fn use_virtual_clock(&self, epoch_millis: f64) {
+        let clock = Rc::new(exec::clock::VirtualClock::new(epoch_millis as i64));
+        self.jvm.borrow().set_clock(clock.clone());
+        self.virtual_clock.replace(Some(clock));
+    }
+
+    // moves the virtual clock ahead, threads whose deadlines have passed run once execution resumes
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = advanceClock)]
+    pub fn advance_clock(&self, millis: f64) -> Result<(), JsValue> {
+        let clock = self.virtual_clock.borrow();
+        let clock =
+            clock.as_ref().ok_or_else(|| JsValue::from(js_sys::Error::new("the virtual clock is not in use")))?;
+        clock.advance((millis * 1_000_000.0) as i64);
+        self.jvm.borrow().promises.borrow_mut().wake();
+        Ok(())
+    }
+
+    // System.in is read from the callback, which is called whenever more input is needed
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = setStdin)]
+    pub fn set_stdin(&self, callback: js_sys::Function) {
+        self.jvm.borrow().stdin.borrow_mut().set_callback(callback);
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = pushStdin)]
+    pub fn push_stdin(&self, data: JsValue) {
+        let jvm = self.jvm.borrow();
+        let mut stdin = jvm.stdin.borrow_mut();
+        match data.as_string() {
+            Some(str) => stdin.push(str.as_bytes()),
+            None => stdin.push(&js_sys::Uint8Array::new(&data).to_vec()),
         }
     }
 
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = closeStdin)]
+    pub fn close_stdin(&self) {
+        self.jvm.borrow().stdin.borrow_mut().close();
+    }
+
+    // copies the contents of a zip archive into the file system seen by java.io
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = loadFileSystem)]
+    pub fn load_file_system(&self, zip_bytes: &[u8]) -> Result<(), JsValue> {
+        let jvm = self.jvm.borrow();
+        let mut vfs = jvm.vfs.borrow_mut();
+        vfs::import_zip(vfs.get_file_system_mut(), zip_bytes).map_err(|err| js_sys::Error::new(&err).into())
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = saveFileSystem)]
+    pub fn save_file_system(&self) -> Result<Vec<u8>, JsValue> {
+        let jvm = self.jvm.borrow();
+        let vfs = jvm.vfs.borrow();
+        vfs::export_zip(vfs.get_file_system()).map_err(|err| js_sys::Error::new(&err).into())
+    }
+
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getClassName)]
     pub fn get_class_name(&self, id: usize) -> String {
-        let heap = self.jvm.heap.borrow();
+        let jvm = self.jvm.borrow();
+        let heap = jvm.heap.borrow();
         heap.loaded_classes[id].java_type.clone()
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = dumpClass)]
     pub fn dump_class(&self, name: &str) -> String {
-        let heap = self.jvm.heap.borrow();
+        let jvm = self.jvm.borrow();
+        let heap = jvm.heap.borrow();
         format!("{:?}", heap.loaded_classes[heap.loaded_classes_lookup[name]])
     }
 
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = pin)]
+    pub fn pin(&self, handle: usize) -> Result<(), JsValue> {
+        let jvm = self.jvm.borrow();
+        let mut handles = jvm.handles.borrow_mut();
+        match handles.pin(handle) {
+            true => Ok(()),
+            false => Err(js_sys::Error::new(&format!("invalid handle {}", handle)).into()),
+        }
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = release)]
+    pub fn release(&self, handle: usize) -> Result<(), JsValue> {
+        let jvm = self.jvm.borrow();
+        let mut handles = jvm.handles.borrow_mut();
+        match handles.release(handle) {
+            true => Ok(()),
+            false => Err(js_sys::Error::new(&format!("handle {} has already been released", handle)).into()),
+        }
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getTypeName)]
+    pub fn get_type_name(&self, handle: usize) -> Result<String, JsValue> {
+        self.with_env(|env| env.get_object_type_name(get_object_handle(env, handle)?))
+    }
+
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getObject)]
-    pub fn get_object(&self, id: usize) -> String {
-        let heap = self.jvm.heap.borrow();
-        let obj = &heap.object_heap_map.get(&id).unwrap();
-        format!("{:?}", obj)
+    pub fn get_object(&self, handle: usize) -> Result<JsValue, JsValue> {
+        self.with_env(|env| {
+            let id = get_object_handle(env, handle)?;
+            let fields = env.jvm.heap.borrow().object_heap_map[&id].instance_fields.clone();
+
+            let obj = js_sys::Object::new();
+            for (name, value) in fields.iter() {
+                let value = js::to_js_value(env, value, "Ljava/lang/Object;")?;
+                let _ = js_sys::Reflect::set(&obj, &JsValue::from(name.as_str()), &value);
+            }
+            Ok(obj.into())
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getField)]
+    pub fn get_field(&self, handle: usize, field_name: &str) -> Result<JsValue, JsValue> {
+        self.with_env(|env| {
+            let id = get_object_handle(env, handle)?;
+            js::to_js_value(env, &env.get_field(id, field_name)?, "Ljava/lang/Object;")
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getArrayLength)]
+    pub fn get_array_length(&self, handle: usize) -> Result<usize, JsValue> {
+        self.with_env(|env| env.get_array_length(get_array_handle(env, handle)?))
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getArrayElement)]
+    pub fn get_array_element(&self, handle: usize, index: usize) -> Result<JsValue, JsValue> {
+        self.with_env(|env| {
+            let value = env.get_array_element(get_array_handle(env, handle)?, index)?;
+            js::to_js_value(env, &value, "Ljava/lang/Object;")
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getArray)]
+    pub fn get_array(&self, handle: usize) -> Result<js_sys::Array, JsValue> {
+        self.with_env(|env| {
+            let array_id = get_array_handle(env, handle)?;
+            let values = env.jvm.heap.borrow().array_heap_map[&array_id].values.clone();
+
+            let array = js_sys::Array::new();
+            for value in values.iter() {
+                array.push(&js::to_js_value(env, value, "Ljava/lang/Object;")?);
+            }
+            Ok(array)
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = newArray)]
+    pub fn new_array(&self, data: JsValue) -> Result<JsValue, JsValue> {
+        self.with_env(|env| {
+            let (array_type, length) = match (js::get_typed_array_type(&data), js::get_typed_array_length(&data)) {
+                (Some(array_type), Some(length)) => (array_type, length),
+                _ => {
+                    return Err(
+                        env.throw_exception("java/lang/IllegalArgumentException", Some("expecting a typed array"))
+                    )
+                }
+            };
+            let array_id = env.new_array(array_type, length);
+            js::from_typed_array(env, array_id, 0, &data)?;
+            Ok(js::to_js_handle(env, &JavaValue::Array(array_id)))
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = toTypedArray)]
+    pub fn to_typed_array(&self, handle: usize) -> Result<JsValue, JsValue> {
+        self.with_env(|env| {
+            let array_id = get_array_handle(env, handle)?;
+            js::to_typed_array(env, array_id, 0, env.get_array_length(array_id)?)
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getArrayRegion)]
+    pub fn get_array_region(&self, handle: usize, start: usize, length: usize) -> Result<JsValue, JsValue> {
+        self.with_env(|env| js::to_typed_array(env, get_array_handle(env, handle)?, start, length))
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = setArrayRegion)]
+    pub fn set_array_region(&self, handle: usize, start: usize, data: JsValue) -> Result<(), JsValue> {
+        self.with_env(|env| js::from_typed_array(env, get_array_handle(env, handle)?, start, &data))
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getString)]
-    pub fn get_string(&self, id: usize) -> String {
-        let env = JniEnv::empty(&self.jvm);
-        env.get_string(id)
+    pub fn get_string(&self, handle: usize) -> Result<String, JsValue> {
+        self.with_env(|env| {
+            let id = get_object_handle(env, handle)?;
+            env.get_string(id)
+        })
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = getStacktrace)]
     pub fn get_stacktrace(&self) {
-        self.jvm.throw_npe();
+        self.jvm.borrow().throw_npe();
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = loadClass)]
+    pub fn load_class(&self, class_name: &str) -> Result<JsValue, JsValue> {
+        self.invoke_from_host(|env| {
+            let class_id = env.get_class_id(&class_name.replace(".", "/"))?;
+            let class_object = JavaValue::Object(Some(env.get_class_object(class_id)));
+            Ok(js::to_js_handle(env, &class_object))
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = newInstance)]
+    pub fn new_instance(
+        &self,
+        class_name: &str,
+        method_descriptor: &str,
+        args: Box<[JsValue]>,
+    ) -> Result<JsValue, JsValue> {
+        self.invoke_from_host(|env| {
+            let class_id = env.get_class_id(&class_name.replace(".", "/"))?;
+            let descriptor = js::parse_method_descriptor(env, method_descriptor)?;
+            let params = js::from_js_arguments(env, &descriptor, &args)?;
+
+            let instance = env.new_instance(class_id)?;
+            env.invoke_instance_method(InvokeType::Special, instance, class_id, "<init>", method_descriptor, &params)?;
+            Ok(js::to_js_handle(env, &JavaValue::Object(Some(instance))))
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = invokeStatic)]
+    pub fn invoke_static(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        method_descriptor: &str,
+        args: Box<[JsValue]>,
+    ) -> Result<JsValue, JsValue> {
+        self.invoke_from_host(|env| {
+            let class_id = env.get_class_id(&class_name.replace(".", "/"))?;
+            let descriptor = js::parse_method_descriptor(env, method_descriptor)?;
+            let params = js::from_js_arguments(env, &descriptor, &args)?;
+
+            let result = env.invoke_static_method(class_id, method_name, method_descriptor, &params)?;
+            js::to_js_return_value(env, &descriptor, result)
+        })
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = invokeMethod)]
+    pub fn invoke_method(
+        &self,
+        instance: JsValue,
+        method_name: &str,
+        method_descriptor: &str,
+        args: Box<[JsValue]>,
+    ) -> Result<JsValue, JsValue> {
+        self.invoke_from_host(|env| {
+            let instance = match js::from_js_value(env, &instance, "Ljava/lang/Object;")? {
+                JavaValue::Object(Some(instance)) => instance,
+                _ => return Err(env.jvm.throw_npe()),
+            };
+            let class_id = env.get_class_id(&env.get_object_type_name(instance)?)?;
+            let descriptor = js::parse_method_descriptor(env, method_descriptor)?;
+            let params = js::from_js_arguments(env, &descriptor, &args)?;
+
+            let result = env.invoke_instance_method(
+                InvokeType::Virtual,
+                instance,
+                class_id,
+                method_name,
+                method_descriptor,
+                &params,
+            )?;
+            js::to_js_return_value(env, &descriptor, result)
+        })
     }
 
     #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = executeMain)]
-    pub fn execute_main(&mut self, class_name: &str) -> Result<(), JsValue> {
-        let frame = {
-            let main_class = self.jvm.classpath.get_classpath_entry(class_name).expect("main class not found");
-            let main_method = self
-                .jvm
-                .classpath
-                .get_static_method(main_class, "main", "([Ljava/lang/String;)V")
-                .expect("main method not found")
-                .1;
-            self.jvm.create_stack_frame(main_class, main_method).unwrap()
+    pub fn execute_main(&self, class_name: &str, args: Box<[JsValue]>) -> Result<js_sys::Promise, JsValue> {
+        let args = get_program_arguments(&args)?;
+        let result = self.jvm.borrow().start_main(class_name, &args);
+        Ok(run_to_completion(self.jvm.clone(), result))
+    }
+
+    #[wasm_bindgen(method, js_class = "WebJvmRuntime", js_name = executeJar)]
+    pub fn execute_jar(&self, jar_bytes: &[u8], args: Box<[JsValue]>) -> Result<js_sys::Promise, JsValue> {
+        let manifest = {
+            // the classpath can only be extended while no Java code is running
+            let mut jvm = match self.jvm.try_borrow_mut() {
+                Ok(jvm) => jvm,
+                Err(_) => return Ok(reject_with_error("cannot execute a jar while Java code is running")),
+            };
+            match jvm.classpath.add_classpath_jar_with_dependencies(jar_bytes) {
+                Ok(Some(manifest)) => manifest,
+                Ok(None) => return Ok(reject_with_error("jar does not contain a manifest")),
+                Err(err) => return Ok(reject_with_error(&err)),
+            }
+        };
+        let main_class = match manifest.get_main_class() {
+            Some(main_class) => main_class,
+            None => return Ok(reject_with_error("jar manifest does not define a Main-Class")),
         };
+        let args = get_program_arguments(&args)?;
+        let result = self.jvm.borrow().start_main(&main_class, &args);
+        Ok(run_to_completion(self.jvm.clone(), result))
+    }
+}
 
-        exec::env::initialize(&mut self.jvm).unwrap();
-        self.jvm.push_call_stack_frame(frame);
-        self.jvm.executor.step_until_stack_depth(&self.jvm, 1).unwrap();
+impl WebJvmRuntime {
+    // exceptions thrown by the call are rejected as JavaScript errors
+    fn invoke_from_host(&self, call: impl FnOnce(&JniEnv) -> RuntimeResult<JsValue>) -> Result<JsValue, JsValue> {
+        let jvm = self.jvm.borrow();
+        let result = jvm.invoke_from_host(call);
+        result.map_err(|err| to_js_error(&jvm, err))
+    }
 
-        Ok(())
+    fn with_env<T>(&self, call: impl FnOnce(&JniEnv) -> RuntimeResult<T>) -> Result<T, JsValue> {
+        let jvm = self.jvm.borrow();
+        let result = call(&JniEnv::empty(&jvm));
+        result.map_err(|err| to_js_error(&jvm, err))
+    }
+}
+
+fn to_js_error(jvm: &Jvm, err: JavaThrowable) -> JsValue {
+    match err {
+        JavaThrowable::Unhandled(ex) | JavaThrowable::Handled(ex) => js_sys::Error::new(&jvm.describe_exception(ex)),
+        err => js_sys::Error::new(&err.to_string()),
+    }
+    .into()
+}
+
+fn get_handle_reference(env: &JniEnv, handle: usize) -> RuntimeResult<JavaValue> {
+    let reference = env.jvm.handles.borrow().get(handle);
+    match reference {
+        Some(reference) => Ok(reference),
+        None => {
+            Err(env.throw_exception("java/lang/IllegalArgumentException", Some(&format!("invalid handle {}", handle))))
+        }
+    }
+}
+
+fn get_object_handle(env: &JniEnv, handle: usize) -> RuntimeResult<usize> {
+    match get_handle_reference(env, handle)? {
+        JavaValue::Object(Some(id)) if env.jvm.heap.borrow().object_heap_map.contains_key(&id) => Ok(id),
+        _ => Err(env.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some(&format!("handle {} is not an object", handle)),
+        )),
+    }
+}
+
+fn get_array_handle(env: &JniEnv, handle: usize) -> RuntimeResult<usize> {
+    match get_handle_reference(env, handle)? {
+        JavaValue::Array(id) if env.jvm.heap.borrow().array_heap_map.contains_key(&id) => Ok(id),
+        _ => Err(env.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some(&format!("handle {} is not an array", handle)),
+        )),
     }
 }
 
+fn reject_with_error(message: &str) -> js_sys::Promise {
+    js_sys::Promise::reject(&js_sys::Error::new(message).into())
+}
+
+// the returned promise resolves to the exit status, suspended execution is resumed whenever a
+// promise awaited by a Java thread settles or a sleeping thread is due
+fn run_to_completion(jvm: Rc<RefCell<Jvm>>, result: RuntimeResult<Option<i32>>) -> js_sys::Promise {
+    let mut result = Some(result);
+    js_sys::Promise::new(&mut |resolve, reject| {
+        if let Some(result) = result.take() {
+            continue_execution(jvm.clone(), result, resolve, reject);
+        }
+    })
+}
+
+fn continue_execution(
+    jvm: Rc<RefCell<Jvm>>,
+    result: RuntimeResult<Option<i32>>,
+    resolve: js_sys::Function,
+    reject: js_sys::Function,
+) {
+    let _ = match result {
+        Ok(Some(status)) => resolve.call1(&JsValue::null(), &JsValue::from(status)),
+        Err(err) => reject.call1(&JsValue::null(), &to_js_error(&jvm.borrow(), err)),
+        Ok(None) => {
+            let is_resumed = Rc::new(Cell::new(false));
+            let create_resume_callback = || -> js_sys::Function {
+                let (jvm, resolve, reject, is_resumed) =
+                    (jvm.clone(), resolve.clone(), reject.clone(), is_resumed.clone());
+                Closure::once_into_js(move || {
+                    if !is_resumed.replace(true) {
+                        let result = jvm.borrow().resume();
+                        continue_execution(jvm, result, resolve, reject);
+                    }
+                })
+                .unchecked_into()
+            };
+
+            let jvm = jvm.borrow();
+            jvm.promises.borrow_mut().set_waker(create_resume_callback());
+            // a virtual clock only moves while threads wait for the host when it is advanced
+            let clock = jvm.clock.borrow();
+            if let Some(deadline) = jvm.threads.borrow().get_next_deadline().filter(|_| clock.is_real_time()) {
+                let delay = (deadline as i64 - clock.current_time_millis()).max(0);
+                js::set_timeout(&create_resume_callback(), delay as i32);
+            }
+            Ok(JsValue::undefined())
+        }
+    };
+}
+
+fn get_program_arguments(args: &[JsValue]) -> Result<Vec<String>, JsValue> {
+    args.iter().map(|arg| arg.as_string().ok_or_else(|| JsValue::from("program arguments must be strings"))).collect()
+}
+
 #[wasm_bindgen(start)]
 pub fn main() {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -335,16 +895,55 @@ mod tests {
         println!("Total unhandled opcodes: {}", total);
     }
 
+    fn create_jar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        use std::io::{Cursor, Write};
+        use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            zip.start_file(*name, FileOptions::default().compression_method(CompressionMethod::Stored)).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    pub fn failed_jar_leaves_classpath_unchanged() {
+        use crate::Classpath;
+        let main_class: &[u8] = include_bytes!("../test/java/MainTest.class");
+        let manifest: &[u8] = b"Manifest-Version: 1.0\r\nClass-Path: lib/dependency.jar\r\n";
+        let jar = create_jar(&[("META-INF/MANIFEST.MF", manifest), ("MainTest.class", main_class)]);
+
+        let mut cp = Classpath::new();
+        cp.register_jar("lib/dependency.jar", &create_jar(&[("Dependency.class", b"not a class")]));
+        assert!(cp.add_classpath_jar_with_dependencies(&jar).is_err());
+        assert!(cp.get_classpath_entry("MainTest").is_none());
+        assert!(cp.get_classpath_resources("META-INF/MANIFEST.MF").is_empty());
+
+        let unreadable_manifest =
+            create_jar(&[("META-INF/MANIFEST.MF", &[0xff, 0xfe]), ("MainTest.class", main_class)]);
+        assert!(cp.add_classpath_jar_with_dependencies(&unreadable_manifest).is_err());
+        assert!(cp.get_classpath_entry("MainTest").is_none());
+
+        // the dependency is still registered and gets loaded once it can be read
+        cp.register_jar("lib/dependency.jar", &create_jar(&[("dependency.txt", b"dependency")]));
+        let manifest = cp.add_classpath_jar_with_dependencies(&jar).unwrap().unwrap();
+        assert_eq!(manifest.get_class_path(), vec!["lib/dependency.jar"]);
+        assert!(cp.get_classpath_entry("MainTest").is_some());
+        assert_eq!(cp.get_classpath_resources("dependency.txt"), &[b"dependency".to_vec()]);
+    }
+
     #[test]
     pub fn run_tests() {
         use crate::*;
         let mut cp = WebJvmClasspath::new();
-        cp.add_classpath_jar(include_bytes!("../test/java/rt.jar"));
-        cp.add_classpath_entry(include_bytes!("../test/java/MainTest.class"));
+        cp.add_classpath_jar(include_bytes!("../test/java/rt.jar")).unwrap();
+        cp.add_classpath_entry(include_bytes!("../test/java/MainTest.class")).unwrap();
 
         println!("Executing JVM...");
-        let mut rt = WebJvmRuntime::new(cp);
-        rt.execute_main("MainTest").unwrap();
+        let rt = WebJvmRuntime::new(cp);
+        let status = rt.jvm.borrow().execute_main("MainTest", &[]).unwrap();
+        assert_eq!(status, 0);
         println!("Finished executing!");
     }
 }
@@ -0,0 +1,73 @@
+use std::{collections::HashMap, io::Cursor};
+
+pub struct JarManifest {
+    main_attributes: HashMap<String, String>,
+}
+
+impl JarManifest {
+    pub fn parse(manifest: &str) -> JarManifest {
+        // lines beginning with a single space continue the value of the previous line
+        let mut lines: Vec<String> = Vec::new();
+        for line in manifest.lines() {
+            let line = line.trim_end_matches('\r');
+            if let Some(continuation) = line.strip_prefix(' ') {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(continuation);
+                }
+            } else {
+                lines.push(String::from(line));
+            }
+        }
+
+        let mut main_attributes = HashMap::new();
+        for line in &lines {
+            // the main section ends at the first blank line, per-entry sections follow it
+            if line.is_empty() {
+                break;
+            }
+            if let Some(separator) = line.find(':') {
+                let key = line[0..separator].trim();
+                let value = line[separator + 1..].trim();
+                main_attributes.insert(key.to_ascii_lowercase(), String::from(value));
+            }
+        }
+
+        JarManifest {
+            main_attributes,
+        }
+    }
+
+    // returns None if the jar has no manifest
+    pub fn from_jar(jar_bytes: &[u8]) -> Result<Option<JarManifest>, String> {
+        use std::io::prelude::*;
+        use zip::{result::ZipError, *};
+
+        let mut cursor = Cursor::new(jar_bytes);
+        let mut zip = ZipArchive::new(&mut cursor).map_err(|err| format!("invalid zip archive: {}", err))?;
+        let mut file = match zip.by_name("META-INF/MANIFEST.MF") {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(format!("error reading manifest: {}", err)),
+        };
+        let mut contents = String::with_capacity(file.size() as usize);
+        file.read_to_string(&mut contents).map_err(|err| format!("error reading manifest: {}", err))?;
+
+        Ok(Some(JarManifest::parse(&contents)))
+    }
+
+    // attribute names are case-insensitive
+    pub fn get_main_attribute(&self, name: &str) -> Option<&str> {
+        self.main_attributes.get(&name.to_ascii_lowercase()).map(|value| value.as_str())
+    }
+
+    pub fn get_main_class(&self) -> Option<String> {
+        self.get_main_attribute("Main-Class").map(|main_class| main_class.replace(".", "/"))
+    }
+
+    pub fn get_class_path(&self) -> Vec<&str> {
+        match self.get_main_attribute("Class-Path") {
+            Some(class_path) => class_path.split_whitespace().collect(),
+            None => Vec::new(),
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use std::{
+    borrow::Cow,
     collections::HashMap,
+    fmt,
     ops::{Index, IndexMut},
 };
 
-use crate::exec::{env::JniEnv, jvm::Jvm};
+use crate::{
+    exec::{env::JniEnv, identity_hash::IdentityHashGenerator, jvm::Jvm},
+    StackTraceElement,
+};
 use classfile_parser::{attribute_info::CodeAttribute, method_info::MethodAccessFlags, ClassAccessFlags};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -25,8 +30,8 @@ pub enum JavaValue {
 }
 
 impl JavaValue {
-    pub fn default(descriptor: &str) -> JavaValue {
-        match descriptor.chars().next().expect("invalid descriptor") {
+    pub fn default(descriptor: &str) -> Result<JavaValue, ()> {
+        Ok(match descriptor.chars().next().ok_or(())? {
             'B' => JavaValue::Byte(0),
             'S' => JavaValue::Short(0),
             'I' => JavaValue::Int(0),
@@ -36,8 +41,8 @@ impl JavaValue {
             'C' => JavaValue::Char(0),
             'Z' => JavaValue::Boolean(false),
             'L' | '[' => JavaValue::Object(None),
-            _ => panic!("invalid descriptor"),
-        }
+            _ => return Err(()),
+        })
     }
 
     pub fn is_object(&self) -> bool {
@@ -138,6 +143,7 @@ pub enum JavaArrayType {
 pub struct JavaArray {
     pub array_type: JavaArrayType,
     pub values: Vec<JavaValue>,
+    pub identity_hash: Option<i32>,
 }
 
 #[derive(Debug, Clone)]
@@ -147,29 +153,47 @@ pub enum InternalMetadata {
 }
 
 impl InternalMetadata {
-    pub fn into_string(self) -> String {
+    pub fn into_string(self) -> Result<String, VmError> {
         match self {
-            InternalMetadata::Text(val) => val,
-            _ => panic!(),
+            InternalMetadata::Text(val) => Ok(val),
+            other => Err(VmError::Internal(format!("expected text metadata, found {:?}", other))),
         }
     }
 
-    pub fn into_usize(self) -> usize {
+    pub fn into_usize(self) -> Result<usize, VmError> {
         match self {
-            InternalMetadata::Numeric(val) => val,
-            _ => panic!(),
+            InternalMetadata::Numeric(val) => Ok(val),
+            other => Err(VmError::Internal(format!("expected numeric metadata, found {:?}", other))),
         }
     }
 }
 
+// a field hidden by a field of the same name in a subclass is stored under its qualified name
+pub fn get_hidden_field_key(declaring_class: &str, name: &str) -> String {
+    format!("{}.{}", declaring_class, name)
+}
+
 #[derive(Debug)]
 pub struct JavaObject {
     pub class_id: usize,
     pub internal_metadata: HashMap<String, InternalMetadata>,
     pub instance_fields: HashMap<String, JavaValue>,
+    pub has_hidden_fields: bool,
+    pub identity_hash: Option<i32>,
 }
 
 impl JavaObject {
+    // the key the field of the given declaring class is stored under
+    pub fn get_field_key<'a>(&self, declaring_class: &str, name: &'a str) -> Cow<'a, str> {
+        if self.has_hidden_fields {
+            let key = get_hidden_field_key(declaring_class, name);
+            if self.instance_fields.contains_key(&key) {
+                return Cow::Owned(key);
+            }
+        }
+        Cow::Borrowed(name)
+    }
+
     pub fn set_field(&mut self, jvm: &Jvm, name: &str, val: JavaValue) -> RuntimeResult<()> {
         if !self.instance_fields.contains_key(name) {
             Err(jvm.throw_exception("java/lang/NoSuchFieldError", Some(name)))
@@ -227,9 +251,10 @@ impl JavaClass {
             match current_class.superclass_id {
                 Some(id) => current_class = &heap.loaded_classes[id],
                 None => {
+                    drop(heap);
                     return Err(
                         jvm.throw_exception("java/lang/NoSuchFieldError", Some(&format!("{}.{}", root_class, name)))
-                    )
+                    );
                 }
             }
         }
@@ -263,6 +288,28 @@ pub struct CallStackFrame {
     pub metadata: Option<CodeAttribute>,
     pub instructions: Vec<u8>,
     pub state: CallStackFrameState,
+    pub monitor: Option<usize>,
+}
+
+impl CallStackFrame {
+    pub fn new_virtual(container_class: &str, container_method: &str) -> CallStackFrame {
+        CallStackFrame {
+            container_class: String::from(container_class),
+            container_method: String::from(container_method),
+            access_flags: MethodAccessFlags::STATIC,
+            instructions: Vec::new(),
+            is_native_frame: false,
+            metadata: None,
+            state: CallStackFrameState {
+                instruction_offset: 0,
+                line_number: 0,
+                lvt: JavaValueVec::new(),
+                return_stack_value: None,
+                stack: JavaValueVec::new(),
+            },
+            monitor: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -305,7 +352,7 @@ impl JavaValueVec {
                         };
                         if &java_type == "java/lang/String" {
                             let env = JniEnv::empty(jvm);
-                            format!("String(\"{}\")", env.get_string(*id))
+                            format!("String(\"{}\")", env.get_string(*id).unwrap_or_default())
                         } else {
                             java_type
                         }
@@ -422,6 +469,10 @@ impl JavaValueVec {
         self.len() == 0
     }
 
+    pub fn iter(&self) -> std::slice::Iter<'_, JavaValue> {
+        self.vec.iter()
+    }
+
     pub fn reverse(&mut self) {
         self.vec.reverse();
     }
@@ -444,6 +495,7 @@ pub struct Heap {
     pub interned_string_map: HashMap<String, usize>,
     pub object_id_offset: usize,
     pub main_thread_object: usize,
+    pub identity_hashes: IdentityHashGenerator,
 }
 
 pub type RuntimeResult<T> = std::result::Result<T, JavaThrowable>;
@@ -452,6 +504,61 @@ pub type RuntimeResult<T> = std::result::Result<T, JavaThrowable>;
 pub enum JavaThrowable {
     Handled(usize),
     Unhandled(usize),
+    Exit(i32),
+    Suspended,
+    Fatal(VmError, Vec<StackTraceElement>),
+}
+
+impl fmt::Display for JavaThrowable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JavaThrowable::Handled(id) => write!(f, "handled exception {}", id),
+            JavaThrowable::Unhandled(id) => write!(f, "unhandled exception {}", id),
+            JavaThrowable::Exit(status) => write!(f, "exit with status {}", status),
+            JavaThrowable::Suspended => {
+                write!(f, "execution suspended until the host completes a call or a deadline passes")
+            }
+            JavaThrowable::Fatal(error, stack_trace) => {
+                write!(f, "{}", error)?;
+                for element in stack_trace {
+                    write!(f, "\n\tat {}.{}", element.class_name.replace("/", "."), element.method)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum VmError {
+    Internal(String),
+    Linkage {
+        error_class: String,
+        message: String,
+    },
+    MalformedClassFile {
+        class_name: String,
+        message: String,
+    },
+    // a request of the embedder that cannot be served in the current state of the VM
+    IllegalHostCall(String),
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::Internal(message) => write!(f, "internal VM error: {}", message),
+            VmError::Linkage {
+                error_class,
+                message,
+            } => write!(f, "{}: {}", error_class.replace("/", "."), message),
+            VmError::MalformedClassFile {
+                class_name,
+                message,
+            } => write!(f, "malformed class file {}: {}", class_name, message),
+            VmError::IllegalHostCall(message) => write!(f, "{}", message),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -463,7 +570,7 @@ pub struct MethodDescriptor {
 impl MethodDescriptor {
     fn read_token(desc: &[char], mut offset: usize) -> Result<(String, usize), ()> {
         let mut token = String::with_capacity(1);
-        while desc[offset] == '[' {
+        while offset < desc.len() && desc[offset] == '[' {
             token.push(desc[offset]);
             offset += 1;
         }
@@ -479,6 +586,9 @@ impl MethodDescriptor {
                 while desc[offset] != ';' {
                     token.push(desc[offset]);
                     offset += 1;
+                    if offset == desc.len() {
+                        return Err(());
+                    }
                 }
                 token.push(';');
                 offset += 1;
@@ -491,7 +601,7 @@ impl MethodDescriptor {
 
     pub fn new(desc: &str) -> Result<MethodDescriptor, ()> {
         let chars: Vec<char> = desc.chars().collect();
-        let open_paren = chars.iter().position(|ch| *ch == '(').unwrap();
+        let open_paren = chars.iter().position(|ch| *ch == '(').ok_or(())?;
         let mut argument_types = Vec::new();
         let mut offset = open_paren + 1;
         while offset < chars.len() && chars[offset] != ')' {
@@ -499,7 +609,7 @@ impl MethodDescriptor {
             argument_types.push(token);
             offset = new_offset;
         }
-        if chars[offset] != ')' {
+        if offset == chars.len() || chars[offset] != ')' {
             return Err(());
         }
         offset += 1;
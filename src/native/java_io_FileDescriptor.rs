@@ -1,9 +1,69 @@
+use std::io;
+
 use crate::{
     exec::env::JniEnv,
-    model::{JavaValue, RuntimeResult},
+    model::{JavaThrowable, JavaValue, RuntimeResult},
     Classpath,
 };
 
+// the descriptor number of the FileDescriptor held by a stream or RandomAccessFile
+pub fn get_fd(env: &JniEnv, stream: usize) -> RuntimeResult<i32> {
+    let fd = match env.get_field(stream, "fd")? {
+        JavaValue::Object(Some(fd_obj)) => env.get_field(fd_obj, "fd")?.as_int().unwrap(),
+        _ => -1,
+    };
+    if fd < 0 {
+        return Err(env.throw_exception("java/io/IOException", Some("Stream Closed")));
+    }
+    Ok(fd)
+}
+
+pub fn set_fd(env: &JniEnv, stream: usize, fd: i32) -> RuntimeResult<()> {
+    match env.get_field(stream, "fd")? {
+        JavaValue::Object(Some(fd_obj)) => env.set_field(fd_obj, "fd", JavaValue::Int(fd)),
+        _ => Err(env.throw_exception("java/io/IOException", Some("Stream Closed"))),
+    }
+}
+
+pub fn close_fd(env: &JniEnv, stream: usize) -> RuntimeResult<()> {
+    let fd = match env.get_field(stream, "fd")? {
+        JavaValue::Object(Some(fd_obj)) => env.get_field(fd_obj, "fd")?.as_int().unwrap(),
+        _ => return Ok(()),
+    };
+    if fd > 2 {
+        env.jvm.vfs.borrow_mut().close(fd);
+    }
+    set_fd(env, stream, -1)
+}
+
+pub fn io_exception(env: &JniEnv, err: io::Error) -> JavaThrowable {
+    env.throw_exception("java/io/IOException", Some(&err.to_string()))
+}
+
+pub fn file_not_found_exception(env: &JniEnv, path: &str, err: io::Error) -> JavaThrowable {
+    env.throw_exception("java/io/FileNotFoundException", Some(&format!("{} ({})", path, err)))
+}
+
+pub fn get_path_parameter(env: &JniEnv, index: usize) -> RuntimeResult<String> {
+    match env.parameters[index] {
+        JavaValue::Object(Some(path)) => env.get_string(path),
+        _ => Err(env.throw_exception("java/lang/NullPointerException", None)),
+    }
+}
+
+pub fn get_byte_region(env: &JniEnv, array_index: usize) -> RuntimeResult<(usize, usize, usize)> {
+    let byte_buffer = match env.parameters[array_index] {
+        JavaValue::Array(byte_buffer) => byte_buffer,
+        _ => return Err(env.throw_exception("java/lang/NullPointerException", None)),
+    };
+    let offset = env.parameters[array_index + 1].as_int().unwrap();
+    let length = env.parameters[array_index + 2].as_int().unwrap();
+    if offset < 0 || length < 0 || offset as usize + length as usize > env.get_array_length(byte_buffer)? {
+        return Err(env.throw_exception("java/lang/IndexOutOfBoundsException", None));
+    }
+    Ok((byte_buffer, offset as usize, length as usize))
+}
+
 #[allow(non_snake_case)]
 fn Java_java_io_FileDescriptor_initIDs(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     Ok(None)
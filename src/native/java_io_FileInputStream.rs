@@ -1,5 +1,13 @@
+use super::java_io_FileDescriptor::{
+    close_fd, file_not_found_exception, get_byte_region, get_fd, get_path_parameter, io_exception, set_fd,
+};
 use crate::{
-    exec::env::JniEnv,
+    exec::{
+        env::JniEnv,
+        stdin::{StdinRead, MAX_READ_LENGTH},
+        vfs::OpenOptions,
+    },
+    js::{self, HostCallResult},
     model::{JavaValue, RuntimeResult},
     Classpath,
 };
@@ -9,6 +17,140 @@ fn Java_java_io_FileInputStream_initIDs(_: &JniEnv) -> RuntimeResult<Option<Java
     Ok(None)
 }
 
+// None when no input is available yet and the thread has been parked until there is
+fn read_stdin(env: &JniEnv, max_length: usize) -> RuntimeResult<Option<StdinRead>> {
+    match js::take_host_call_result(env) {
+        HostCallResult::None => (),
+        HostCallResult::Pending => return Ok(None),
+        HostCallResult::Settled(Ok(value)) => env.jvm.stdin.borrow_mut().accept(&value),
+        HostCallResult::Settled(Err(err)) => {
+            return Err(env.throw_exception("java/io/IOException", Some(&js::get_error_message(&err))))
+        }
+    }
+
+    let result = env.jvm.stdin.borrow_mut().read(max_length);
+    match result {
+        Ok(StdinRead::Pending(promise)) => {
+            js::await_promise(env, &promise);
+            Ok(None)
+        }
+        Ok(result) => Ok(Some(result)),
+        Err(message) => Err(env.throw_exception("java/io/IOException", Some(&message))),
+    }
+}
+
+fn read(env: &JniEnv, max_length: usize) -> RuntimeResult<Option<StdinRead>> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    if fd == 0 {
+        return read_stdin(env, max_length);
+    }
+
+    let mut buf = vec![0; max_length];
+    let result = env.jvm.vfs.borrow_mut().read(fd, &mut buf);
+    match result {
+        Ok(0) if max_length > 0 => Ok(Some(StdinRead::Eof)),
+        Ok(length) => {
+            buf.truncate(length);
+            Ok(Some(StdinRead::Data(buf)))
+        }
+        Err(err) => Err(io_exception(env, err)),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_open0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_path_parameter(env, 1)?;
+    let options = OpenOptions {
+        read: true,
+        write: false,
+        append: false,
+        create: false,
+        truncate: false,
+    };
+    let result = env.jvm.vfs.borrow_mut().open(&path, options);
+    match result {
+        Ok(fd) => set_fd(env, env.get_current_instance()?, fd)?,
+        Err(err) => return Err(file_not_found_exception(env, &path, err)),
+    }
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_read0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(match read(env, 1)? {
+        Some(StdinRead::Data(data)) => Some(JavaValue::Int(data[0] as i32)),
+        Some(_) => Some(JavaValue::Int(-1)),
+        None => None,
+    })
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_readBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (byte_buffer, offset, length) = get_byte_region(env, 1)?;
+    if length == 0 {
+        return Ok(Some(JavaValue::Int(0)));
+    }
+
+    Ok(match read(env, length)? {
+        Some(StdinRead::Data(data)) => {
+            let data: Vec<i8> = data.into_iter().map(|b| b as i8).collect();
+            env.set_byte_array_region(byte_buffer, offset, &data)?;
+            Some(JavaValue::Int(data.len() as i32))
+        }
+        Some(_) => Some(JavaValue::Int(-1)),
+        None => None,
+    })
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_available0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let available = match get_fd(env, env.get_current_instance()?)? {
+        0 => env.jvm.stdin.borrow().available() as u64,
+        fd => {
+            let result = env.jvm.vfs.borrow_mut().available(fd);
+            result.map_err(|err| io_exception(env, err))?
+        }
+    };
+    Ok(Some(JavaValue::Int(available.min(i32::MAX as u64) as i32)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_skip(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let length = env.parameters[1].as_long().unwrap().max(0) as u64;
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    if fd != 0 {
+        let mut vfs = env.jvm.vfs.borrow_mut();
+        let skipped = vfs.available(fd).and_then(|available| {
+            let position = vfs.get_position(fd)?;
+            vfs.seek(fd, position + length.min(available))?;
+            Ok(length.min(available))
+        });
+        drop(vfs);
+        return Ok(Some(JavaValue::Long(skipped.map_err(|err| io_exception(env, err))? as i64)));
+    }
+
+    Ok(match read(env, length.min(MAX_READ_LENGTH as u64) as usize)? {
+        Some(StdinRead::Data(data)) => Some(JavaValue::Long(data.len() as i64)),
+        Some(_) => Some(JavaValue::Long(0)),
+        None => None,
+    })
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileInputStream_close0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    close_fd(env, env.get_current_instance()?)?;
+    Ok(None)
+}
+
 pub fn initialize(cp: &mut Classpath) {
-    register_jni!(cp, Java_java_io_FileInputStream_initIDs);
+    register_jni!(
+        cp,
+        Java_java_io_FileInputStream_initIDs,
+        Java_java_io_FileInputStream_open0,
+        Java_java_io_FileInputStream_read0,
+        Java_java_io_FileInputStream_readBytes,
+        Java_java_io_FileInputStream_available0,
+        Java_java_io_FileInputStream_skip,
+        Java_java_io_FileInputStream_close0
+    );
 }
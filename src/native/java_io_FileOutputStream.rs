@@ -1,8 +1,12 @@
+use super::java_io_FileDescriptor::{
+    close_fd, file_not_found_exception, get_byte_region, get_fd, get_path_parameter, io_exception, set_fd,
+};
 use crate::{
-    exec::env::JniEnv,
+    exec::{env::JniEnv, vfs::OpenOptions},
     model::{JavaValue, RuntimeResult},
     Classpath,
 };
+use std::io::Write;
 
 #[allow(non_snake_case)]
 fn Java_java_io_FileOutputStream_initIDs(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
@@ -10,48 +14,64 @@ fn Java_java_io_FileOutputStream_initIDs(_: &JniEnv) -> RuntimeResult<Option<Jav
 }
 
 #[allow(non_snake_case)]
-fn Java_java_io_FileOutputStream_writeBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let byte_buffer = env.parameters[1].as_array().unwrap();
-    let offset = env.parameters[2].as_int().unwrap();
-    let length = env.parameters[3].as_int().unwrap();
-    let _ = env.parameters[4].as_boolean().unwrap();
-
-    let mut local_buffer = Vec::with_capacity(length as usize);
-    for i in offset..offset + length {
-        local_buffer.push(env.get_array_element(byte_buffer, i as usize).as_byte().unwrap() as u8);
+fn Java_java_io_FileOutputStream_open0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_path_parameter(env, 1)?;
+    let append = env.parameters[2].as_boolean().unwrap();
+    let options = OpenOptions {
+        read: false,
+        write: true,
+        append,
+        create: true,
+        truncate: !append,
+    };
+    let result = env.jvm.vfs.borrow_mut().open(&path, options);
+    match result {
+        Ok(fd) => set_fd(env, env.get_current_instance()?, fd)?,
+        Err(err) => return Err(file_not_found_exception(env, &path, err)),
     }
+    Ok(None)
+}
 
-    let fos = env.get_current_instance()?;
-    let fd_obj = env.get_field(fos, "fd").as_object().unwrap().unwrap();
-    let fd = env.get_field(fd_obj, "fd").as_int().unwrap();
-    if fd == 1 || fd == 2 {
-        #[cfg(target_arch = "wasm32")]
-        {
-            let str = String::from_utf8(local_buffer).unwrap();
-            if fd == 1 {
-                web_sys::console::log_1(&str.into());
-            } else if fd == 2 {
-                web_sys::console::error_1(&str.into());
-            }
-        }
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use std::io::Write;
-            if fd == 1 {
-                let mut stdout = std::io::stdout();
-                stdout.write_all(&local_buffer).unwrap();
-            } else if fd == 2 {
-                let mut stderr = std::io::stderr();
-                stderr.write_all(&local_buffer).unwrap();
-            }
-        }
-    } else {
-        return Err(env.throw_exception("java/lang/UnsupportedOperationException", Some("writing to a file")));
-    }
+fn write(env: &JniEnv, data: &[u8]) -> RuntimeResult<()> {
+    let result = match get_fd(env, env.get_current_instance()?)? {
+        1 => env.jvm.stdout.borrow_mut().write_all(data),
+        2 => env.jvm.stderr.borrow_mut().write_all(data),
+        fd => env.jvm.vfs.borrow_mut().write(fd, data),
+    };
+    result.map_err(|err| io_exception(env, err))
+}
 
+#[allow(non_snake_case)]
+fn Java_java_io_FileOutputStream_write(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let byte = env.parameters[1].as_int().unwrap() as u8;
+    write(env, &[byte])?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileOutputStream_writeBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (byte_buffer, offset, length) = get_byte_region(env, 1)?;
+    let mut local_buffer = vec![0; length];
+    env.get_byte_array_region(byte_buffer, offset, &mut local_buffer)?;
+    let local_buffer: Vec<u8> = local_buffer.into_iter().map(|b| b as u8).collect();
+
+    write(env, &local_buffer)?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_FileOutputStream_close0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    close_fd(env, env.get_current_instance()?)?;
     Ok(None)
 }
 
 pub fn initialize(cp: &mut Classpath) {
-    register_jni!(cp, Java_java_io_FileOutputStream_initIDs, Java_java_io_FileOutputStream_writeBytes);
+    register_jni!(
+        cp,
+        Java_java_io_FileOutputStream_initIDs,
+        Java_java_io_FileOutputStream_open0,
+        Java_java_io_FileOutputStream_write,
+        Java_java_io_FileOutputStream_writeBytes,
+        Java_java_io_FileOutputStream_close0
+    );
 }
@@ -0,0 +1,151 @@
+use super::java_io_FileDescriptor::{
+    close_fd, file_not_found_exception, get_byte_region, get_fd, get_path_parameter, io_exception, set_fd,
+};
+use crate::{
+    exec::{env::JniEnv, vfs::OpenOptions},
+    model::{JavaValue, RuntimeResult},
+    Classpath,
+};
+
+const O_RDWR: i32 = 2;
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_initIDs(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_open0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_path_parameter(env, 1)?;
+    let is_writable = env.parameters[2].as_int().unwrap() & O_RDWR != 0;
+    let options = OpenOptions {
+        read: true,
+        write: is_writable,
+        append: false,
+        create: is_writable,
+        truncate: false,
+    };
+    let result = env.jvm.vfs.borrow_mut().open(&path, options);
+    match result {
+        Ok(fd) => set_fd(env, env.get_current_instance()?, fd)?,
+        Err(err) => return Err(file_not_found_exception(env, &path, err)),
+    }
+    Ok(None)
+}
+
+fn read(env: &JniEnv, buf: &mut [u8]) -> RuntimeResult<usize> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let result = env.jvm.vfs.borrow_mut().read(fd, buf);
+    result.map_err(|err| io_exception(env, err))
+}
+
+fn write(env: &JniEnv, data: &[u8]) -> RuntimeResult<()> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let result = env.jvm.vfs.borrow_mut().write(fd, data);
+    result.map_err(|err| io_exception(env, err))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_read0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let mut buf = [0];
+    Ok(Some(JavaValue::Int(match read(env, &mut buf)? {
+        0 => -1,
+        _ => buf[0] as i32,
+    })))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_readBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (byte_buffer, offset, length) = get_byte_region(env, 1)?;
+    if length == 0 {
+        return Ok(Some(JavaValue::Int(0)));
+    }
+
+    let mut buf = vec![0; length];
+    let length = read(env, &mut buf)?;
+    if length == 0 {
+        return Ok(Some(JavaValue::Int(-1)));
+    }
+    let data: Vec<i8> = buf[..length].iter().map(|b| *b as i8).collect();
+    env.set_byte_array_region(byte_buffer, offset, &data)?;
+    Ok(Some(JavaValue::Int(length as i32)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_write0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let byte = env.parameters[1].as_int().unwrap() as u8;
+    write(env, &[byte])?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_writeBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (byte_buffer, offset, length) = get_byte_region(env, 1)?;
+    let mut buf = vec![0; length];
+    env.get_byte_array_region(byte_buffer, offset, &mut buf)?;
+    let data: Vec<u8> = buf.into_iter().map(|b| b as u8).collect();
+
+    write(env, &data)?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_getFilePointer(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let result = env.jvm.vfs.borrow_mut().get_position(fd);
+    Ok(Some(JavaValue::Long(result.map_err(|err| io_exception(env, err))? as i64)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_seek0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let position = env.parameters[1].as_long().unwrap();
+    if position < 0 {
+        return Err(env.throw_exception("java/io/IOException", Some("Negative seek offset")));
+    }
+    let result = env.jvm.vfs.borrow_mut().seek(fd, position as u64);
+    result.map_err(|err| io_exception(env, err))?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_length(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let result = env.jvm.vfs.borrow_mut().get_length(fd);
+    Ok(Some(JavaValue::Long(result.map_err(|err| io_exception(env, err))? as i64)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_setLength(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let fd = get_fd(env, env.get_current_instance()?)?;
+    let length = env.parameters[1].as_long().unwrap();
+    if length < 0 {
+        return Err(env.throw_exception("java/io/IOException", Some("Invalid argument")));
+    }
+    let result = env.jvm.vfs.borrow_mut().set_length(fd, length as u64);
+    result.map_err(|err| io_exception(env, err))?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_RandomAccessFile_close0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    close_fd(env, env.get_current_instance()?)?;
+    Ok(None)
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(
+        cp,
+        Java_java_io_RandomAccessFile_initIDs,
+        Java_java_io_RandomAccessFile_open0,
+        Java_java_io_RandomAccessFile_read0,
+        Java_java_io_RandomAccessFile_readBytes,
+        Java_java_io_RandomAccessFile_write0,
+        Java_java_io_RandomAccessFile_writeBytes,
+        Java_java_io_RandomAccessFile_getFilePointer,
+        Java_java_io_RandomAccessFile_seek0,
+        Java_java_io_RandomAccessFile_length,
+        Java_java_io_RandomAccessFile_setLength,
+        Java_java_io_RandomAccessFile_close0
+    );
+}
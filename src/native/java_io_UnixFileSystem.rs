@@ -1,23 +1,150 @@
+use super::java_io_FileDescriptor::{get_path_parameter, io_exception};
 use crate::{
-    exec::env::JniEnv,
-    model::{JavaValue, RuntimeResult},
+    exec::{
+        env::JniEnv,
+        vfs::{get_file_name, normalize_path, FileMetadata},
+    },
+    model::{JavaArrayType, JavaValue, RuntimeResult},
     Classpath,
 };
 
+const BA_EXISTS: i32 = 0x01;
+const BA_REGULAR: i32 = 0x02;
+const BA_DIRECTORY: i32 = 0x04;
+const BA_HIDDEN: i32 = 0x08;
+
+fn get_file_path(env: &JniEnv, index: usize) -> RuntimeResult<String> {
+    match env.parameters[index] {
+        JavaValue::Object(Some(file)) => match env.get_field(file, "path")? {
+            JavaValue::Object(Some(path)) => Ok(normalize_path(&env.get_string(path)?)),
+            _ => Err(env.throw_exception("java/lang/NullPointerException", None)),
+        },
+        _ => Err(env.throw_exception("java/lang/NullPointerException", None)),
+    }
+}
+
+fn get_metadata(env: &JniEnv, index: usize) -> RuntimeResult<Option<FileMetadata>> {
+    let path = get_file_path(env, index)?;
+    Ok(env.jvm.vfs.borrow().get_file_system().metadata(&path))
+}
+
 #[allow(non_snake_case)]
 fn Java_java_io_UnixFileSystem_initIDs(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     Ok(None)
 }
 
 #[allow(non_snake_case)]
-fn Java_java_io_UnixFileSystem_getBooleanAttributes0(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    Ok(Some(JavaValue::Int(0)))
+fn Java_java_io_UnixFileSystem_getBooleanAttributes0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_file_path(env, 1)?;
+    let attributes = match env.jvm.vfs.borrow().get_file_system().metadata(&path) {
+        Some(metadata) => {
+            let file_type = if metadata.is_directory {
+                BA_DIRECTORY
+            } else {
+                BA_REGULAR
+            };
+            let hidden = if get_file_name(&path).starts_with('.') {
+                BA_HIDDEN
+            } else {
+                0
+            };
+            BA_EXISTS | file_type | hidden
+        }
+        None => 0,
+    };
+    Ok(Some(JavaValue::Int(attributes)))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_io_UnixFileSystem_canonicalize0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    // TODO: actually canonicalize paths
-    Ok(Some(env.parameters[1].clone()))
+    let path = get_path_parameter(env, 1)?;
+    Ok(Some(JavaValue::Object(Some(env.new_string(&normalize_path(&path))?))))
+}
+
+// there are no permissions, every existing file is accessible
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_checkAccess(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Boolean(get_metadata(env, 1)?.is_some())))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_setPermission(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Boolean(get_metadata(env, 1)?.is_some())))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_setReadOnly(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Boolean(get_metadata(env, 1)?.is_some())))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_getLastModifiedTime(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let last_modified = get_metadata(env, 1)?.map_or(0, |metadata| metadata.last_modified);
+    Ok(Some(JavaValue::Long(last_modified)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_setLastModifiedTime(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_file_path(env, 1)?;
+    let time = env.parameters[2].as_long().unwrap();
+    let result = env.jvm.vfs.borrow_mut().get_file_system_mut().set_last_modified(&path, time);
+    Ok(Some(JavaValue::Boolean(result.unwrap_or(false))))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_getLength(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let length = get_metadata(env, 1)?.map_or(0, |metadata| metadata.length);
+    Ok(Some(JavaValue::Long(length as i64)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_createFileExclusively(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = normalize_path(&get_path_parameter(env, 1)?);
+    let result = env.jvm.vfs.borrow_mut().get_file_system_mut().create_file(&path);
+    Ok(Some(JavaValue::Boolean(result.map_err(|err| io_exception(env, err))?)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_delete0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_file_path(env, 1)?;
+    let result = env.jvm.vfs.borrow_mut().get_file_system_mut().delete(&path);
+    Ok(Some(JavaValue::Boolean(result.unwrap_or(false))))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_createDirectory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_file_path(env, 1)?;
+    let result = env.jvm.vfs.borrow_mut().get_file_system_mut().create_directory(&path);
+    Ok(Some(JavaValue::Boolean(result.unwrap_or(false))))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_rename0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let from = get_file_path(env, 1)?;
+    let to = get_file_path(env, 2)?;
+    let result = env.jvm.vfs.borrow_mut().get_file_system_mut().rename(&from, &to);
+    Ok(Some(JavaValue::Boolean(result.unwrap_or(false))))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_list(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let path = get_file_path(env, 1)?;
+    let names = match env.jvm.vfs.borrow().get_file_system().list(&path) {
+        Some(names) => names,
+        None => return Ok(Some(JavaValue::Object(None))),
+    };
+
+    let string_class = env.get_class_id("java/lang/String")?;
+    let array = env.new_array(JavaArrayType::Object(string_class), names.len());
+    for (i, name) in names.iter().enumerate() {
+        env.set_array_element(array, i, JavaValue::Object(Some(env.new_string(name)?)))?;
+    }
+    Ok(Some(JavaValue::Array(array)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_io_UnixFileSystem_getSpace(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Long(0)))
 }
 
 pub fn initialize(cp: &mut Classpath) {
@@ -25,6 +152,18 @@ pub fn initialize(cp: &mut Classpath) {
         cp,
         Java_java_io_UnixFileSystem_initIDs,
         Java_java_io_UnixFileSystem_getBooleanAttributes0,
-        Java_java_io_UnixFileSystem_canonicalize0
+        Java_java_io_UnixFileSystem_canonicalize0,
+        Java_java_io_UnixFileSystem_checkAccess,
+        Java_java_io_UnixFileSystem_setPermission,
+        Java_java_io_UnixFileSystem_setReadOnly,
+        Java_java_io_UnixFileSystem_getLastModifiedTime,
+        Java_java_io_UnixFileSystem_setLastModifiedTime,
+        Java_java_io_UnixFileSystem_getLength,
+        Java_java_io_UnixFileSystem_createFileExclusively,
+        Java_java_io_UnixFileSystem_delete0,
+        Java_java_io_UnixFileSystem_createDirectory,
+        Java_java_io_UnixFileSystem_rename0,
+        Java_java_io_UnixFileSystem_list,
+        Java_java_io_UnixFileSystem_getSpace
     );
 }
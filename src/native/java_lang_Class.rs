@@ -1,8 +1,20 @@
-use classfile_parser::ClassAccessFlags;
+use classfile_parser::{
+    attribute_info::{
+        enclosing_method_attribute_parser, exceptions_attribute_parser, inner_classes_attribute_parser,
+        signature_attribute_parser, AttributeInfo, InnerClass,
+    },
+    constant_info::ConstantInfo,
+    field_info::{FieldAccessFlags, FieldInfo},
+    method_info::{MethodAccessFlags, MethodInfo},
+    ClassAccessFlags, ClassFile,
+};
 
 use crate::{
-    model::{JavaArrayType, JavaValue, MethodDescriptor, RuntimeResult},
-    util::get_constant_string,
+    model::{JavaArrayType, JavaThrowable, JavaValue, MethodDescriptor, RuntimeResult, VmError},
+    util::{
+        find_constant_name_and_type, find_constant_string, get_attribute, get_constant_string,
+        get_descriptor_class_name,
+    },
     Classpath, JniEnv,
 };
 
@@ -18,30 +30,27 @@ fn Java_java_lang_Class_desiredAssertionStatus0(_: &JniEnv) -> RuntimeResult<Opt
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getName0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_name = env.get_internal_metadata(env.get_current_instance()?, "class_name").unwrap().into_string();
+    let class_id = get_this_class_id(env)?;
+    let class_name = env.jvm.get_class_name_from_id(class_id);
     let non_internalized = class_name.replace("/", ".");
-    let result = env.new_string(&non_internalized);
+    let result = env.new_string(&non_internalized)?;
     Ok(Some(JavaValue::Object(Some(result))))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_isArray(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_name = env.get_internal_metadata(env.get_current_instance()?, "class_name").unwrap().into_string();
+    let class_name = env.get_text_metadata(env.get_current_instance()?, "class_name")?;
     Ok(Some(JavaValue::Boolean(class_name.starts_with('['))))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getComponentType(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_name = env.get_internal_metadata(env.get_current_instance()?, "class_name").unwrap().into_string();
+    let class_name = env.get_text_metadata(env.get_current_instance()?, "class_name")?;
     if !class_name.starts_with('[') {
         return Ok(Some(JavaValue::Object(None)));
     }
 
-    let mut component_name = &class_name[1..class_name.len()];
-    if component_name.starts_with('L') && component_name.ends_with(';') {
-        component_name = &component_name[1..component_name.len() - 1];
-    }
-    let component_class_id = env.get_class_id(component_name)?;
+    let component_class_id = env.get_class_id(get_descriptor_class_name(&class_name[1..]))?;
     let class_instance = env.get_class_object(component_class_id);
 
     Ok(Some(JavaValue::Object(Some(class_instance))))
@@ -50,7 +59,7 @@ fn Java_java_lang_Class_getComponentType(env: &JniEnv) -> RuntimeResult<Option<J
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getPrimitiveClass(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let class_name_id = env.get_current_instance()?;
-    let class_name = env.get_string(class_name_id);
+    let class_name = env.get_string(class_name_id)?;
     let signature_name = match class_name.as_str() {
         "byte" => "B",
         "short" => "S",
@@ -60,6 +69,7 @@ fn Java_java_lang_Class_getPrimitiveClass(env: &JniEnv) -> RuntimeResult<Option<
         "double" => "D",
         "char" => "C",
         "boolean" => "Z",
+        "void" => "V",
         x => return Err(env.throw_exception("java/lang/IllegalArgumentException", Some(x))),
     };
 
@@ -74,80 +84,225 @@ fn Java_java_lang_Class_forName0(env: &JniEnv) -> RuntimeResult<Option<JavaValue
     let name = env.get_string(match env.parameters[0].as_object().unwrap() {
         Some(id) => id,
         None => return Err(env.throw_exception("java/lang/NullPointerException", None)),
-    });
+    })?;
     let initialize = env.parameters[1].as_boolean().unwrap();
     let class_id = env.load_class(&name.replace(".", "/"), initialize)?;
     let class_object = env.get_class_object(class_id);
     Ok(Some(JavaValue::Object(Some(class_object))))
 }
 
-fn get_declared_methods(env: &JniEnv, constructors: bool) -> RuntimeResult<usize> {
-    let class_id = env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize();
-    let class_file = env.get_class_file(class_id);
-    let methods = &class_file.methods;
+fn get_this_class_id(env: &JniEnv) -> RuntimeResult<usize> {
+    env.get_numeric_metadata(env.get_current_instance()?, "class_id")
+}
+
+// array and primitive classes have no class file and declare no members
+fn get_declared_class_file<'a>(env: &'a JniEnv, class_id: usize) -> RuntimeResult<Option<&'a ClassFile>> {
+    let is_class_type = {
+        let heap = env.jvm.heap.borrow();
+        let class = &heap.loaded_classes[class_id];
+        !class.is_array_type && !class.is_primitive_type
+    };
+    match is_class_type {
+        true => Ok(Some(env.get_class_file(class_id)?)),
+        false => Ok(None),
+    }
+}
+
+fn get_type_class(env: &JniEnv, descriptor: &str) -> RuntimeResult<usize> {
+    let class_id = env.load_class(get_descriptor_class_name(descriptor), false)?;
+    Ok(env.get_class_object(class_id))
+}
+
+fn new_class_array<S: AsRef<str>>(env: &JniEnv, descriptors: &[S]) -> RuntimeResult<usize> {
+    let class_array =
+        env.new_array(JavaArrayType::Object(env.load_class("java/lang/Class", false)?), descriptors.len());
+    for (i, descriptor) in descriptors.iter().enumerate() {
+        let class_object = get_type_class(env, descriptor.as_ref())?;
+        env.set_array_element(class_array, i, JavaValue::Object(Some(class_object)))?;
+    }
+    Ok(class_array)
+}
+
+// attributes are only parsed when they are needed, so the constant indices they contain are checked here
+fn get_attribute_constant<'a>(env: &JniEnv, class_file: &'a ClassFile, index: u16) -> RuntimeResult<&'a String> {
+    find_constant_string(&class_file.const_pool, index)
+        .ok_or_else(|| invalid_attribute_constant(env, class_file, index))
+}
+
+fn invalid_attribute_constant(env: &JniEnv, class_file: &ClassFile, index: u16) -> JavaThrowable {
+    env.jvm.raise(VmError::MalformedClassFile {
+        class_name: get_constant_string(&class_file.const_pool, class_file.this_class).clone(),
+        message: format!("invalid constant index {} in attribute", index),
+    })
+}
+
+fn get_signature(env: &JniEnv, class_file: &ClassFile, attributes: &[AttributeInfo]) -> RuntimeResult<JavaValue> {
+    match get_attribute(&class_file.const_pool, attributes, "Signature")
+        .and_then(|attribute| signature_attribute_parser(&attribute.info).ok())
+    {
+        Some((_, signature)) => {
+            let signature = get_attribute_constant(env, class_file, signature.signature_index)?;
+            Ok(JavaValue::Object(Some(env.new_string(signature)?)))
+        }
+        None => Ok(JavaValue::Object(None)),
+    }
+}
+
+// the raw contents of an attribute as a Java byte array, null if the attribute is not present
+pub fn get_attribute_bytes(
+    env: &JniEnv,
+    const_pool: &[ConstantInfo],
+    attributes: &[AttributeInfo],
+    attribute_name: &str,
+) -> RuntimeResult<JavaValue> {
+    let attribute = match get_attribute(const_pool, attributes, attribute_name) {
+        Some(attribute) => attribute,
+        None => return Ok(JavaValue::Object(None)),
+    };
+
+    let bytes: Vec<i8> = attribute.info.iter().map(|byte| *byte as i8).collect();
+    let array = env.new_array(JavaArrayType::Byte, bytes.len());
+    env.set_byte_array_region(array, 0, &bytes)?;
+    Ok(JavaValue::Array(array))
+}
+
+fn get_inner_classes(class_file: &ClassFile) -> Vec<InnerClass> {
+    match get_attribute(&class_file.const_pool, &class_file.attributes, "InnerClasses")
+        .and_then(|attribute| inner_classes_attribute_parser(&attribute.info).ok())
+    {
+        Some((_, attribute)) => attribute.classes,
+        None => Vec::new(),
+    }
+}
 
+// reflected methods are numbered after the methods of all superclasses
+fn get_method_slot_offset(env: &JniEnv, class_id: usize) -> RuntimeResult<usize> {
     let mut starting_offset = 0usize;
     let mut superclass = env.get_superclass(class_id);
-    while superclass.is_some() {
-        let sc_id = superclass.unwrap();
-        starting_offset += env.get_class_file(sc_id).methods_count as usize;
+    while let Some(sc_id) = superclass {
+        starting_offset += env.get_class_file(sc_id)?.methods_count as usize;
+        superclass = env.get_superclass(sc_id);
+    }
+    Ok(starting_offset)
+}
 
+pub fn get_field_slot_offset(env: &JniEnv, class_id: usize) -> RuntimeResult<usize> {
+    let mut starting_offset = 0usize;
+    let mut superclass = env.get_superclass(class_id);
+    while let Some(sc_id) = superclass {
+        starting_offset += env.get_class_file(sc_id)?.fields_count as usize;
         superclass = env.get_superclass(sc_id);
     }
+    Ok(starting_offset)
+}
+
+pub fn get_reflected_field<'a>(
+    env: &'a JniEnv,
+    reflected: usize,
+) -> RuntimeResult<(usize, &'a ClassFile, &'a FieldInfo)> {
+    let class_object = env.get_field(reflected, "clazz")?.as_object().unwrap().unwrap();
+    let class_id = env.get_numeric_metadata(class_object, "class_id")?;
+    let slot = env.get_field(reflected, "slot")?.as_int().unwrap() as usize;
+
+    let class_file = env.get_class_file(class_id)?;
+    match slot.checked_sub(get_field_slot_offset(env, class_id)?).and_then(|index| class_file.fields.get(index)) {
+        Some(field) => Ok((class_id, class_file, field)),
+        None => Err(env.throw_exception("java/lang/InternalError", Some(&format!("invalid field slot {}", slot)))),
+    }
+}
+
+// resolves a java.lang.reflect.Method or Constructor to its declaring class and method
+pub fn get_reflected_method<'a>(
+    env: &'a JniEnv,
+    reflected: usize,
+) -> RuntimeResult<(usize, &'a ClassFile, &'a MethodInfo)> {
+    let class_object = env.get_field(reflected, "clazz")?.as_object().unwrap().unwrap();
+    let class_id = env.get_numeric_metadata(class_object, "class_id")?;
+    let slot = env.get_field(reflected, "slot")?.as_int().unwrap() as usize;
+
+    let class_file = env.get_class_file(class_id)?;
+    match slot.checked_sub(get_method_slot_offset(env, class_id)?).and_then(|index| class_file.methods.get(index)) {
+        Some(method) => Ok((class_id, class_file, method)),
+        None => Err(env.throw_exception("java/lang/InternalError", Some(&format!("invalid method slot {}", slot)))),
+    }
+}
+
+fn get_declared_methods(env: &JniEnv, constructors: bool) -> RuntimeResult<usize> {
+    let class_id = get_this_class_id(env)?;
+    let public_only = env.parameters[1].as_boolean().unwrap();
 
     let method_class_type = match constructors {
         true => "java/lang/reflect/Constructor",
         false => "java/lang/reflect/Method",
     };
     let method_type_id = env.load_class(method_class_type, false)?;
-    let result_array = env.new_array(
-        JavaArrayType::Object(method_type_id),
-        methods
-            .iter()
-            .filter(|method| match constructors {
-                true => get_constant_string(&class_file.const_pool, method.name_index) == "<init>",
-                false => get_constant_string(&class_file.const_pool, method.name_index) != "<clinit>",
-            })
-            .count(),
-    );
-    for (i, method) in methods.iter().enumerate() {
+    let class_file = match get_declared_class_file(env, class_id)? {
+        Some(class_file) => class_file,
+        None => return Ok(env.new_array(JavaArrayType::Object(method_type_id), 0)),
+    };
+    let starting_offset = get_method_slot_offset(env, class_id)?;
+
+    let mut reflected_methods = Vec::new();
+    for (i, method) in class_file.methods.iter().enumerate() {
         let method_name = get_constant_string(&class_file.const_pool, method.name_index);
-        if constructors && method_name != "<init>" {
+        if (method_name == "<init>") != constructors || method_name == "<clinit>" {
             continue;
         }
-        if !constructors && (method_name == "<init>" || method_name == "<clinit>") {
+        if public_only && !method.access_flags.contains(MethodAccessFlags::PUBLIC) {
             continue;
         }
+
         let reflected_method = env.new_instance(method_type_id)?;
-        let method_name_interned = env.new_interned_string(method_name);
-        env.set_field(reflected_method, "clazz", JavaValue::Object(Some(env.get_current_instance()?)));
-        env.set_field(reflected_method, "slot", JavaValue::Int(starting_offset as i32 + i as i32));
+        env.set_field(reflected_method, "clazz", JavaValue::Object(Some(env.get_current_instance()?)))?;
+        env.set_field(reflected_method, "slot", JavaValue::Int(starting_offset as i32 + i as i32))?;
         if !constructors {
-            env.set_field(reflected_method, "name", JavaValue::Object(Some(method_name_interned)));
+            let method_name_interned = env.new_interned_string(method_name)?;
+            env.set_field(reflected_method, "name", JavaValue::Object(Some(method_name_interned)))?;
         }
-        env.set_field(reflected_method, "modifiers", JavaValue::Int(method.access_flags.bits() as i32));
+        env.set_field(reflected_method, "modifiers", JavaValue::Int(method.access_flags.bits() as i32))?;
 
         let signature = get_constant_string(&class_file.const_pool, method.descriptor_index);
         let descriptor = MethodDescriptor::new(signature).unwrap();
 
-        let parameter_types = env.new_array(
-            JavaArrayType::Object(env.load_class("java/lang/Class", false)?),
-            descriptor.argument_types.len(),
-        );
-        for i in 0..descriptor.argument_types.len() {
-            let param_type_id = env.load_class(&descriptor.argument_types[i], false)?;
-            let param_type_class = env.get_class_object(param_type_id);
-            env.set_array_element(parameter_types, i, JavaValue::Object(Some(param_type_class)));
+        let parameter_types = new_class_array(env, &descriptor.argument_types)?;
+        env.set_field(reflected_method, "parameterTypes", JavaValue::Array(parameter_types))?;
+
+        if !constructors {
+            let return_type_class = get_type_class(env, &descriptor.return_type)?;
+            env.set_field(reflected_method, "returnType", JavaValue::Object(Some(return_type_class)))?;
         }
-        env.set_field(reflected_method, "parameterTypes", JavaValue::Array(parameter_types));
 
+        let exception_types: Vec<&str> = match get_attribute(&class_file.const_pool, &method.attributes, "Exceptions")
+            .and_then(|attribute| exceptions_attribute_parser(&attribute.info).ok())
+        {
+            Some((_, attribute)) => attribute
+                .exception_table
+                .iter()
+                .map(|index| Ok(get_attribute_constant(env, class_file, *index)?.as_str()))
+                .collect::<RuntimeResult<_>>()?,
+            None => Vec::new(),
+        };
+        let exception_types = new_class_array(env, &exception_types)?;
+        env.set_field(reflected_method, "exceptionTypes", JavaValue::Array(exception_types))?;
+        env.set_field(reflected_method, "signature", get_signature(env, class_file, &method.attributes)?)?;
+
+        let const_pool = &class_file.const_pool;
+        let annotations = get_attribute_bytes(env, const_pool, &method.attributes, "RuntimeVisibleAnnotations")?;
+        env.set_field(reflected_method, "annotations", annotations)?;
+        let parameter_annotations =
+            get_attribute_bytes(env, const_pool, &method.attributes, "RuntimeVisibleParameterAnnotations")?;
+        env.set_field(reflected_method, "parameterAnnotations", parameter_annotations)?;
         if !constructors {
-            let return_type_id = env.load_class(&descriptor.return_type, false)?;
-            let return_type_class = env.get_class_object(return_type_id);
-            env.set_field(reflected_method, "returnType", JavaValue::Object(Some(return_type_class)));
+            let annotation_default = get_attribute_bytes(env, const_pool, &method.attributes, "AnnotationDefault")?;
+            env.set_field(reflected_method, "annotationDefault", annotation_default)?;
         }
 
-        env.set_array_element(result_array, i, JavaValue::Object(Some(reflected_method)));
+        reflected_methods.push(reflected_method);
+    }
+
+    let result_array = env.new_array(JavaArrayType::Object(method_type_id), reflected_methods.len());
+    for (i, reflected_method) in reflected_methods.into_iter().enumerate() {
+        env.set_array_element(result_array, i, JavaValue::Object(Some(reflected_method)))?;
     }
 
     Ok(result_array)
@@ -159,52 +314,195 @@ fn Java_java_lang_Class_getDeclaredConstructors0(env: &JniEnv) -> RuntimeResult<
     Ok(Some(JavaValue::Array(constructors)))
 }
 
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getDeclaredMethods0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let methods = get_declared_methods(env, false)?;
+    Ok(Some(JavaValue::Array(methods)))
+}
+
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getDeclaredFields0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_id = env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize();
-    let class_file = env.get_class_file(class_id);
-    let fields = &class_file.fields;
+    let class_id = get_this_class_id(env)?;
+    let public_only = env.parameters[1].as_boolean().unwrap();
 
-    let mut starting_offset = 0usize;
-    let mut superclass = env.get_superclass(class_id);
-    while superclass.is_some() {
-        let sc_id = superclass.unwrap();
-        starting_offset += env.get_class_file(sc_id).fields_count as usize;
+    let field_type_id = env.load_class("java/lang/reflect/Field", false)?;
+    let class_file = match get_declared_class_file(env, class_id)? {
+        Some(class_file) => class_file,
+        None => return Ok(Some(JavaValue::Array(env.new_array(JavaArrayType::Object(field_type_id), 0)))),
+    };
 
-        superclass = env.get_superclass(sc_id);
-    }
+    let starting_offset = get_field_slot_offset(env, class_id)?;
+
+    let mut reflected_fields = Vec::new();
+    for (i, field) in class_file.fields.iter().enumerate() {
+        if public_only && !field.access_flags.contains(FieldAccessFlags::PUBLIC) {
+            continue;
+        }
 
-    let field_type_id = env.load_class("java/lang/reflect/Field", false)?;
-    let result_array = env.new_array(JavaArrayType::Object(field_type_id), fields.len());
-    for (i, field) in fields.iter().enumerate() {
         let reflected_field = env.new_instance(field_type_id)?;
-        let field_name = env.new_interned_string(get_constant_string(&class_file.const_pool, field.name_index));
-        env.set_field(reflected_field, "clazz", JavaValue::Object(Some(env.get_current_instance()?)));
-        env.set_field(reflected_field, "slot", JavaValue::Int(starting_offset as i32 + i as i32));
-        env.set_field(reflected_field, "name", JavaValue::Object(Some(field_name)));
-
-        let mut field_type_name = get_constant_string(&class_file.const_pool, field.descriptor_index).as_str();
-        if field_type_name.starts_with('L') && field_type_name.ends_with(';') {
-            field_type_name = &field_type_name[1..field_type_name.len() - 1];
+        let field_name = env.new_interned_string(get_constant_string(&class_file.const_pool, field.name_index))?;
+        env.set_field(reflected_field, "clazz", JavaValue::Object(Some(env.get_current_instance()?)))?;
+        env.set_field(reflected_field, "slot", JavaValue::Int(starting_offset as i32 + i as i32))?;
+        env.set_field(reflected_field, "name", JavaValue::Object(Some(field_name)))?;
+
+        let field_type_class =
+            get_type_class(env, get_constant_string(&class_file.const_pool, field.descriptor_index))?;
+        env.set_field(reflected_field, "type", JavaValue::Object(Some(field_type_class)))?;
+        env.set_field(reflected_field, "modifiers", JavaValue::Int(field.access_flags.bits() as i32))?;
+        env.set_field(reflected_field, "signature", get_signature(env, class_file, &field.attributes)?)?;
+        let annotations =
+            get_attribute_bytes(env, &class_file.const_pool, &field.attributes, "RuntimeVisibleAnnotations")?;
+        env.set_field(reflected_field, "annotations", annotations)?;
+
+        reflected_fields.push(reflected_field);
+    }
+
+    let result_array = env.new_array(JavaArrayType::Object(field_type_id), reflected_fields.len());
+    for (i, reflected_field) in reflected_fields.into_iter().enumerate() {
+        env.set_array_element(result_array, i, JavaValue::Object(Some(reflected_field)))?;
+    }
+
+    Ok(Some(JavaValue::Array(result_array)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getDeclaredClasses0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    let mut declared_classes = Vec::new();
+    if let Some(class_file) = get_declared_class_file(env, class_id)? {
+        let class_name = get_constant_string(&class_file.const_pool, class_file.this_class);
+        for inner_class in get_inner_classes(class_file) {
+            if inner_class.outer_class_info_index != 0
+                && get_attribute_constant(env, class_file, inner_class.outer_class_info_index)? == class_name
+            {
+                declared_classes.push(get_attribute_constant(env, class_file, inner_class.inner_class_info_index)?);
+            }
+        }
+    }
+
+    Ok(Some(JavaValue::Array(new_class_array(env, &declared_classes)?)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getDeclaringClass0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    let class_file = match get_declared_class_file(env, class_id)? {
+        Some(class_file) => class_file,
+        None => return Ok(Some(JavaValue::Object(None))),
+    };
+
+    let class_name = get_constant_string(&class_file.const_pool, class_file.this_class);
+    for inner_class in get_inner_classes(class_file) {
+        if inner_class.outer_class_info_index != 0
+            && get_attribute_constant(env, class_file, inner_class.inner_class_info_index)? == class_name
+        {
+            let outer_class_name = get_attribute_constant(env, class_file, inner_class.outer_class_info_index)?;
+            return Ok(Some(JavaValue::Object(Some(get_type_class(env, outer_class_name)?))));
         }
+    }
 
-        let field_type_id = env.load_class(field_type_name, false)?;
-        let field_type_class = env.get_class_object(field_type_id);
-        env.set_field(reflected_field, "type", JavaValue::Object(Some(field_type_class)));
-        env.set_field(reflected_field, "modifiers", JavaValue::Int(field.access_flags.bits() as i32));
+    Ok(Some(JavaValue::Object(None)))
+}
+
+// returns the enclosing class, method name and method descriptor of a local or anonymous class
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getEnclosingMethod0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    let class_file = match get_declared_class_file(env, class_id)? {
+        Some(class_file) => class_file,
+        None => return Ok(Some(JavaValue::Object(None))),
+    };
+    let enclosing_method = match get_attribute(&class_file.const_pool, &class_file.attributes, "EnclosingMethod")
+        .and_then(|attribute| enclosing_method_attribute_parser(&attribute.info).ok())
+    {
+        Some((_, attribute)) => attribute,
+        None => return Ok(Some(JavaValue::Object(None))),
+    };
 
-        env.set_array_element(result_array, i, JavaValue::Object(Some(reflected_field)));
+    let result_array = env.new_array(JavaArrayType::Object(env.load_class("java/lang/Object", false)?), 3);
+    let enclosing_class = get_type_class(env, get_attribute_constant(env, class_file, enclosing_method.class_index)?)?;
+    env.set_array_element(result_array, 0, JavaValue::Object(Some(enclosing_class)))?;
+    if enclosing_method.method_index != 0 {
+        let (method_name, method_descriptor) =
+            find_constant_name_and_type(&class_file.const_pool, enclosing_method.method_index)
+                .ok_or_else(|| invalid_attribute_constant(env, class_file, enclosing_method.method_index))?;
+        env.set_array_element(result_array, 1, JavaValue::Object(Some(env.new_interned_string(method_name)?)))?;
+        env.set_array_element(result_array, 2, JavaValue::Object(Some(env.new_string(method_descriptor)?)))?;
     }
 
     Ok(Some(JavaValue::Array(result_array)))
 }
 
 #[allow(non_snake_case)]
-fn Java_java_lang_Class_isPrimitive(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_name = env.get_internal_metadata(env.get_current_instance()?, "class_name").unwrap().into_string();
+fn Java_java_lang_Class_getInterfaces0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    let interfaces = {
+        let heap = env.jvm.heap.borrow();
+        heap.loaded_classes[class_id].direct_interfaces.clone()
+    };
+    Ok(Some(JavaValue::Array(new_class_array(env, &interfaces)?)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getGenericSignature0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    match get_declared_class_file(env, class_id)? {
+        Some(class_file) => Ok(Some(get_signature(env, class_file, &class_file.attributes)?)),
+        None => Ok(Some(JavaValue::Object(None))),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getRawAnnotations(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    match get_declared_class_file(env, class_id)? {
+        Some(class_file) => Ok(Some(get_attribute_bytes(
+            env,
+            &class_file.const_pool,
+            &class_file.attributes,
+            "RuntimeVisibleAnnotations",
+        )?)),
+        None => Ok(Some(JavaValue::Object(None))),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getRawTypeAnnotations(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    match get_declared_class_file(env, class_id)? {
+        Some(class_file) => Ok(Some(get_attribute_bytes(
+            env,
+            &class_file.const_pool,
+            &class_file.attributes,
+            "RuntimeVisibleTypeAnnotations",
+        )?)),
+        None => Ok(Some(JavaValue::Object(None))),
+    }
+}
+
+// the constant pool of a class is identified by its class object
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_getConstantPool(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let constant_pool_class_id = env.get_class_id("sun/reflect/ConstantPool")?;
+    let constant_pool = env.new_instance(constant_pool_class_id)?;
+    env.set_field(constant_pool, "constantPoolOop", JavaValue::Object(Some(env.get_current_instance()?)))?;
+    Ok(Some(JavaValue::Object(Some(constant_pool))))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_isInstance(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_name = env.get_text_metadata(env.get_current_instance()?, "class_name")?;
+    let is_instance = env.jvm.is_instance_of(&env.parameters[1], &class_name, false)?;
+    Ok(Some(JavaValue::Boolean(is_instance)))
+}
 
-    let is_primitive =
-        matches!(class_name.chars().next().unwrap(), 'B' | 'S' | 'I' | 'J' | 'F' | 'D' | 'Z' | 'C' | 'V');
+#[allow(non_snake_case)]
+fn Java_java_lang_Class_isPrimitive(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_this_class_id(env)?;
+    let is_primitive = {
+        let heap = env.jvm.heap.borrow();
+        heap.loaded_classes[class_id].is_primitive_type
+    };
     Ok(Some(JavaValue::Boolean(is_primitive)))
 }
 
@@ -213,8 +511,8 @@ fn Java_java_lang_Class_isAssignableFrom(env: &JniEnv) -> RuntimeResult<Option<J
     let this_class = env.get_current_instance()?;
     let compare_class = env.parameters[1].as_object().unwrap().unwrap();
 
-    let this_class_name = env.get_internal_metadata(this_class, "class_name").unwrap().into_string();
-    let compare_class_id = env.get_internal_metadata(compare_class, "class_id").unwrap().into_usize();
+    let this_class_name = env.get_text_metadata(this_class, "class_name")?;
+    let compare_class_id = env.get_numeric_metadata(compare_class, "class_id")?;
     let is_assignable_from = env.jvm.is_assignable_from(&this_class_name, compare_class_id)?;
 
     Ok(Some(JavaValue::Boolean(is_assignable_from)))
@@ -222,21 +520,21 @@ fn Java_java_lang_Class_isAssignableFrom(env: &JniEnv) -> RuntimeResult<Option<J
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_isInterface(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_id = env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize();
-    let class_file = env.get_class_file(class_id);
+    let class_id = env.get_numeric_metadata(env.get_current_instance()?, "class_id")?;
+    let class_file = env.get_class_file(class_id)?;
     Ok(Some(JavaValue::Boolean(class_file.access_flags.contains(ClassAccessFlags::INTERFACE))))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getModifiers(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_id = env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize();
-    let class_file = env.get_class_file(class_id);
+    let class_id = env.get_numeric_metadata(env.get_current_instance()?, "class_id")?;
+    let class_file = env.get_class_file(class_id)?;
     Ok(Some(JavaValue::Int(class_file.access_flags.bits() as i32)))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Class_getSuperclass(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_id = env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize();
+    let class_id = env.get_numeric_metadata(env.get_current_instance()?, "class_id")?;
 
     let heap = env.jvm.heap.borrow();
     let id = match heap.loaded_classes[class_id].superclass_id {
@@ -259,7 +557,17 @@ pub fn initialize(cp: &mut Classpath) {
         Java_java_lang_Class_getPrimitiveClass,
         Java_java_lang_Class_forName0,
         Java_java_lang_Class_getDeclaredConstructors0,
+        Java_java_lang_Class_getDeclaredMethods0,
         Java_java_lang_Class_getDeclaredFields0,
+        Java_java_lang_Class_getDeclaredClasses0,
+        Java_java_lang_Class_getDeclaringClass0,
+        Java_java_lang_Class_getEnclosingMethod0,
+        Java_java_lang_Class_getInterfaces0,
+        Java_java_lang_Class_getGenericSignature0,
+        Java_java_lang_Class_getRawAnnotations,
+        Java_java_lang_Class_getRawTypeAnnotations,
+        Java_java_lang_Class_getConstantPool,
+        Java_java_lang_Class_isInstance,
         Java_java_lang_Class_isPrimitive,
         Java_java_lang_Class_isAssignableFrom,
         Java_java_lang_Class_isInterface,
@@ -3,6 +3,11 @@ use crate::{
     Classpath, JniEnv,
 };
 
+fn find_resources(env: &JniEnv, method_name: &str, method_descriptor: &str) -> RuntimeResult<Option<JavaValue>> {
+    let resources_class = env.get_class_id("webjvm/lang/Resources")?;
+    env.invoke_static_method(resources_class, method_name, method_descriptor, &[env.parameters[0].clone()])
+}
+
 #[allow(non_snake_case)]
 fn Java_java_lang_ClassLoader_registerNatives(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     Ok(None)
@@ -15,7 +20,7 @@ fn Java_java_lang_ClassLoader_findBuiltinLib(env: &JniEnv) -> RuntimeResult<Opti
 
 #[allow(non_snake_case)]
 fn Java_java_lang_ClassLoader_findLoadedClass0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let class_name = env.get_string(env.parameters[1].as_object().unwrap().unwrap());
+    let class_name = env.get_string(env.parameters[1].as_object().unwrap().unwrap())?;
     let heap = env.jvm.heap.borrow();
     if let Some(looked_up_id) = heap.loaded_classes_lookup.get(&class_name) {
         Ok(Some(JavaValue::Object(Some(env.get_class_object(*looked_up_id)))))
@@ -32,18 +37,57 @@ fn Java_java_lang_ClassLoader_findBootstrapClass(env: &JniEnv) -> RuntimeResult<
 #[allow(non_snake_case)]
 fn Java_java_lang_ClassLoader_00024NativeLibrary_load(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let instance = env.get_current_instance()?;
-    env.set_field(instance, "loaded", JavaValue::Boolean(true));
+    env.set_field(instance, "loaded", JavaValue::Boolean(true))?;
 
     Ok(None)
 }
 
+// every class is defined by the bootstrap loader, so both system and bootstrap resource lookups
+// are served from the resources held in the classpath
+#[allow(non_snake_case)]
+fn Java_java_lang_ClassLoader_getSystemResource(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    find_resources(env, "getResource", "(Ljava/lang/String;)Ljava/net/URL;")
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_ClassLoader_getSystemResources(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    find_resources(env, "getResources", "(Ljava/lang/String;)Ljava/util/Enumeration;")
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_ClassLoader_getBootstrapResource(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    find_resources(env, "getResource", "(Ljava/lang/String;)Ljava/net/URL;")
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_ClassLoader_getBootstrapResources(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    find_resources(env, "getResources", "(Ljava/lang/String;)Ljava/util/Enumeration;")
+}
+
 pub fn initialize(cp: &mut Classpath) {
+    cp.add_native_override("java/lang/ClassLoader", "getSystemResource", "(Ljava/lang/String;)Ljava/net/URL;");
+    cp.add_native_override(
+        "java/lang/ClassLoader",
+        "getSystemResources",
+        "(Ljava/lang/String;)Ljava/util/Enumeration;",
+    );
+    cp.add_native_override("java/lang/ClassLoader", "getBootstrapResource", "(Ljava/lang/String;)Ljava/net/URL;");
+    cp.add_native_override(
+        "java/lang/ClassLoader",
+        "getBootstrapResources",
+        "(Ljava/lang/String;)Ljava/util/Enumeration;",
+    );
+
     register_jni!(
         cp,
         Java_java_lang_ClassLoader_registerNatives,
         Java_java_lang_ClassLoader_findBuiltinLib,
         Java_java_lang_ClassLoader_findLoadedClass0,
         Java_java_lang_ClassLoader_findBootstrapClass,
-        Java_java_lang_ClassLoader_00024NativeLibrary_load
+        Java_java_lang_ClassLoader_00024NativeLibrary_load,
+        Java_java_lang_ClassLoader_getSystemResource,
+        Java_java_lang_ClassLoader_getSystemResources,
+        Java_java_lang_ClassLoader_getBootstrapResource,
+        Java_java_lang_ClassLoader_getBootstrapResources
     );
 }
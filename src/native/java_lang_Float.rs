@@ -9,6 +9,12 @@ fn Java_java_lang_Float_floatToRawIntBits(env: &JniEnv) -> RuntimeResult<Option<
     Ok(Some(JavaValue::Int(float.to_bits() as i32)))
 }
 
+#[allow(non_snake_case)]
+fn Java_java_lang_Float_intBitsToFloat(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let int = env.parameters[0].as_int().unwrap();
+    Ok(Some(JavaValue::Float(f32::from_bits(int as u32))))
+}
+
 pub fn initialize(cp: &mut Classpath) {
-    register_jni!(cp, Java_java_lang_Float_floatToRawIntBits);
+    register_jni!(cp, Java_java_lang_Float_floatToRawIntBits, Java_java_lang_Float_intBitsToFloat);
 }
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+
 use crate::{
-    model::{JavaArray, JavaArrayType, JavaValue, RuntimeResult},
+    exec::thread::{PendingWait, ThreadState},
+    model::{Heap, JavaArray, JavaArrayType, JavaObject, JavaValue, RuntimeResult},
     Classpath, JniEnv,
 };
 
@@ -10,70 +13,183 @@ fn Java_java_lang_Object_registerNatives(_: &JniEnv) -> RuntimeResult<Option<Jav
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Object_hashCode(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    env.invoke_static_method(
-        env.get_class_id("java/lang/System")?,
-        "identityHashCode",
-        "(Ljava/lang/Object;)I",
-        &[JavaValue::Object(Some(env.get_current_instance()?))],
-    )
+    let id = match env.parameters[0] {
+        JavaValue::Array(id) => id,
+        _ => env.get_current_instance()?,
+    };
+    Ok(Some(JavaValue::Int(env.jvm.get_identity_hash(id)?)))
+}
+
+// the class name of an array type, which is its descriptor
+fn get_array_type_name(heap: &Heap, array_type: &JavaArrayType) -> String {
+    let component_type = match array_type {
+        JavaArrayType::Byte => String::from("B"),
+        JavaArrayType::Short => String::from("S"),
+        JavaArrayType::Int => String::from("I"),
+        JavaArrayType::Long => String::from("J"),
+        JavaArrayType::Float => String::from("F"),
+        JavaArrayType::Double => String::from("D"),
+        JavaArrayType::Char => String::from("C"),
+        JavaArrayType::Boolean => String::from("Z"),
+        JavaArrayType::Object(class_id) => {
+            let class = &heap.loaded_classes[*class_id];
+            match class.is_array_type {
+                true => class.java_type.clone(),
+                false => format!("L{};", class.java_type),
+            }
+        }
+        JavaArrayType::Array(component_type) => get_array_type_name(heap, component_type),
+    };
+    format!("[{}", component_type)
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Object_getClass(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    if env.parameters[0].is_array() {
-        let array_type = {
+    if let JavaValue::Array(id) = env.parameters[0] {
+        let type_name = {
             let heap = env.jvm.heap.borrow();
-            heap.array_heap_map[&env.parameters[0].as_array().unwrap()].array_type.clone()
-        };
-        let type_name = match array_type {
-            JavaArrayType::Byte => String::from("[B"),
-            JavaArrayType::Short => String::from("[S"),
-            JavaArrayType::Int => String::from("[I"),
-            JavaArrayType::Long => String::from("[J"),
-            JavaArrayType::Float => String::from("[F"),
-            JavaArrayType::Double => String::from("[D"),
-            JavaArrayType::Char => String::from("[C"),
-            JavaArrayType::Boolean => String::from("[Z"),
-            JavaArrayType::Object(obj_type) => {
-                let heap = env.jvm.heap.borrow();
-                format!("[L{};", heap.loaded_classes[obj_type].java_type)
-            }
-            JavaArrayType::Array(_) => unimplemented!("getClass() on multidimensional arrays not yet implemented"),
+            get_array_type_name(&heap, &heap.array_heap_map[&id].array_type)
         };
         let type_obj = env.get_class_object(env.get_class_id(&type_name)?);
         return Ok(Some(JavaValue::Object(Some(type_obj))));
     }
-    let type_name = env.get_object_type_name(env.get_current_instance()?);
+    let type_name = env.get_object_type_name(env.get_current_instance()?)?;
     let type_obj = env.get_class_object(env.get_class_id(&type_name)?);
 
     Ok(Some(JavaValue::Object(Some(type_obj))))
 }
 
+// clones are shallow copies, the identity hash and internal metadata belong to the original only
 #[allow(non_snake_case)]
 fn Java_java_lang_Object_clone(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    match &env.parameters[0] {
+    match env.parameters[0] {
         JavaValue::Array(id) => {
             let new_array = {
                 let heap = env.jvm.heap.borrow();
-                let old_array = heap.array_heap_map.get(id).unwrap();
+                let old_array = match heap.array_heap_map.get(&id) {
+                    Some(array) => array,
+                    None => {
+                        drop(heap);
+                        return Err(env.jvm.invalid_reference(id));
+                    }
+                };
                 JavaArray {
                     array_type: old_array.array_type.clone(),
                     values: old_array.values.clone(),
+                    identity_hash: None,
                 }
             };
             let array_id = env.jvm.heap_store_array(new_array);
             Ok(Some(JavaValue::Array(array_id)))
         }
-        JavaValue::Object(_) => {
-            // TODO
-            panic!("Java_java_lang_Object_clone");
+        _ => {
+            let id = env.get_current_instance()?;
+            if !env.jvm.is_instance_of(&env.parameters[0], "java/lang/Cloneable", false)? {
+                let class_name = env.get_object_type_name(id)?.replace('/', ".");
+                return Err(env.throw_exception("java/lang/CloneNotSupportedException", Some(&class_name)));
+            }
+
+            let new_instance = {
+                let heap = env.jvm.heap.borrow();
+                let old_instance = &heap.object_heap_map[&id];
+                JavaObject {
+                    class_id: old_instance.class_id,
+                    internal_metadata: HashMap::new(),
+                    instance_fields: old_instance.instance_fields.clone(),
+                    has_hidden_fields: old_instance.has_hidden_fields,
+                    identity_hash: None,
+                }
+            };
+            let instance_id = env.jvm.heap_store_instance(new_instance);
+            Ok(Some(JavaValue::Object(Some(instance_id))))
         }
-        _ => panic!(),
     }
 }
 
+// arrays are objects too, so they have a monitor of their own
+fn get_monitor_object(env: &JniEnv) -> RuntimeResult<usize> {
+    match env.parameters[0] {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => Ok(id),
+        _ => Err(env.throw_exception("java/lang/NullPointerException", None)),
+    }
+}
+
+fn notify(env: &JniEnv, all: bool) -> RuntimeResult<Option<JavaValue>> {
+    let object = get_monitor_object(env)?;
+    let mut threads = env.jvm.threads.borrow_mut();
+    if !threads.holds_monitor(object) {
+        drop(threads);
+        return Err(env.throw_exception("java/lang/IllegalMonitorStateException", None));
+    }
+    threads.notify(object, all);
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Object_notify(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    notify(env, false)
+}
+
 #[allow(non_snake_case)]
-fn Java_java_lang_Object_notifyAll(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+fn Java_java_lang_Object_notifyAll(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    notify(env, true)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Object_wait(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let object = get_monitor_object(env)?;
+    let timeout = env.parameters[1].as_long().unwrap();
+    if timeout < 0 {
+        return Err(env.throw_exception("java/lang/IllegalArgumentException", Some("timeout value is negative")));
+    }
+
+    let mut threads = env.jvm.threads.borrow_mut();
+    match threads.get_current_thread_mut().pending_wait.take() {
+        Some(pending_wait) => {
+            // woken up, so the monitor has to be reacquired before returning
+            if !threads.restore_monitor(pending_wait.object, pending_wait.entry_count) {
+                let thread = threads.get_current_thread_mut();
+                thread.pending_wait = Some(pending_wait);
+                thread.state = ThreadState::Blocked(pending_wait.object);
+                return Ok(None);
+            }
+        }
+        None => {
+            // the monitor has to be owned even if the thread is already interrupted
+            if !threads.holds_monitor(object) {
+                drop(threads);
+                return Err(env.throw_exception("java/lang/IllegalMonitorStateException", None));
+            }
+            if !threads.get_current_thread().interrupted {
+                let entry_count = match threads.release_monitor_fully(object) {
+                    Some(entry_count) => entry_count,
+                    None => {
+                        drop(threads);
+                        return Err(env.throw_exception("java/lang/IllegalMonitorStateException", None));
+                    }
+                };
+                let thread = threads.get_current_thread_mut();
+                thread.pending_wait = Some(PendingWait {
+                    object,
+                    entry_count,
+                });
+                thread.state = ThreadState::Waiting {
+                    object,
+                    deadline: match timeout {
+                        0 => None,
+                        timeout => Some((env.jvm.clock.borrow().current_time_millis() + timeout) as u64),
+                    },
+                };
+                return Ok(None);
+            }
+        }
+    }
+
+    let thread = threads.get_current_thread_mut();
+    if std::mem::replace(&mut thread.interrupted, false) {
+        drop(threads);
+        return Err(env.throw_exception("java/lang/InterruptedException", None));
+    }
     Ok(None)
 }
 
@@ -84,6 +200,8 @@ pub fn initialize(cp: &mut Classpath) {
         Java_java_lang_Object_hashCode,
         Java_java_lang_Object_getClass,
         Java_java_lang_Object_clone,
-        Java_java_lang_Object_notifyAll
+        Java_java_lang_Object_notify,
+        Java_java_lang_Object_notifyAll,
+        Java_java_lang_Object_wait
     );
 }
@@ -9,6 +9,27 @@ fn Java_java_lang_Runtime_availableProcessors(_: &JniEnv) -> RuntimeResult<Optio
     Ok(Some(JavaValue::Int(1)))
 }
 
+#[allow(non_snake_case)]
+fn Java_java_lang_Runtime_gc(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    env.jvm.request_garbage_collection();
+    Ok(None)
+}
+
+// runs the finalizers of objects that have already been found unreachable on a secondary finalizer thread
+#[allow(non_snake_case)]
+fn Java_java_lang_Runtime_runFinalization0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    if env.jvm.classpath.get_classpath_entry("java/lang/ref/Finalizer").is_some() {
+        let finalizer_class_id = env.get_class_id("java/lang/ref/Finalizer")?;
+        env.invoke_static_method(finalizer_class_id, "runFinalization", "()V", &[])?;
+    }
+    Ok(None)
+}
+
 pub fn initialize(cp: &mut Classpath) {
-    register_jni!(cp, Java_java_lang_Runtime_availableProcessors);
+    register_jni!(
+        cp,
+        Java_java_lang_Runtime_availableProcessors,
+        Java_java_lang_Runtime_gc,
+        Java_java_lang_Runtime_runFinalization0
+    );
 }
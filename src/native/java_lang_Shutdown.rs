@@ -0,0 +1,29 @@
+use crate::{
+    exec::env::JniEnv,
+    model::{JavaThrowable, JavaValue, RuntimeResult},
+    Classpath,
+};
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Shutdown_beforeHalt(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Shutdown_halt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Err(JavaThrowable::Exit(env.parameters[0].as_int().unwrap()))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Shutdown_runAllFinalizers(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(None)
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(
+        cp,
+        Java_java_lang_Shutdown_beforeHalt,
+        Java_java_lang_Shutdown_halt0,
+        Java_java_lang_Shutdown_runAllFinalizers
+    );
+}
@@ -0,0 +1,75 @@
+use crate::{
+    fdlibm,
+    model::{JavaValue, RuntimeResult},
+    Classpath, JniEnv,
+};
+
+macro_rules! define_unary {
+    ( $fname:ident, $function:ident ) => {
+        #[allow(non_snake_case)]
+        fn $fname(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+            let a = env.parameters[0].as_double().unwrap();
+            Ok(Some(JavaValue::Double(fdlibm::$function(a))))
+        }
+    };
+}
+
+// doubles take two slots, so the second argument starts at index 2
+macro_rules! define_binary {
+    ( $fname:ident, $function:ident ) => {
+        #[allow(non_snake_case)]
+        fn $fname(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+            let a = env.parameters[0].as_double().unwrap();
+            let b = env.parameters[2].as_double().unwrap();
+            Ok(Some(JavaValue::Double(fdlibm::$function(a, b))))
+        }
+    };
+}
+
+define_unary!(Java_java_lang_StrictMath_sin, sin);
+define_unary!(Java_java_lang_StrictMath_cos, cos);
+define_unary!(Java_java_lang_StrictMath_tan, tan);
+define_unary!(Java_java_lang_StrictMath_asin, asin);
+define_unary!(Java_java_lang_StrictMath_acos, acos);
+define_unary!(Java_java_lang_StrictMath_atan, atan);
+define_unary!(Java_java_lang_StrictMath_exp, exp);
+define_unary!(Java_java_lang_StrictMath_log, log);
+define_unary!(Java_java_lang_StrictMath_log10, log10);
+define_unary!(Java_java_lang_StrictMath_sqrt, sqrt);
+define_unary!(Java_java_lang_StrictMath_cbrt, cbrt);
+define_unary!(Java_java_lang_StrictMath_sinh, sinh);
+define_unary!(Java_java_lang_StrictMath_cosh, cosh);
+define_unary!(Java_java_lang_StrictMath_tanh, tanh);
+define_unary!(Java_java_lang_StrictMath_expm1, expm1);
+define_unary!(Java_java_lang_StrictMath_log1p, log1p);
+
+define_binary!(Java_java_lang_StrictMath_IEEEremainder, remainder);
+define_binary!(Java_java_lang_StrictMath_atan2, atan2);
+define_binary!(Java_java_lang_StrictMath_pow, pow);
+define_binary!(Java_java_lang_StrictMath_hypot, hypot);
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(
+        cp,
+        Java_java_lang_StrictMath_sin,
+        Java_java_lang_StrictMath_cos,
+        Java_java_lang_StrictMath_tan,
+        Java_java_lang_StrictMath_asin,
+        Java_java_lang_StrictMath_acos,
+        Java_java_lang_StrictMath_atan,
+        Java_java_lang_StrictMath_exp,
+        Java_java_lang_StrictMath_log,
+        Java_java_lang_StrictMath_log10,
+        Java_java_lang_StrictMath_sqrt,
+        Java_java_lang_StrictMath_cbrt,
+        Java_java_lang_StrictMath_sinh,
+        Java_java_lang_StrictMath_cosh,
+        Java_java_lang_StrictMath_tanh,
+        Java_java_lang_StrictMath_expm1,
+        Java_java_lang_StrictMath_log1p,
+        Java_java_lang_StrictMath_IEEEremainder,
+        Java_java_lang_StrictMath_atan2,
+        Java_java_lang_StrictMath_pow,
+        Java_java_lang_StrictMath_hypot
+    );
+}
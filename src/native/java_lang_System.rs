@@ -9,20 +9,13 @@ fn Java_java_lang_System_registerNatives(_: &JniEnv) -> RuntimeResult<Option<Jav
 }
 
 #[allow(non_snake_case)]
-fn Java_java_lang_System_currentTimeMillis(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    #[cfg(target_arch = "wasm32")]
-    return Ok(Some(JavaValue::Long(js_sys::Date::now() as i64)));
-
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        Ok(Some(JavaValue::Long(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64)))
-    }
+fn Java_java_lang_System_currentTimeMillis(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Long(env.jvm.clock.borrow().current_time_millis())))
 }
 
 #[allow(non_snake_case)]
-fn Java_java_lang_System_nanoTime(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    Ok(Some(JavaValue::Long(0)))
+fn Java_java_lang_System_nanoTime(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Long(env.jvm.clock.borrow().nano_time())))
 }
 
 #[allow(non_snake_case)]
@@ -34,8 +27,8 @@ fn Java_java_lang_System_arraycopy(env: &JniEnv) -> RuntimeResult<Option<JavaVal
     let length = env.parameters[4].as_int().unwrap();
 
     for i in 0..length {
-        let value = env.get_array_element(src, (srcPos + i) as usize);
-        env.set_array_element(dest, (destPos + i) as usize, value);
+        let value = env.get_array_element(src, (srcPos + i) as usize)?;
+        env.set_array_element(dest, (destPos + i) as usize, value)?;
     }
 
     Ok(None)
@@ -58,17 +51,19 @@ fn Java_java_lang_System_initProperties(env: &JniEnv) -> RuntimeResult<Option<Ja
         ("line.separator", "\n"),
         ("user.name", "web"),
         ("user.home", "/dev/null"),
-        ("user.dir", "/dev/null"),
+        ("user.dir", "/"),
         ("sun.nio.PageAlignDirectMemory", "false"),
         ("sun.stdout.encoding", "UTF-8"),
         ("sun.stderr.encoding", "UTF-8"),
+        // reflective calls always go through the native accessors instead of generating bytecode
+        ("sun.reflect.inflationThreshold", "2147483647"),
     ];
 
     let props_class = env.get_class_id("java/util/Properties")?;
     let prop_map = env.parameters[0].as_object().unwrap().unwrap();
     for default_property in default_properties {
-        let key_str = env.new_string(default_property.0);
-        let value_str = env.new_string(default_property.1);
+        let key_str = env.new_string(default_property.0)?;
+        let value_str = env.new_string(default_property.1)?;
         env.invoke_instance_method(
             InvokeType::Virtual,
             prop_map,
@@ -85,7 +80,7 @@ fn Java_java_lang_System_initProperties(env: &JniEnv) -> RuntimeResult<Option<Ja
 #[allow(non_snake_case)]
 fn Java_java_lang_System_setIn0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let stdin = env.parameters[0].clone();
-    env.set_static_field("java/lang/System", "in", stdin);
+    env.set_static_field("java/lang/System", "in", stdin)?;
 
     Ok(None)
 }
@@ -93,7 +88,7 @@ fn Java_java_lang_System_setIn0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>
 #[allow(non_snake_case)]
 fn Java_java_lang_System_setOut0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let stdout = env.parameters[0].clone();
-    env.set_static_field("java/lang/System", "out", stdout);
+    env.set_static_field("java/lang/System", "out", stdout)?;
 
     Ok(None)
 }
@@ -101,31 +96,27 @@ fn Java_java_lang_System_setOut0(env: &JniEnv) -> RuntimeResult<Option<JavaValue
 #[allow(non_snake_case)]
 fn Java_java_lang_System_setErr0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let stderr = env.parameters[0].clone();
-    env.set_static_field("java/lang/System", "err", stderr);
+    env.set_static_field("java/lang/System", "err", stderr)?;
 
     Ok(None)
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_System_mapLibraryName(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let library_name = env.get_string(env.parameters[0].as_object().unwrap().unwrap());
+    let library_name = env.get_string(env.parameters[0].as_object().unwrap().unwrap())?;
     let linux_style = format!("lib{}.so", library_name);
-    let java_str = env.new_string(&linux_style);
+    let java_str = env.new_string(&linux_style)?;
 
     Ok(Some(JavaValue::Object(Some(java_str))))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_System_identityHashCode(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let mut x = match &env.parameters[0] {
-        JavaValue::Array(id) => *id,
-        JavaValue::Object(obj) => *obj.as_ref().unwrap(),
-        _ => panic!(),
+    let hash = match env.parameters[0] {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => env.jvm.get_identity_hash(id)?,
+        _ => 0,
     };
-    x = ((x >> 16) ^ x) * 0x45d9f3b;
-    x = ((x >> 16) ^ x) * 0x45d9f3b;
-    x = (x >> 16) ^ x;
-    Ok(Some(JavaValue::Int(x as i32)))
+    Ok(Some(JavaValue::Int(hash)))
 }
 
 pub fn initialize(cp: &mut Classpath) {
@@ -1,5 +1,6 @@
 use crate::{
-    model::{InternalMetadata, JavaValue, RuntimeResult},
+    exec::thread::ThreadState,
+    model::{JavaValue, RuntimeResult},
     Classpath, JniEnv,
 };
 
@@ -10,7 +11,7 @@ fn Java_java_lang_Thread_registerNatives(_: &JniEnv) -> RuntimeResult<Option<Jav
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Thread_currentThread(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    Ok(Some(JavaValue::Object(Some(env.get_main_thread()))))
+    Ok(Some(JavaValue::Object(Some(env.get_current_thread()))))
 }
 
 #[allow(non_snake_case)]
@@ -20,8 +21,8 @@ fn Java_java_lang_Thread_setPriority0(_: &JniEnv) -> RuntimeResult<Option<JavaVa
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Thread_isAlive(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    if let Some(is_alive) = env.get_internal_metadata(env.get_current_instance()?, "is_alive") {
-        Ok(Some(JavaValue::Boolean(is_alive.into_usize() == 1)))
+    if let Some(is_alive) = env.get_internal_metadata(env.get_current_instance()?, "is_alive")? {
+        Ok(Some(JavaValue::Boolean(is_alive.into_usize().map_err(|err| env.jvm.raise(err))? == 1)))
     } else {
         Ok(Some(JavaValue::Boolean(false)))
     }
@@ -29,10 +30,80 @@ fn Java_java_lang_Thread_isAlive(env: &JniEnv) -> RuntimeResult<Option<JavaValue
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Thread_start0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    env.set_internal_metadata(env.get_current_instance()?, "is_alive", InternalMetadata::Numeric(1));
+    env.jvm.start_thread(env.get_current_instance()?)?;
     Ok(None)
 }
 
+#[allow(non_snake_case)]
+fn Java_java_lang_Thread_yield(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    env.jvm.threads.borrow_mut().request_yield();
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Thread_sleep(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let millis = env.parameters[0].as_long().unwrap();
+    if millis < 0 {
+        return Err(env.throw_exception("java/lang/IllegalArgumentException", Some("timeout value is negative")));
+    }
+
+    let is_interrupted = {
+        let mut threads = env.jvm.threads.borrow_mut();
+        let thread = threads.get_current_thread_mut();
+        if thread.pending_sleep {
+            thread.pending_sleep = false;
+        } else if !thread.interrupted {
+            thread.pending_sleep = true;
+            thread.state = ThreadState::Sleeping((env.jvm.clock.borrow().current_time_millis() + millis) as u64);
+            return Ok(None);
+        }
+        std::mem::replace(&mut thread.interrupted, false)
+    };
+
+    if is_interrupted {
+        return Err(env.throw_exception("java/lang/InterruptedException", Some("sleep interrupted")));
+    }
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Thread_interrupt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let thread_object = env.get_current_instance()?;
+    let mut threads = env.jvm.threads.borrow_mut();
+    if let Some(thread_id) = threads.find_thread(thread_object) {
+        threads.interrupt(thread_id);
+    }
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Thread_isInterrupted(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let thread_object = env.get_current_instance()?;
+    let clear_interrupted = env.parameters[1].as_boolean().unwrap();
+    let mut threads = env.jvm.threads.borrow_mut();
+    let is_interrupted = match threads.find_thread(thread_object) {
+        Some(thread_id) => {
+            let thread = threads.get_thread_mut(thread_id);
+            let is_interrupted = thread.interrupted;
+            if clear_interrupted {
+                thread.interrupted = false;
+            }
+            is_interrupted
+        }
+        None => false,
+    };
+    Ok(Some(JavaValue::Boolean(is_interrupted)))
+}
+
+#[allow(non_snake_case)]
+fn Java_java_lang_Thread_holdsLock(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let object = match env.parameters[0] {
+        JavaValue::Object(Some(id)) | JavaValue::Array(id) => id,
+        _ => return Err(env.throw_exception("java/lang/NullPointerException", None)),
+    };
+    Ok(Some(JavaValue::Boolean(env.jvm.threads.borrow().holds_monitor(object))))
+}
+
 pub fn initialize(cp: &mut Classpath) {
     register_jni!(
         cp,
@@ -40,6 +111,11 @@ pub fn initialize(cp: &mut Classpath) {
         Java_java_lang_Thread_currentThread,
         Java_java_lang_Thread_setPriority0,
         Java_java_lang_Thread_isAlive,
-        Java_java_lang_Thread_start0
+        Java_java_lang_Thread_start0,
+        Java_java_lang_Thread_yield,
+        Java_java_lang_Thread_sleep,
+        Java_java_lang_Thread_interrupt0,
+        Java_java_lang_Thread_isInterrupted,
+        Java_java_lang_Thread_holdsLock
     );
 }
@@ -6,16 +6,16 @@ use crate::{
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Throwable_getStackTraceElement(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let arr = env.get_field(env.get_current_instance()?, "backtrace").as_array().unwrap();
+    let arr = env.get_field(env.get_current_instance()?, "backtrace")?.as_array().unwrap();
     let index = env.parameters[1].as_int().unwrap();
-    let val = env.get_array_element(arr, index as usize);
+    let val = env.get_array_element(arr, index as usize)?;
     Ok(Some(val))
 }
 
 #[allow(non_snake_case)]
 fn Java_java_lang_Throwable_getStackTraceDepth(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let arr = env.get_field(env.get_current_instance()?, "backtrace").as_array().unwrap();
-    Ok(Some(JavaValue::Int(env.get_array_length(arr) as i32)))
+    let arr = env.get_field(env.get_current_instance()?, "backtrace")?.as_array().unwrap();
+    Ok(Some(JavaValue::Int(env.get_array_length(arr)? as i32)))
 }
 
 #[allow(non_snake_case)]
@@ -27,8 +27,8 @@ fn Java_java_lang_Throwable_fillInStackTrace(env: &JniEnv) -> RuntimeResult<Opti
         let (class_name, method_name, line_number) = {
             let csf = env.jvm.call_stack_frames.borrow();
             let frame = &csf[csf_len - i - 5];
-            let class_name = env.new_string(&frame.container_class.replace("/", "."));
-            let method_name = env.new_string(&frame.container_method[0..frame.container_method.find('(').unwrap()]);
+            let class_name = env.new_string(&frame.container_class.replace("/", "."))?;
+            let method_name = env.new_string(&frame.container_method[0..frame.container_method.find('(').unwrap()])?;
             let line_number = match frame.is_native_frame {
                 true => -2,
                 false => -1,
@@ -51,9 +51,9 @@ fn Java_java_lang_Throwable_fillInStackTrace(env: &JniEnv) -> RuntimeResult<Opti
                 JavaValue::Int(line_number),
             ],
         )?;
-        env.set_array_element(stacktrace, i - 1, JavaValue::Object(Some(ste)));
+        env.set_array_element(stacktrace, i - 1, JavaValue::Object(Some(ste)))?;
     }
-    env.set_field(env.get_current_instance()?, "backtrace", JavaValue::Array(stacktrace));
+    env.set_field(env.get_current_instance()?, "backtrace", JavaValue::Array(stacktrace))?;
 
     Ok(Some(JavaValue::Object(Some(env.get_current_instance()?))))
 }
@@ -6,36 +6,32 @@ use crate::{
 
 #[allow(non_snake_case)]
 fn Java_java_lang_reflect_Array_newArray(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let component_type_name =
-        env.get_internal_metadata(env.get_current_instance()?, "class_name").unwrap().into_string();
+    let component_class_id = env.get_numeric_metadata(env.get_current_instance()?, "class_id")?;
     let length = env.parameters[1].as_int().unwrap();
 
     if length < 0 {
         return Err(env.throw_exception("java/lang/NegativeArraySizeException", None));
     }
 
-    let array_id = env.new_array(
-        match component_type_name.as_str() {
-            "byte" => JavaArrayType::Byte,
-            "short" => JavaArrayType::Short,
-            "int" => JavaArrayType::Int,
-            "long" => JavaArrayType::Long,
-            "float" => JavaArrayType::Float,
-            "double" => JavaArrayType::Double,
-            "char" => JavaArrayType::Char,
-            "boolean" => JavaArrayType::Boolean,
-            other => {
-                if other.starts_with('[') {
-                    unimplemented!("multidimensional arrays not yet implemented");
-                } else {
-                    JavaArrayType::Object(
-                        env.get_internal_metadata(env.get_current_instance()?, "class_id").unwrap().into_usize(),
-                    )
-                }
-            }
-        },
-        length as usize,
-    );
+    // array components are referenced by their array class, like anewarray does
+    let (component_type_name, is_primitive_type) = {
+        let heap = env.jvm.heap.borrow();
+        let component_class = &heap.loaded_classes[component_class_id];
+        (component_class.java_type.clone(), component_class.is_primitive_type)
+    };
+    let array_type = match (component_type_name.as_str(), is_primitive_type) {
+        ("byte", true) => JavaArrayType::Byte,
+        ("short", true) => JavaArrayType::Short,
+        ("int", true) => JavaArrayType::Int,
+        ("long", true) => JavaArrayType::Long,
+        ("float", true) => JavaArrayType::Float,
+        ("double", true) => JavaArrayType::Double,
+        ("char", true) => JavaArrayType::Char,
+        ("boolean", true) => JavaArrayType::Boolean,
+        (_, true) => return Err(env.throw_exception("java/lang/IllegalArgumentException", None)),
+        (_, false) => JavaArrayType::Object(component_class_id),
+    };
+    let array_id = env.new_array(array_type, length as usize);
 
     Ok(Some(JavaValue::Array(array_id)))
 }
@@ -0,0 +1,18 @@
+use super::java_lang_Class::{get_attribute_bytes, get_reflected_method};
+use crate::{
+    exec::env::JniEnv,
+    model::{JavaValue, RuntimeResult},
+    Classpath,
+};
+
+#[allow(non_snake_case)]
+fn Java_java_lang_reflect_Executable_getTypeAnnotationBytes0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (_, class_file, method) = get_reflected_method(env, env.get_current_instance()?)?;
+    let type_annotations =
+        get_attribute_bytes(env, &class_file.const_pool, &method.attributes, "RuntimeVisibleTypeAnnotations")?;
+    Ok(Some(type_annotations))
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(cp, Java_java_lang_reflect_Executable_getTypeAnnotationBytes0);
+}
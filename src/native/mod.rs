@@ -2,6 +2,7 @@
 
 use crate::{
     model::{JavaValue, RuntimeResult},
+    util::log_error,
     Classpath, JniEnv, NativeMethod,
 };
 
@@ -27,7 +28,7 @@ macro_rules! register_jni {
                 name: String::from(stringify!($func)),
                 handler: Box::new($func),
             };
-            $cp.add_native_method(Box::new(method));
+            $cp.add_jni_native_method(stringify!($func), Box::new(method));
         )*
     };
 }
@@ -38,16 +39,21 @@ mod java_lang_Double;
 mod java_lang_Float;
 mod java_lang_Object;
 mod java_lang_Runtime;
+mod java_lang_Shutdown;
+mod java_lang_StrictMath;
 mod java_lang_String;
 mod java_lang_System;
 mod java_lang_Thread;
 mod java_lang_Throwable;
 
 mod java_lang_reflect_Array;
+mod java_lang_reflect_Executable;
+mod java_lang_reflect_Field;
 
 mod java_io_FileDescriptor;
 mod java_io_FileInputStream;
 mod java_io_FileOutputStream;
+mod java_io_RandomAccessFile;
 mod java_io_UnixFileSystem;
 
 mod java_security_AccessController;
@@ -59,10 +65,29 @@ mod sun_misc_URLClassPath;
 mod sun_misc_Unsafe;
 mod sun_misc_VM;
 
+mod sun_reflect_ConstantPool;
 mod sun_reflect_NativeConstructorAccessorImpl;
+mod sun_reflect_NativeMethodAccessorImpl;
 mod sun_reflect_Reflection;
 
+mod webjvm_js_JSObject;
+mod webjvm_lang_Resources;
+
+// classes shipped with the VM, compiled from the sources in the java directory
+const BUNDLED_CLASSES: &[&[u8]] = &[
+    include_bytes!("../../java/webjvm/lang/Resources.class"),
+    include_bytes!("../../java/webjvm/lang/Resources$ResourceConnection.class"),
+    include_bytes!("../../java/webjvm/js/JSObject.class"),
+    include_bytes!("../../java/webjvm/js/JSException.class"),
+];
+
 pub fn initialize(cp: &mut Classpath) {
+    for class_bytes in BUNDLED_CLASSES {
+        if let Err(err) = cp.add_classpath_entry(class_bytes) {
+            log_error(&format!("invalid bundled class: {}", err));
+        }
+    }
+
     java_lang_Object::initialize(cp);
     java_lang_String::initialize(cp);
     java_lang_Class::initialize(cp);
@@ -70,15 +95,20 @@ pub fn initialize(cp: &mut Classpath) {
     java_lang_System::initialize(cp);
     java_lang_Float::initialize(cp);
     java_lang_Double::initialize(cp);
+    java_lang_StrictMath::initialize(cp);
     java_lang_Thread::initialize(cp);
     java_lang_Throwable::initialize(cp);
     java_lang_Runtime::initialize(cp);
+    java_lang_Shutdown::initialize(cp);
 
     java_lang_reflect_Array::initialize(cp);
+    java_lang_reflect_Executable::initialize(cp);
+    java_lang_reflect_Field::initialize(cp);
 
     java_io_FileInputStream::initialize(cp);
     java_io_FileOutputStream::initialize(cp);
     java_io_FileDescriptor::initialize(cp);
+    java_io_RandomAccessFile::initialize(cp);
     java_io_UnixFileSystem::initialize(cp);
 
     java_security_AccessController::initialize(cp);
@@ -91,5 +121,10 @@ pub fn initialize(cp: &mut Classpath) {
     sun_misc_URLClassPath::initialize(cp);
 
     sun_reflect_Reflection::initialize(cp);
+    sun_reflect_ConstantPool::initialize(cp);
     sun_reflect_NativeConstructorAccessorImpl::initialize(cp);
+    sun_reflect_NativeMethodAccessorImpl::initialize(cp);
+
+    webjvm_js_JSObject::initialize(cp);
+    webjvm_lang_Resources::initialize(cp);
 }
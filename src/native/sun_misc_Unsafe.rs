@@ -1,11 +1,328 @@
-use std::mem::size_of;
+use classfile_parser::{field_info::FieldAccessFlags, ClassAccessFlags};
 
+use super::java_lang_Class::{get_field_slot_offset, get_reflected_field};
 use crate::{
-    model::{InternalMetadata, JavaValue, RuntimeResult},
+    exec::{memory::MemoryError, thread::ThreadState},
+    model::{JavaClass, JavaThrowable, JavaValue, RuntimeResult},
+    util::get_constant_string,
     Classpath, InvokeType, JniEnv,
 };
 
-const ADDRESS_SIZE: i32 = size_of::<usize>() as i32;
+// addresses are handed out by the VM's memory arena rather than the host, so they are always 64 bit
+const ADDRESS_SIZE: i32 = 8;
+const PAGE_SIZE: i32 = 4096;
+// static field offsets are kept apart from instance field offsets so that a mismatched base is detected
+const STATIC_FIELD_OFFSET: i64 = 1 << 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AccessType {
+    Object,
+    Boolean,
+    Byte,
+    Short,
+    Char,
+    Int,
+    Long,
+    Float,
+    Double,
+}
+
+impl AccessType {
+    // the number of parameter slots a value of this type occupies
+    fn get_slot_size(self) -> usize {
+        match self {
+            AccessType::Long | AccessType::Double => 2,
+            _ => 1,
+        }
+    }
+
+    fn encode(self, value: &JavaValue) -> Option<Vec<u8>> {
+        Some(match self {
+            AccessType::Object => return None,
+            AccessType::Boolean | AccessType::Byte => vec![value.as_int().ok()? as u8],
+            AccessType::Short | AccessType::Char => (value.as_int().ok()? as u16).to_le_bytes().to_vec(),
+            AccessType::Int => value.as_int().ok()?.to_le_bytes().to_vec(),
+            AccessType::Long => value.as_long().ok()?.to_le_bytes().to_vec(),
+            AccessType::Float => value.as_float().ok()?.to_bits().to_le_bytes().to_vec(),
+            AccessType::Double => value.as_double().ok()?.to_bits().to_le_bytes().to_vec(),
+        })
+    }
+
+    fn decode(self, bytes: &[u8]) -> JavaValue {
+        let mut buf = [0u8; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        match self {
+            AccessType::Object => JavaValue::Object(None),
+            AccessType::Boolean => JavaValue::Boolean(buf[0] != 0),
+            AccessType::Byte => JavaValue::Byte(buf[0] as i8),
+            AccessType::Short => JavaValue::Short(i16::from_le_bytes([buf[0], buf[1]])),
+            AccessType::Char => JavaValue::Char(u16::from_le_bytes([buf[0], buf[1]])),
+            AccessType::Int => JavaValue::Int(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+            AccessType::Long => JavaValue::Long(i64::from_le_bytes(buf)),
+            AccessType::Float => JavaValue::Float(f32::from_bits(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]))),
+            AccessType::Double => JavaValue::Double(f64::from_bits(u64::from_le_bytes(buf))),
+        }
+    }
+
+    fn get_size(self) -> usize {
+        match self {
+            AccessType::Object => 0,
+            AccessType::Boolean | AccessType::Byte => 1,
+            AccessType::Short | AccessType::Char => 2,
+            AccessType::Int | AccessType::Float => 4,
+            AccessType::Long | AccessType::Double => 8,
+        }
+    }
+}
+
+enum Location {
+    Memory(u64),
+    ArrayElement(usize, usize),
+    InstanceField(usize, String),
+    StaticField(String, String),
+}
+
+fn memory_error(env: &JniEnv, error: MemoryError) -> JavaThrowable {
+    match error {
+        MemoryError::OutOfMemory => env.throw_exception("java/lang/OutOfMemoryError", None),
+        MemoryError::InvalidAddress(address) => env.throw_exception(
+            "java/lang/InternalError",
+            Some(&format!("invalid memory access at address 0x{:x}", address)),
+        ),
+    }
+}
+
+// field offsets are the reflective slots of the fields, which are numbered across the class hierarchy
+// returns the declaring class, the name and whether the field is static
+fn get_field_at_slot(env: &JniEnv, class_id: usize, slot: usize) -> RuntimeResult<Option<(usize, String, bool)>> {
+    let mut current = Some(class_id);
+    while let Some(id) = current {
+        let starting_offset = get_field_slot_offset(env, id)?;
+        if slot >= starting_offset {
+            let class_file = env.get_class_file(id)?;
+            return Ok(class_file.fields.get(slot - starting_offset).map(|field| {
+                (
+                    id,
+                    get_constant_string(&class_file.const_pool, field.name_index).clone(),
+                    field.access_flags.contains(FieldAccessFlags::STATIC),
+                )
+            }));
+        }
+        current = env.get_superclass(id);
+    }
+    Ok(None)
+}
+
+// a null base makes the offset an absolute address, the base of static fields is their class object
+fn get_location(env: &JniEnv, base: &JavaValue, offset: i64) -> RuntimeResult<Location> {
+    let invalid_offset =
+        || env.throw_exception("java/lang/InternalError", Some(&format!("invalid field offset {}", offset)));
+
+    match base {
+        JavaValue::Object(None) => Ok(Location::Memory(offset as u64)),
+        JavaValue::Array(array_id) => Ok(Location::ArrayElement(*array_id, offset as usize)),
+        JavaValue::Object(Some(id)) if offset >= STATIC_FIELD_OFFSET => {
+            let class_id = match env.get_internal_metadata(*id, "class_id")? {
+                Some(class_id) => class_id.into_usize().map_err(|err| env.jvm.raise(err))?,
+                None => return Err(invalid_offset()),
+            };
+            match get_field_at_slot(env, class_id, (offset - STATIC_FIELD_OFFSET) as usize)? {
+                Some((_, name, true)) => Ok(Location::StaticField(env.jvm.get_class_name_from_id(class_id), name)),
+                _ => Err(invalid_offset()),
+            }
+        }
+        JavaValue::Object(Some(id)) if offset >= 0 => {
+            let class_id = env.get_class_id(&env.get_object_type_name(*id)?)?;
+            match get_field_at_slot(env, class_id, offset as usize)? {
+                Some((declaring_class_id, name, false)) => {
+                    let declaring_class = env.jvm.get_class_name_from_id(declaring_class_id);
+                    let heap = env.jvm.heap.borrow();
+                    let key = heap.object_heap_map[id].get_field_key(&declaring_class, &name).into_owned();
+                    Ok(Location::InstanceField(*id, key))
+                }
+                _ => Err(invalid_offset()),
+            }
+        }
+        _ => Err(invalid_offset()),
+    }
+}
+
+// the address forms take (long address, value) and the object forms (Object base, long offset, value),
+// returns the location and the index of the first value parameter
+fn get_access(env: &JniEnv) -> RuntimeResult<(Location, usize)> {
+    match &env.parameters[1] {
+        JavaValue::Long(address) => Ok((Location::Memory(*address as u64), 3)),
+        base => Ok((get_location(env, base, env.parameters[2].as_long().unwrap())?, 4)),
+    }
+}
+
+fn read(env: &JniEnv, location: &Location, access_type: AccessType) -> RuntimeResult<JavaValue> {
+    match location {
+        Location::Memory(address) => {
+            if access_type == AccessType::Object {
+                return Err(memory_error(env, MemoryError::InvalidAddress(*address)));
+            }
+            let mut buf = vec![0u8; access_type.get_size()];
+            env.jvm.memory.borrow_mut().read(*address, &mut buf).map_err(|err| memory_error(env, err))?;
+            Ok(access_type.decode(&buf))
+        }
+        Location::ArrayElement(array_id, index) => env.get_array_element(*array_id, *index),
+        Location::InstanceField(instance_id, name) => env.get_field(*instance_id, name),
+        Location::StaticField(class_name, name) => JavaClass::get_static_field(env.jvm, class_name, name),
+    }
+}
+
+fn write(env: &JniEnv, location: &Location, access_type: AccessType, value: JavaValue) -> RuntimeResult<()> {
+    match location {
+        Location::Memory(address) => match access_type.encode(&value) {
+            Some(bytes) => env.jvm.memory.borrow_mut().write(*address, &bytes).map_err(|err| memory_error(env, err)),
+            None => Err(memory_error(env, MemoryError::InvalidAddress(*address))),
+        },
+        Location::ArrayElement(array_id, index) => env.set_array_element(*array_id, *index, value),
+        Location::InstanceField(instance_id, name) => env.set_field(*instance_id, name, value),
+        Location::StaticField(class_name, name) => JavaClass::set_static_field(env.jvm, class_name, name, value),
+    }
+}
+
+macro_rules! define_get {
+    ( $fname:ident, $access_type:ident ) => {
+        #[allow(non_snake_case)]
+        fn $fname(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+            let (location, _) = get_access(env)?;
+            Ok(Some(read(env, &location, AccessType::$access_type)?))
+        }
+    };
+}
+
+macro_rules! define_put {
+    ( $fname:ident, $access_type:ident ) => {
+        #[allow(non_snake_case)]
+        fn $fname(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+            let (location, value_index) = get_access(env)?;
+            write(env, &location, AccessType::$access_type, env.parameters[value_index].clone())?;
+            Ok(None)
+        }
+    };
+}
+
+define_get!(Java_sun_misc_Unsafe_getObject, Object);
+define_get!(Java_sun_misc_Unsafe_getBoolean, Boolean);
+define_get!(Java_sun_misc_Unsafe_getByte, Byte);
+define_get!(Java_sun_misc_Unsafe_getShort, Short);
+define_get!(Java_sun_misc_Unsafe_getChar, Char);
+define_get!(Java_sun_misc_Unsafe_getInt, Int);
+define_get!(Java_sun_misc_Unsafe_getLong, Long);
+define_get!(Java_sun_misc_Unsafe_getFloat, Float);
+define_get!(Java_sun_misc_Unsafe_getDouble, Double);
+define_get!(Java_sun_misc_Unsafe_getAddress, Long);
+
+define_put!(Java_sun_misc_Unsafe_putObject, Object);
+define_put!(Java_sun_misc_Unsafe_putBoolean, Boolean);
+define_put!(Java_sun_misc_Unsafe_putByte, Byte);
+define_put!(Java_sun_misc_Unsafe_putShort, Short);
+define_put!(Java_sun_misc_Unsafe_putChar, Char);
+define_put!(Java_sun_misc_Unsafe_putInt, Int);
+define_put!(Java_sun_misc_Unsafe_putLong, Long);
+define_put!(Java_sun_misc_Unsafe_putFloat, Float);
+define_put!(Java_sun_misc_Unsafe_putDouble, Double);
+define_put!(Java_sun_misc_Unsafe_putAddress, Long);
+
+// threads only switch between instructions, so volatile and ordered accesses are plain accesses
+define_get!(Java_sun_misc_Unsafe_getObjectVolatile, Object);
+define_get!(Java_sun_misc_Unsafe_getBooleanVolatile, Boolean);
+define_get!(Java_sun_misc_Unsafe_getByteVolatile, Byte);
+define_get!(Java_sun_misc_Unsafe_getShortVolatile, Short);
+define_get!(Java_sun_misc_Unsafe_getCharVolatile, Char);
+define_get!(Java_sun_misc_Unsafe_getIntVolatile, Int);
+define_get!(Java_sun_misc_Unsafe_getLongVolatile, Long);
+define_get!(Java_sun_misc_Unsafe_getFloatVolatile, Float);
+define_get!(Java_sun_misc_Unsafe_getDoubleVolatile, Double);
+
+define_put!(Java_sun_misc_Unsafe_putObjectVolatile, Object);
+define_put!(Java_sun_misc_Unsafe_putBooleanVolatile, Boolean);
+define_put!(Java_sun_misc_Unsafe_putByteVolatile, Byte);
+define_put!(Java_sun_misc_Unsafe_putShortVolatile, Short);
+define_put!(Java_sun_misc_Unsafe_putCharVolatile, Char);
+define_put!(Java_sun_misc_Unsafe_putIntVolatile, Int);
+define_put!(Java_sun_misc_Unsafe_putLongVolatile, Long);
+define_put!(Java_sun_misc_Unsafe_putFloatVolatile, Float);
+define_put!(Java_sun_misc_Unsafe_putDoubleVolatile, Double);
+
+define_put!(Java_sun_misc_Unsafe_putOrderedObject, Object);
+define_put!(Java_sun_misc_Unsafe_putOrderedInt, Int);
+define_put!(Java_sun_misc_Unsafe_putOrderedLong, Long);
+
+fn compare_and_swap(env: &JniEnv, access_type: AccessType) -> RuntimeResult<Option<JavaValue>> {
+    let (location, value_index) = get_access(env)?;
+    let expected = &env.parameters[value_index];
+    let update = env.parameters[value_index + access_type.get_slot_size()].clone();
+
+    let current = read(env, &location, access_type)?;
+    let is_expected = match access_type {
+        AccessType::Object => current == *expected,
+        AccessType::Long => current.as_long() == expected.as_long(),
+        _ => current.as_int() == expected.as_int(),
+    };
+    if is_expected {
+        write(env, &location, access_type, update)?;
+    }
+    Ok(Some(JavaValue::Boolean(is_expected)))
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_compareAndSwapObject(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    compare_and_swap(env, AccessType::Object)
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_compareAndSwapInt(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    compare_and_swap(env, AccessType::Int)
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_compareAndSwapLong(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    compare_and_swap(env, AccessType::Long)
+}
+
+fn get_and_update(
+    env: &JniEnv,
+    access_type: AccessType,
+    update: impl FnOnce(&JavaValue, &JavaValue) -> JavaValue,
+) -> RuntimeResult<Option<JavaValue>> {
+    let (location, value_index) = get_access(env)?;
+    let current = read(env, &location, access_type)?;
+    write(env, &location, access_type, update(&current, &env.parameters[value_index]))?;
+    Ok(Some(current))
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_getAndAddInt(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    get_and_update(env, AccessType::Int, |current, delta| {
+        JavaValue::Int(current.as_int().unwrap().wrapping_add(delta.as_int().unwrap()))
+    })
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_getAndAddLong(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    get_and_update(env, AccessType::Long, |current, delta| {
+        JavaValue::Long(current.as_long().unwrap().wrapping_add(delta.as_long().unwrap()))
+    })
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_getAndSetInt(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    get_and_update(env, AccessType::Int, |_, value| value.clone())
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_getAndSetLong(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    get_and_update(env, AccessType::Long, |_, value| value.clone())
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_getAndSetObject(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    get_and_update(env, AccessType::Object, |_, value| value.clone())
+}
 
 #[allow(non_snake_case)]
 fn Java_sun_misc_Unsafe_registerNatives(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
@@ -17,46 +334,21 @@ fn Java_sun_misc_Unsafe_arrayBaseOffset(_: &JniEnv) -> RuntimeResult<Option<Java
     Ok(Some(JavaValue::Int(0)))
 }
 
+// array offsets are element indices regardless of the component type
 #[allow(non_snake_case)]
 fn Java_sun_misc_Unsafe_arrayIndexScale(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let array_class = &env.parameters[1].as_object().unwrap().unwrap();
-    let scale = match env
-        .invoke_instance_method(
-            InvokeType::Virtual,
-            *array_class,
-            env.get_class_id("java/lang/Class")?,
-            "getComponentType",
-            "()Ljava/lang/Class;",
-            &[],
-        )?
-        .unwrap()
-    {
-        JavaValue::Object(obj) => match obj {
-            Some(_component_type) => {
-                1
-
-                // size_of::<JavaValue>() as i32
-
-                // match env.get_internal_metadata(component_type, "class_name").unwrap().into_string().as_str() {
-                //     "byte" => 1,
-                //     "short" => 2,
-                //     "int" => 4,
-                //     "long" => 8,
-                //     "float" => 4,
-                //     "double" => 8,
-                //     "char" => 2,
-                //     "boolean" => 1,
-                //     _ => ADDRESS_SIZE,
-                // }
-            }
-            None => {
-                return Err(env.throw_exception("java/lang/InvalidClassCastException", Some("expecting array type")))
-            }
-        },
-        _ => panic!(),
-    };
-
-    Ok(Some(JavaValue::Int(scale)))
+    match env.invoke_instance_method(
+        InvokeType::Virtual,
+        *array_class,
+        env.get_class_id("java/lang/Class")?,
+        "getComponentType",
+        "()Ljava/lang/Class;",
+        &[],
+    )? {
+        Some(JavaValue::Object(Some(_))) => Ok(Some(JavaValue::Int(1))),
+        _ => Err(env.throw_exception("java/lang/IllegalArgumentException", Some("expecting array type"))),
+    }
 }
 
 #[allow(non_snake_case)]
@@ -64,133 +356,265 @@ fn Java_sun_misc_Unsafe_addressSize(_: &JniEnv) -> RuntimeResult<Option<JavaValu
     Ok(Some(JavaValue::Int(ADDRESS_SIZE)))
 }
 
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_pageSize(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Int(PAGE_SIZE)))
+}
+
+fn get_field_offset(env: &JniEnv, is_static: bool) -> RuntimeResult<i64> {
+    let field = match env.parameters[1] {
+        JavaValue::Object(Some(field)) => field,
+        _ => return Err(env.jvm.throw_npe()),
+    };
+    let (_, _, field_info) = get_reflected_field(env, field)?;
+    if field_info.access_flags.contains(FieldAccessFlags::STATIC) != is_static {
+        return Err(env.throw_exception("java/lang/IllegalArgumentException", None));
+    }
+
+    let slot = env.get_field(field, "slot")?.as_int().unwrap() as i64;
+    Ok(match is_static {
+        true => STATIC_FIELD_OFFSET + slot,
+        false => slot,
+    })
+}
+
 #[allow(non_snake_case)]
 fn Java_sun_misc_Unsafe_objectFieldOffset(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let field = env.parameters[1].as_object().unwrap().unwrap();
-    let name = env.get_field(field, "name").as_object().unwrap().unwrap();
-    Ok(Some(JavaValue::Long(name as i64)))
+    Ok(Some(JavaValue::Long(get_field_offset(env, false)?)))
 }
 
-fn get_value_at_offset(env: &JniEnv, container: &JavaValue, offset: i64) -> JavaValue {
-    if container.is_array() {
-        let array_id = env.parameters[1].as_array().unwrap();
-        let pointer = env.parameters[2].as_long().unwrap() as usize;
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_staticFieldOffset(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(Some(JavaValue::Long(get_field_offset(env, true)?)))
+}
 
-        env.get_array_element(array_id, pointer)
-    } else {
-        let obj = container.as_object().unwrap().unwrap();
-        let field_name = env.get_string(offset as usize);
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_staticFieldBase(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let field = match env.parameters[1] {
+        JavaValue::Object(Some(field)) => field,
+        _ => return Err(env.jvm.throw_npe()),
+    };
+    let (class_id, _, _) = get_reflected_field(env, field)?;
+    Ok(Some(JavaValue::Object(Some(env.get_class_object(class_id)))))
+}
 
-        let heap = env.jvm.heap.borrow();
-        let internal_obj = heap.object_heap_map.get(&obj).unwrap();
-        internal_obj.instance_fields.get(&field_name).unwrap().clone()
+fn get_memory_size(env: &JniEnv, index: usize) -> RuntimeResult<u64> {
+    match env.parameters[index].as_long().unwrap() {
+        size if size < 0 => Err(env.throw_exception("java/lang/IllegalArgumentException", None)),
+        size => Ok(size as u64),
     }
 }
 
-fn set_value_at_offset(env: &JniEnv, container: &JavaValue, offset: i64, value: JavaValue) {
-    if container.is_array() {
-        let array_id = env.parameters[1].as_array().unwrap();
-        let pointer = env.parameters[2].as_long().unwrap() as usize;
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_allocateMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let size = get_memory_size(env, 1)?;
+    if size == 0 {
+        return Ok(Some(JavaValue::Long(0)));
+    }
 
-        env.set_array_element(array_id, pointer, value);
-    } else {
-        let obj = container.as_object().unwrap().unwrap();
-        let field_name = env.get_string(offset as usize);
+    let address = env.jvm.memory.borrow_mut().allocate(size).map_err(|err| memory_error(env, err))?;
+    Ok(Some(JavaValue::Long(address as i64)))
+}
 
-        let mut heap = env.jvm.heap.borrow_mut();
-        let internal_obj = heap.object_heap_map.get_mut(&obj).unwrap();
-        internal_obj.instance_fields.insert(field_name, value);
-    }
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_reallocateMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let address = env.parameters[1].as_long().unwrap() as u64;
+    let size = get_memory_size(env, 3)?;
+
+    let mut memory = env.jvm.memory.borrow_mut();
+    let result = match (address, size) {
+        (0, 0) => Ok(0),
+        (0, size) => memory.allocate(size),
+        (address, 0) => memory.free(address).map(|_| 0),
+        (address, size) => memory.reallocate(address, size),
+    };
+    drop(memory);
+
+    let address = result.map_err(|err| memory_error(env, err))?;
+    Ok(Some(JavaValue::Long(address as i64)))
 }
 
-fn cas(env: &JniEnv, type_size: usize) -> RuntimeResult<Option<JavaValue>> {
-    let container = &env.parameters[1];
-    let offset = env.parameters[2].as_long().unwrap();
-    let expect = env.parameters[4].clone();
-    let update = env.parameters[4 + type_size].clone();
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_freeMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let address = env.parameters[1].as_long().unwrap() as u64;
+    if address != 0 {
+        let result = env.jvm.memory.borrow_mut().free(address);
+        result.map_err(|err| memory_error(env, err))?;
+    }
+    Ok(None)
+}
 
-    let current_field = get_value_at_offset(env, container, offset);
-    if current_field == expect {
-        set_value_at_offset(env, container, offset, update);
-        Ok(Some(JavaValue::Boolean(true)))
-    } else {
-        Ok(Some(JavaValue::Boolean(false)))
+// checked before buffers of the region's size are allocated
+fn check_array_region(env: &JniEnv, array_id: usize, offset: i64, length: usize) -> RuntimeResult<()> {
+    let array_length = env.get_array_length(array_id)?;
+    match (offset as usize).checked_add(length) {
+        Some(end) if offset >= 0 && end <= array_length => Ok(()),
+        _ => Err(env.throw_exception("java/lang/ArrayIndexOutOfBoundsException", Some(&offset.to_string()))),
     }
 }
 
-fn get_value_volatile(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let obj = &env.parameters[1];
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_setMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let offset = env.parameters[2].as_long().unwrap();
-    let current_field = get_value_at_offset(env, obj, offset);
-    Ok(Some(current_field))
-}
+    let length = get_memory_size(env, 4)? as usize;
+    let value = env.parameters[6].as_int().unwrap() as i8;
 
-macro_rules! define_cas {
-    ( $fname:ident, $type_size:literal ) => {
-        #[allow(non_snake_case)]
-        fn $fname(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-            cas(env, $type_size)
+    match env.parameters[1] {
+        JavaValue::Object(None) => {
+            let result = env.jvm.memory.borrow_mut().fill(offset as u64, length, value as u8);
+            result.map_err(|err| memory_error(env, err))
         }
-    };
+        JavaValue::Array(array_id) => {
+            check_array_region(env, array_id, offset, length)?;
+            env.set_byte_array_region(array_id, offset as usize, &vec![value; length])
+        }
+        _ => Err(env.throw_exception("java/lang/IllegalArgumentException", None)),
+    }?;
+    Ok(None)
 }
 
-define_cas!(Java_sun_misc_Unsafe_compareAndSwapObject, 1);
-define_cas!(Java_sun_misc_Unsafe_compareAndSwapInt, 1);
-define_cas!(Java_sun_misc_Unsafe_compareAndSwapLong, 2);
-
+// copies between off-heap memory and byte arrays, or element-wise between arrays of the same type
 #[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_getIntVolatile(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    get_value_volatile(env)
+fn Java_sun_misc_Unsafe_copyMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let source_offset = env.parameters[2].as_long().unwrap();
+    let destination_offset = env.parameters[5].as_long().unwrap();
+    let length = get_memory_size(env, 7)? as usize;
+
+    match (&env.parameters[1], &env.parameters[4]) {
+        (JavaValue::Object(None), JavaValue::Object(None)) => {
+            let result = env.jvm.memory.borrow_mut().copy(source_offset as u64, destination_offset as u64, length);
+            result.map_err(|err| memory_error(env, err))?;
+        }
+        (JavaValue::Object(None), JavaValue::Array(destination)) => {
+            check_array_region(env, *destination, destination_offset, length)?;
+            let mut buf = vec![0u8; length];
+            let result = env.jvm.memory.borrow_mut().read(source_offset as u64, &mut buf);
+            result.map_err(|err| memory_error(env, err))?;
+            let buf: Vec<i8> = buf.into_iter().map(|byte| byte as i8).collect();
+            env.set_byte_array_region(*destination, destination_offset as usize, &buf)?;
+        }
+        (JavaValue::Array(source), JavaValue::Object(None)) => {
+            check_array_region(env, *source, source_offset, length)?;
+            let mut buf = vec![0i8; length];
+            env.get_byte_array_region(*source, source_offset as usize, &mut buf)?;
+            let buf: Vec<u8> = buf.into_iter().map(|byte| byte as u8).collect();
+            let result = env.jvm.memory.borrow_mut().write(destination_offset as u64, &buf);
+            result.map_err(|err| memory_error(env, err))?;
+        }
+        (JavaValue::Array(source), JavaValue::Array(destination)) => {
+            check_array_region(env, *source, source_offset, length)?;
+            check_array_region(env, *destination, destination_offset, length)?;
+            let values = (0..length)
+                .map(|i| env.get_array_element(*source, source_offset as usize + i))
+                .collect::<RuntimeResult<Vec<JavaValue>>>()?;
+            for (i, value) in values.into_iter().enumerate() {
+                env.set_array_element(*destination, destination_offset as usize + i, value)?;
+            }
+        }
+        _ => return Err(env.throw_exception("java/lang/IllegalArgumentException", None)),
+    }
+    Ok(None)
 }
 
-#[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_getObjectVolatile(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    get_value_volatile(env)
+fn get_class_parameter(env: &JniEnv) -> RuntimeResult<usize> {
+    match env.parameters[1] {
+        JavaValue::Object(Some(class_object)) => env.get_numeric_metadata(class_object, "class_id"),
+        _ => Err(env.jvm.throw_npe()),
+    }
 }
 
+// creates an instance without running any constructor
 #[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_allocateMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let instance = env.get_current_instance()?;
-    let block_size = env.parameters[1].as_long().unwrap();
-    let mut buf: Vec<u8> = Vec::with_capacity(block_size as usize);
-    let ptr = buf.as_mut_ptr();
-
-    std::mem::forget(buf);
+fn Java_sun_misc_Unsafe_allocateInstance(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_class_parameter(env)?;
+    let is_instantiable = {
+        let heap = env.jvm.heap.borrow();
+        let class = &heap.loaded_classes[class_id];
+        !class.is_array_type
+            && !class.is_primitive_type
+            && !class.access_flags.intersects(ClassAccessFlags::ABSTRACT | ClassAccessFlags::INTERFACE)
+    };
+    if !is_instantiable {
+        let class_name = env.jvm.get_class_name_from_id(class_id).replace("/", ".");
+        return Err(env.throw_exception("java/lang/InstantiationException", Some(&class_name)));
+    }
 
-    env.set_internal_metadata(instance, &(ptr as i64).to_string(), InternalMetadata::Numeric(block_size as usize));
+    env.jvm.initialize_class(class_id)?;
+    Ok(Some(JavaValue::Object(Some(env.new_instance(class_id)?))))
+}
 
-    Ok(Some(JavaValue::Long(ptr as i64)))
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_ensureClassInitialized(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    env.jvm.initialize_class(get_class_parameter(env)?)?;
+    Ok(None)
 }
 
 #[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_freeMemory(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let instance = env.get_current_instance()?;
-    let address = env.parameters[1].as_long().unwrap() as *mut u8;
-    let block_size = env.remove_internal_metadata(instance, &(address as i64).to_string()).unwrap().into_usize();
+fn Java_sun_misc_Unsafe_shouldBeInitialized(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_id = get_class_parameter(env)?;
+    let heap = env.jvm.heap.borrow();
+    Ok(Some(JavaValue::Boolean(!heap.loaded_classes[class_id].is_initialized)))
+}
 
-    unsafe {
-        let data = Vec::from_raw_parts(address, block_size, block_size);
-        std::mem::drop(data);
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_throwException(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match env.parameters[1] {
+        JavaValue::Object(Some(exception)) => Err(env.jvm.throw_exception_ref(exception)),
+        _ => Err(env.jvm.throw_npe()),
     }
+}
 
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_fullFence(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     Ok(None)
 }
 
 #[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_getByte(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let address = env.parameters[1].as_long().unwrap() as *mut i8;
-    let value = unsafe { std::ptr::read(address) };
+fn Java_sun_misc_Unsafe_loadFence(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(None)
+}
 
-    Ok(Some(JavaValue::Byte(value)))
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_storeFence(_: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Ok(None)
 }
 
+// blocks until the permit is available, the thread is interrupted or the deadline passes, an absolute
+// time is in milliseconds since the epoch and a relative one in nanoseconds
 #[allow(non_snake_case)]
-fn Java_sun_misc_Unsafe_putLong(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
-    let address = env.parameters[1].as_long().unwrap() as *mut i64;
-    let value = env.parameters[3].as_long().unwrap();
+fn Java_sun_misc_Unsafe_park(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let is_absolute = env.parameters[1].as_boolean().unwrap();
+    let time = env.parameters[2].as_long().unwrap();
+
+    let mut threads = env.jvm.threads.borrow_mut();
+    let thread = threads.get_current_thread_mut();
+    if std::mem::replace(&mut thread.pending_park, false)
+        || std::mem::replace(&mut thread.park_permit, false)
+        || thread.interrupted
+    {
+        return Ok(None);
+    }
 
-    unsafe { std::ptr::write(address, value) };
+    let now = env.jvm.clock.borrow().current_time_millis();
+    let deadline = match (is_absolute, time) {
+        (false, 0) => None,
+        (false, nanos) if nanos > 0 => Some(now.saturating_add((nanos - 1) / 1_000_000 + 1)),
+        (true, millis) if millis > now => Some(millis),
+        _ => return Ok(None),
+    };
+    thread.pending_park = true;
+    thread.state = ThreadState::Parked(deadline.map(|deadline| deadline as u64));
+    Ok(None)
+}
 
+#[allow(non_snake_case)]
+fn Java_sun_misc_Unsafe_unpark(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    if let JavaValue::Object(Some(thread_object)) = env.parameters[1] {
+        let mut threads = env.jvm.threads.borrow_mut();
+        if let Some(thread_id) = threads.find_thread(thread_object) {
+            threads.unpark(thread_id);
+        }
+    }
     Ok(None)
 }
 
@@ -201,15 +625,72 @@ pub fn initialize(cp: &mut Classpath) {
         Java_sun_misc_Unsafe_arrayBaseOffset,
         Java_sun_misc_Unsafe_arrayIndexScale,
         Java_sun_misc_Unsafe_addressSize,
+        Java_sun_misc_Unsafe_pageSize,
         Java_sun_misc_Unsafe_objectFieldOffset,
+        Java_sun_misc_Unsafe_staticFieldOffset,
+        Java_sun_misc_Unsafe_staticFieldBase,
+        Java_sun_misc_Unsafe_getObject,
+        Java_sun_misc_Unsafe_getBoolean,
+        Java_sun_misc_Unsafe_getByte,
+        Java_sun_misc_Unsafe_getShort,
+        Java_sun_misc_Unsafe_getChar,
+        Java_sun_misc_Unsafe_getInt,
+        Java_sun_misc_Unsafe_getLong,
+        Java_sun_misc_Unsafe_getFloat,
+        Java_sun_misc_Unsafe_getDouble,
+        Java_sun_misc_Unsafe_getAddress,
+        Java_sun_misc_Unsafe_putObject,
+        Java_sun_misc_Unsafe_putBoolean,
+        Java_sun_misc_Unsafe_putByte,
+        Java_sun_misc_Unsafe_putShort,
+        Java_sun_misc_Unsafe_putChar,
+        Java_sun_misc_Unsafe_putInt,
+        Java_sun_misc_Unsafe_putLong,
+        Java_sun_misc_Unsafe_putFloat,
+        Java_sun_misc_Unsafe_putDouble,
+        Java_sun_misc_Unsafe_putAddress,
+        Java_sun_misc_Unsafe_getObjectVolatile,
+        Java_sun_misc_Unsafe_getBooleanVolatile,
+        Java_sun_misc_Unsafe_getByteVolatile,
+        Java_sun_misc_Unsafe_getShortVolatile,
+        Java_sun_misc_Unsafe_getCharVolatile,
+        Java_sun_misc_Unsafe_getIntVolatile,
+        Java_sun_misc_Unsafe_getLongVolatile,
+        Java_sun_misc_Unsafe_getFloatVolatile,
+        Java_sun_misc_Unsafe_getDoubleVolatile,
+        Java_sun_misc_Unsafe_putObjectVolatile,
+        Java_sun_misc_Unsafe_putBooleanVolatile,
+        Java_sun_misc_Unsafe_putByteVolatile,
+        Java_sun_misc_Unsafe_putShortVolatile,
+        Java_sun_misc_Unsafe_putCharVolatile,
+        Java_sun_misc_Unsafe_putIntVolatile,
+        Java_sun_misc_Unsafe_putLongVolatile,
+        Java_sun_misc_Unsafe_putFloatVolatile,
+        Java_sun_misc_Unsafe_putDoubleVolatile,
+        Java_sun_misc_Unsafe_putOrderedObject,
+        Java_sun_misc_Unsafe_putOrderedInt,
+        Java_sun_misc_Unsafe_putOrderedLong,
         Java_sun_misc_Unsafe_compareAndSwapObject,
         Java_sun_misc_Unsafe_compareAndSwapInt,
         Java_sun_misc_Unsafe_compareAndSwapLong,
-        Java_sun_misc_Unsafe_getIntVolatile,
-        Java_sun_misc_Unsafe_getObjectVolatile,
+        Java_sun_misc_Unsafe_getAndAddInt,
+        Java_sun_misc_Unsafe_getAndAddLong,
+        Java_sun_misc_Unsafe_getAndSetInt,
+        Java_sun_misc_Unsafe_getAndSetLong,
+        Java_sun_misc_Unsafe_getAndSetObject,
         Java_sun_misc_Unsafe_allocateMemory,
+        Java_sun_misc_Unsafe_reallocateMemory,
         Java_sun_misc_Unsafe_freeMemory,
-        Java_sun_misc_Unsafe_putLong,
-        Java_sun_misc_Unsafe_getByte
+        Java_sun_misc_Unsafe_setMemory,
+        Java_sun_misc_Unsafe_copyMemory,
+        Java_sun_misc_Unsafe_allocateInstance,
+        Java_sun_misc_Unsafe_ensureClassInitialized,
+        Java_sun_misc_Unsafe_shouldBeInitialized,
+        Java_sun_misc_Unsafe_throwException,
+        Java_sun_misc_Unsafe_fullFence,
+        Java_sun_misc_Unsafe_loadFence,
+        Java_sun_misc_Unsafe_storeFence,
+        Java_sun_misc_Unsafe_park,
+        Java_sun_misc_Unsafe_unpark
     );
 }
@@ -0,0 +1,154 @@
+use classfile_parser::{constant_info::ConstantInfo, ClassFile};
+
+use crate::{
+    exec::env::JniEnv,
+    model::{JavaArrayType, JavaValue, RuntimeResult},
+    util::{get_constant_name_and_type, get_constant_string},
+    Classpath,
+};
+
+// the constant pool oop is the class object of the class owning the constant pool
+fn get_class_file<'a>(env: &'a JniEnv) -> RuntimeResult<&'a ClassFile> {
+    let class_object = match env.parameters[1].as_object() {
+        Ok(Some(id)) => id,
+        _ => return Err(env.jvm.throw_npe()),
+    };
+    let class_id = env.get_numeric_metadata(class_object, "class_id")?;
+    env.get_class_file(class_id)
+}
+
+fn get_constant<'a>(env: &'a JniEnv) -> RuntimeResult<(&'a ClassFile, &'a ConstantInfo)> {
+    let class_file = get_class_file(env)?;
+    let index = env.parameters[2].as_int().unwrap();
+    match (index as usize).checked_sub(1).and_then(|index| class_file.const_pool.get(index)) {
+        Some(constant) => Ok((class_file, constant)),
+        _ => Err(env.throw_exception("java/lang/IllegalArgumentException", Some("Constant pool index out of bounds"))),
+    }
+}
+
+fn wrong_type(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    Err(env.throw_exception("java/lang/IllegalArgumentException", Some("Wrong type at constant pool index")))
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getSize0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let class_file = get_class_file(env)?;
+    Ok(Some(JavaValue::Int(class_file.const_pool.len() as i32 + 1)))
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getClassAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (class_file, constant) = get_constant(env)?;
+    match constant {
+        ConstantInfo::Class(class) => {
+            let class_id = env.load_class(get_constant_string(&class_file.const_pool, class.name_index), false)?;
+            Ok(Some(JavaValue::Object(Some(env.get_class_object(class_id)))))
+        }
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getClassAtIfLoaded0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (class_file, constant) = get_constant(env)?;
+    match constant {
+        ConstantInfo::Class(class) => {
+            let class_name = get_constant_string(&class_file.const_pool, class.name_index);
+            let class_id = {
+                let heap = env.jvm.heap.borrow();
+                heap.loaded_classes_lookup.get(class_name).cloned()
+            };
+            Ok(Some(JavaValue::Object(class_id.map(|class_id| env.get_class_object(class_id)))))
+        }
+        _ => wrong_type(env),
+    }
+}
+
+// returns the class name, member name and descriptor of a field or method reference
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getMemberRefInfoAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (class_file, constant) = get_constant(env)?;
+    let (class_index, name_and_type_index) = match constant {
+        ConstantInfo::FieldRef(field_ref) => (field_ref.class_index, field_ref.name_and_type_index),
+        ConstantInfo::MethodRef(method_ref) => (method_ref.class_index, method_ref.name_and_type_index),
+        ConstantInfo::InterfaceMethodRef(method_ref) => (method_ref.class_index, method_ref.name_and_type_index),
+        _ => return wrong_type(env),
+    };
+
+    let class_name = get_constant_string(&class_file.const_pool, class_index);
+    let (member_name, member_descriptor) = get_constant_name_and_type(&class_file.const_pool, name_and_type_index);
+    let result_array = env.new_array(JavaArrayType::Object(env.get_class_id("java/lang/String")?), 3);
+    for (i, str) in [class_name, member_name, member_descriptor].iter().enumerate() {
+        env.set_array_element(result_array, i, JavaValue::Object(Some(env.new_string(str)?)))?;
+    }
+
+    Ok(Some(JavaValue::Array(result_array)))
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getIntAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match get_constant(env)?.1 {
+        ConstantInfo::Integer(integer) => Ok(Some(JavaValue::Int(integer.value))),
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getLongAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match get_constant(env)?.1 {
+        ConstantInfo::Long(long) => Ok(Some(JavaValue::Long(long.value))),
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getFloatAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match get_constant(env)?.1 {
+        ConstantInfo::Float(float) => Ok(Some(JavaValue::Float(float.value))),
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getDoubleAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match get_constant(env)?.1 {
+        ConstantInfo::Double(double) => Ok(Some(JavaValue::Double(double.value))),
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getStringAt0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let (class_file, constant) = get_constant(env)?;
+    match constant {
+        ConstantInfo::String(str) => {
+            let value = get_constant_string(&class_file.const_pool, str.string_index);
+            Ok(Some(JavaValue::Object(Some(env.new_interned_string(value)?))))
+        }
+        _ => wrong_type(env),
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_ConstantPool_getUTF8At0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    match get_constant(env)?.1 {
+        ConstantInfo::Utf8(utf8) => Ok(Some(JavaValue::Object(Some(env.new_string(&utf8.utf8_string)?)))),
+        _ => wrong_type(env),
+    }
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(
+        cp,
+        Java_sun_reflect_ConstantPool_getSize0,
+        Java_sun_reflect_ConstantPool_getClassAt0,
+        Java_sun_reflect_ConstantPool_getClassAtIfLoaded0,
+        Java_sun_reflect_ConstantPool_getMemberRefInfoAt0,
+        Java_sun_reflect_ConstantPool_getIntAt0,
+        Java_sun_reflect_ConstantPool_getLongAt0,
+        Java_sun_reflect_ConstantPool_getFloatAt0,
+        Java_sun_reflect_ConstantPool_getDoubleAt0,
+        Java_sun_reflect_ConstantPool_getStringAt0,
+        Java_sun_reflect_ConstantPool_getUTF8At0
+    );
+}
@@ -1,30 +1,23 @@
+use super::{
+    java_lang_Class::get_reflected_method,
+    sun_reflect_NativeMethodAccessorImpl::{get_arguments, wrap_invocation_exception},
+};
 use crate::{
-    model::{JavaValue, RuntimeResult},
+    model::{JavaValue, MethodDescriptor, RuntimeResult},
+    util::get_constant_string,
     Classpath, InvokeType, JniEnv,
 };
 
 #[allow(non_snake_case)]
 fn Java_sun_reflect_NativeConstructorAccessorImpl_newInstance0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let constructor = env.parameters[0].as_object().unwrap().unwrap();
-    let args = env.parameters[1].as_array();
-
-    let constructor_declaring_class_obj = env.get_field(constructor, "clazz").as_object().unwrap().unwrap();
-    let constructor_declaring_class_id =
-        env.get_internal_metadata(constructor_declaring_class_obj, "class_id").unwrap().into_usize();
-    let constructor_descriptor = "()V";
+    let (constructor_declaring_class_id, class_file, method_info) = get_reflected_method(env, constructor)?;
+    let constructor_descriptor = get_constant_string(&class_file.const_pool, method_info.descriptor_index);
 
-    let params = match args {
-        Ok(args) => {
-            let args_len = env.get_array_length(args);
-            let mut params = Vec::with_capacity(args_len);
-            for i in 0..args_len {
-                params.push(env.get_array_element(args, i));
-            }
-            params
-        }
-        Err(_) => Vec::new(),
-    };
+    let descriptor = MethodDescriptor::new(constructor_descriptor).unwrap();
+    let params = get_arguments(env, &env.parameters[1], &descriptor)?;
 
+    env.jvm.initialize_class(constructor_declaring_class_id)?;
     let new_instance = env.new_instance(constructor_declaring_class_id)?;
     env.invoke_instance_method(
         InvokeType::Special,
@@ -33,7 +26,8 @@ fn Java_sun_reflect_NativeConstructorAccessorImpl_newInstance0(env: &JniEnv) ->
         "<init>",
         constructor_descriptor,
         &params,
-    )?;
+    )
+    .map_err(|err| wrap_invocation_exception(env, err))?;
 
     Ok(Some(JavaValue::Object(Some(new_instance))))
 }
@@ -0,0 +1,184 @@
+use classfile_parser::method_info::MethodAccessFlags;
+
+use super::java_lang_Class::get_reflected_method;
+use crate::{
+    model::{JavaThrowable, JavaValue, MethodDescriptor, RuntimeResult},
+    util::{get_constant_string, get_descriptor_class_name},
+    Classpath, InvokeType, JniEnv,
+};
+
+const BOX_CLASSES: &[(&str, &str)] = &[
+    ("Z", "java/lang/Boolean"),
+    ("B", "java/lang/Byte"),
+    ("S", "java/lang/Short"),
+    ("C", "java/lang/Character"),
+    ("I", "java/lang/Integer"),
+    ("J", "java/lang/Long"),
+    ("F", "java/lang/Float"),
+    ("D", "java/lang/Double"),
+];
+
+fn get_box_class(primitive_type: &str) -> Option<&'static str> {
+    BOX_CLASSES.iter().find(|(primitive, _)| *primitive == primitive_type).map(|(_, box_class)| *box_class)
+}
+
+fn get_primitive_type(box_class: &str) -> Option<&'static str> {
+    BOX_CLASSES.iter().find(|(_, class)| *class == box_class).map(|(primitive, _)| *primitive)
+}
+
+// applies the identity or widening primitive conversion allowed for reflective arguments
+fn widen_primitive(value: &JavaValue, from: &str, to: &str) -> Option<JavaValue> {
+    let is_widening = from == to
+        || match from {
+            "B" => matches!(to, "S" | "I" | "J" | "F" | "D"),
+            "S" | "C" => matches!(to, "I" | "J" | "F" | "D"),
+            "I" => matches!(to, "J" | "F" | "D"),
+            "J" => matches!(to, "F" | "D"),
+            "F" => to == "D",
+            _ => false,
+        };
+    if !is_widening {
+        return None;
+    }
+
+    Some(match (from, to) {
+        ("J", "J") => JavaValue::Long(value.as_long().ok()?),
+        ("J", "F") => JavaValue::Float(value.as_long().ok()? as f32),
+        ("J", "D") => JavaValue::Double(value.as_long().ok()? as f64),
+        ("F", "F") => JavaValue::Float(value.as_float().ok()?),
+        ("F", "D") => JavaValue::Double(value.as_float().ok()? as f64),
+        ("D", "D") => JavaValue::Double(value.as_double().ok()?),
+        (_, "J") => JavaValue::Long(value.as_int().ok()? as i64),
+        (_, "F") => JavaValue::Float(value.as_int().ok()? as f32),
+        (_, "D") => JavaValue::Double(value.as_int().ok()? as f64),
+        _ => JavaValue::Int(value.as_int().ok()?),
+    })
+}
+
+fn unbox_argument(env: &JniEnv, value: JavaValue, java_type: &str) -> RuntimeResult<JavaValue> {
+    let argument_type_mismatch =
+        || env.throw_exception("java/lang/IllegalArgumentException", Some("argument type mismatch"));
+
+    if get_box_class(java_type).is_none() {
+        return match env.jvm.is_instance_of(&value, get_descriptor_class_name(java_type), true)? {
+            true => Ok(value),
+            false => Err(argument_type_mismatch()),
+        };
+    }
+
+    let id = match value {
+        JavaValue::Object(Some(id)) => id,
+        _ => return Err(argument_type_mismatch()),
+    };
+    let boxed_type = match get_primitive_type(&env.get_object_type_name(id)?) {
+        Some(boxed_type) => boxed_type,
+        None => return Err(argument_type_mismatch()),
+    };
+    match widen_primitive(&env.get_field(id, "value")?, boxed_type, java_type) {
+        Some(value) => Ok(value),
+        None => Err(argument_type_mismatch()),
+    }
+}
+
+pub fn get_arguments(env: &JniEnv, args: &JavaValue, descriptor: &MethodDescriptor) -> RuntimeResult<Vec<JavaValue>> {
+    let mut values = Vec::new();
+    if let JavaValue::Array(args) = args {
+        for i in 0..env.get_array_length(*args)? {
+            values.push(env.get_array_element(*args, i)?);
+        }
+    }
+    if values.len() != descriptor.argument_types.len() {
+        return Err(env.throw_exception("java/lang/IllegalArgumentException", Some("wrong number of arguments")));
+    }
+
+    values
+        .into_iter()
+        .zip(&descriptor.argument_types)
+        .map(|(value, java_type)| unbox_argument(env, value, java_type))
+        .collect()
+}
+
+fn box_return_value(env: &JniEnv, value: Option<JavaValue>, java_type: &str) -> RuntimeResult<JavaValue> {
+    if java_type == "V" {
+        return Ok(JavaValue::Object(None));
+    }
+
+    let value = value.unwrap_or(JavaValue::Object(None));
+    match get_box_class(java_type) {
+        Some(box_class) => {
+            let class_id = env.get_class_id(box_class)?;
+            let descriptor = format!("({})L{};", java_type, box_class);
+            Ok(env.invoke_static_method(class_id, "valueOf", &descriptor, &[value])?.unwrap_or(JavaValue::Object(None)))
+        }
+        None => Ok(value),
+    }
+}
+
+// exceptions thrown by the invoked method reach the caller wrapped in an InvocationTargetException
+pub fn wrap_invocation_exception(env: &JniEnv, err: JavaThrowable) -> JavaThrowable {
+    let target = match err {
+        JavaThrowable::Unhandled(target) => target,
+        other => return other,
+    };
+
+    let wrapped = env.get_class_id("java/lang/reflect/InvocationTargetException").and_then(|class_id| {
+        let exception = env.new_instance(class_id)?;
+        env.invoke_instance_method(
+            InvokeType::Special,
+            exception,
+            class_id,
+            "<init>",
+            "(Ljava/lang/Throwable;)V",
+            &[JavaValue::Object(Some(target))],
+        )?;
+        Ok(exception)
+    });
+    match wrapped {
+        Ok(exception) => env.jvm.throw_exception_ref(exception),
+        Err(err) => err,
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_sun_reflect_NativeMethodAccessorImpl_invoke0(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let method = env.parameters[0].as_object().unwrap().unwrap();
+    let (class_id, class_file, method_info) = get_reflected_method(env, method)?;
+    let method_name = get_constant_string(&class_file.const_pool, method_info.name_index);
+    let method_descriptor = get_constant_string(&class_file.const_pool, method_info.descriptor_index);
+    let descriptor = MethodDescriptor::new(method_descriptor).unwrap();
+
+    let result = if method_info.access_flags.contains(MethodAccessFlags::STATIC) {
+        env.jvm.initialize_class(class_id)?;
+        let params = get_arguments(env, &env.parameters[2], &descriptor)?;
+        env.invoke_static_method(class_id, method_name, method_descriptor, &params)
+    } else {
+        let instance = match env.parameters[1] {
+            JavaValue::Object(Some(id)) | JavaValue::Array(id) => id,
+            _ => return Err(env.jvm.throw_npe()),
+        };
+        let class_name = env.jvm.get_class_name_from_id(class_id);
+        if !env.jvm.is_instance_of(&env.parameters[1], &class_name, false)? {
+            return Err(env.throw_exception(
+                "java/lang/IllegalArgumentException",
+                Some("object is not an instance of declaring class"),
+            ));
+        }
+        let params = get_arguments(env, &env.parameters[2], &descriptor)?;
+
+        // private methods are not overridden, everything else is dispatched on the receiver
+        let invoke_type = match method_info.access_flags.contains(MethodAccessFlags::PRIVATE) {
+            true => InvokeType::Special,
+            false => InvokeType::Virtual,
+        };
+        env.invoke_instance_method(invoke_type, instance, class_id, method_name, method_descriptor, &params)
+    };
+
+    match result {
+        Ok(value) => Ok(Some(box_return_value(env, value, &descriptor.return_type)?)),
+        Err(err) => Err(wrap_invocation_exception(env, err)),
+    }
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(cp, Java_sun_reflect_NativeMethodAccessorImpl_invoke0);
+}
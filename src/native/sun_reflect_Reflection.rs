@@ -18,8 +18,8 @@ fn Java_sun_reflect_Reflection_getCallerClass(env: &JniEnv) -> RuntimeResult<Opt
 #[allow(non_snake_case)]
 fn Java_sun_reflect_Reflection_getClassAccessFlags(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
     let class_obj = env.parameters[0].as_object().unwrap().unwrap();
-    let class_id = env.get_internal_metadata(class_obj, "class_id").unwrap().into_usize();
-    let class_file = env.get_class_file(class_id);
+    let class_id = env.get_numeric_metadata(class_obj, "class_id")?;
+    let class_file = env.get_class_file(class_id)?;
     Ok(Some(JavaValue::Int(class_file.access_flags.bits() as i32 & 0x1FFF)))
 }
 
@@ -0,0 +1,182 @@
+use std::convert::TryFrom;
+
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{
+    exec::env::JniEnv,
+    js,
+    model::{JavaValue, RuntimeResult},
+    Classpath,
+};
+
+fn get_this(env: &JniEnv) -> RuntimeResult<JsValue> {
+    match js::get_js_object(env, env.get_current_instance()?)? {
+        Some(value) => Ok(value),
+        None => {
+            Err(env.throw_exception("webjvm/js/JSException", Some("JSObject does not reference a JavaScript object")))
+        }
+    }
+}
+
+fn get_string_parameter(env: &JniEnv, index: usize) -> RuntimeResult<String> {
+    match env.parameters[index].as_object() {
+        Ok(Some(id)) => env.get_string(id),
+        _ => Err(env.throw_exception("java/lang/NullPointerException", None)),
+    }
+}
+
+fn check_js_result<T>(env: &JniEnv, result: Result<T, JsValue>) -> RuntimeResult<T> {
+    result.map_err(|err| env.throw_exception("webjvm/js/JSException", Some(&js::get_error_message(&err))))
+}
+
+// boxed primitives and strings are passed by value, any other Java object as an opaque handle
+fn to_js(env: &JniEnv, value: &JavaValue) -> RuntimeResult<JsValue> {
+    let id = match value {
+        JavaValue::Object(Some(id)) => *id,
+        value => return js::to_js_value(env, value, "Ljava/lang/Object;"),
+    };
+    if let Some(value) = js::get_js_object(env, id)? {
+        return Ok(value);
+    }
+
+    match env.get_object_type_name(id)?.as_str() {
+        "java/lang/String" => Ok(JsValue::from(env.get_string(id)?)),
+        "java/lang/Boolean"
+        | "java/lang/Byte"
+        | "java/lang/Short"
+        | "java/lang/Character"
+        | "java/lang/Integer"
+        | "java/lang/Long"
+        | "java/lang/Float"
+        | "java/lang/Double" => js::to_js_value(env, &env.get_field(id, "value")?, ""),
+        _ => Ok(js::to_js_handle(env, value)),
+    }
+}
+
+fn box_primitive(env: &JniEnv, class_name: &str, descriptor: &str, value: JavaValue) -> RuntimeResult<JavaValue> {
+    let class_id = env.get_class_id(class_name)?;
+    Ok(env.invoke_static_method(class_id, "valueOf", descriptor, &[value])?.unwrap_or(JavaValue::Object(None)))
+}
+
+fn from_js(env: &JniEnv, value: JsValue) -> RuntimeResult<JavaValue> {
+    if value.is_null() || value.is_undefined() {
+        Ok(JavaValue::Object(None))
+    } else if let Some(str) = value.as_string() {
+        Ok(JavaValue::Object(Some(env.new_string(&str)?)))
+    } else if let Some(bool) = value.as_bool() {
+        box_primitive(env, "java/lang/Boolean", "(Z)Ljava/lang/Boolean;", JavaValue::Boolean(bool))
+    } else if let Some(double) = value.as_f64() {
+        box_primitive(env, "java/lang/Double", "(D)Ljava/lang/Double;", JavaValue::Double(double))
+    } else if value.is_bigint() {
+        match i64::try_from(value) {
+            Ok(long) => box_primitive(env, "java/lang/Long", "(J)Ljava/lang/Long;", JavaValue::Long(long)),
+            Err(_) => Err(env.throw_exception("webjvm/js/JSException", Some("BigInt does not fit into a long"))),
+        }
+    } else {
+        Ok(JavaValue::Object(Some(js::wrap_js_object(env, value)?)))
+    }
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_getWindow(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let window = js::wrap_js_object(env, js_sys::global().into())?;
+    Ok(Some(JavaValue::Object(Some(window))))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_getMember(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let name = get_string_parameter(env, 1)?;
+    let value = check_js_result(env, js_sys::Reflect::get(&this, &JsValue::from(name)))?;
+    Ok(Some(from_js(env, value)?))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_setMember(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let name = get_string_parameter(env, 1)?;
+    let value = to_js(env, &env.parameters[2])?;
+    check_js_result(env, js_sys::Reflect::set(&this, &JsValue::from(name), &value))?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_removeMember(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let name = get_string_parameter(env, 1)?;
+    check_js_result(env, js_sys::Reflect::delete_property(this.unchecked_ref(), &JsValue::from(name)))?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_getSlot(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let index = env.parameters[1].as_int().unwrap();
+    let value = check_js_result(env, js_sys::Reflect::get(&this, &JsValue::from(index)))?;
+    Ok(Some(from_js(env, value)?))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_setSlot(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let index = env.parameters[1].as_int().unwrap();
+    let value = to_js(env, &env.parameters[2])?;
+    check_js_result(env, js_sys::Reflect::set(&this, &JsValue::from(index), &value))?;
+    Ok(None)
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_call(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let name = get_string_parameter(env, 1)?;
+    let function = match check_js_result(env, js_sys::Reflect::get(&this, &JsValue::from(name.as_str())))?
+        .dyn_into::<js_sys::Function>()
+    {
+        Ok(function) => function,
+        Err(_) => {
+            return Err(env.throw_exception("webjvm/js/JSException", Some(&format!("{} is not a function", name))))
+        }
+    };
+
+    let arguments = js_sys::Array::new();
+    if let JavaValue::Array(array_id) = env.parameters[2] {
+        for i in 0..env.get_array_length(array_id)? {
+            arguments.push(&to_js(env, &env.get_array_element(array_id, i)?)?);
+        }
+    }
+
+    let value = check_js_result(env, function.apply(&this, &arguments))?;
+    Ok(Some(from_js(env, value)?))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_eval(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let script = get_string_parameter(env, 1)?;
+    let value = check_js_result(env, js_sys::eval(&script))?;
+    Ok(Some(from_js(env, value)?))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_js_JSObject_toString(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let this = get_this(env)?;
+    let str = match this.as_string() {
+        Some(str) => str,
+        None => String::from(this.unchecked_ref::<js_sys::Object>().to_string()),
+    };
+    Ok(Some(JavaValue::Object(Some(env.new_string(&str)?))))
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(
+        cp,
+        Java_webjvm_js_JSObject_getWindow,
+        Java_webjvm_js_JSObject_getMember,
+        Java_webjvm_js_JSObject_setMember,
+        Java_webjvm_js_JSObject_removeMember,
+        Java_webjvm_js_JSObject_getSlot,
+        Java_webjvm_js_JSObject_setSlot,
+        Java_webjvm_js_JSObject_call,
+        Java_webjvm_js_JSObject_eval,
+        Java_webjvm_js_JSObject_toString
+    );
+}
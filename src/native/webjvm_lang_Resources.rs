@@ -0,0 +1,39 @@
+use crate::{
+    exec::env::JniEnv,
+    model::{JavaArrayType, JavaValue, RuntimeResult},
+    Classpath,
+};
+
+#[allow(non_snake_case)]
+fn Java_webjvm_lang_Resources_getResourceCount(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let name = match env.parameters[0].as_object().unwrap() {
+        Some(id) => env.get_string(id)?,
+        None => return Err(env.throw_exception("java/lang/NullPointerException", None)),
+    };
+    let count = env.jvm.classpath.get_classpath_resources(&name).len();
+
+    Ok(Some(JavaValue::Int(count as i32)))
+}
+
+#[allow(non_snake_case)]
+fn Java_webjvm_lang_Resources_getResourceBytes(env: &JniEnv) -> RuntimeResult<Option<JavaValue>> {
+    let name = match env.parameters[0].as_object().unwrap() {
+        Some(id) => env.get_string(id)?,
+        None => return Err(env.throw_exception("java/lang/NullPointerException", None)),
+    };
+    let index = env.parameters[1].as_int().unwrap();
+
+    let resources = env.jvm.classpath.get_classpath_resources(&name);
+    match resources.get(index as usize) {
+        Some(bytes) if index >= 0 => {
+            let values = bytes.iter().map(|byte| JavaValue::Byte(*byte as i8)).collect();
+            let array_id = env.jvm.create_constant_array(JavaArrayType::Byte, values);
+            Ok(Some(JavaValue::Array(array_id)))
+        }
+        _ => Ok(Some(JavaValue::Object(None))),
+    }
+}
+
+pub fn initialize(cp: &mut Classpath) {
+    register_jni!(cp, Java_webjvm_lang_Resources_getResourceCount, Java_webjvm_lang_Resources_getResourceBytes);
+}
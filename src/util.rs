@@ -1,4 +1,4 @@
-use classfile_parser::constant_info::ConstantInfo;
+use classfile_parser::{attribute_info::AttributeInfo, constant_info::ConstantInfo, ClassFile};
 
 pub static mut PERMIT_LOGGING: bool = false;
 
@@ -23,27 +23,208 @@ pub fn log_error(str: &str) {
     }
 }
 
+pub fn default_output_sink(is_error: bool) -> Box<dyn std::io::Write> {
+    #[cfg(target_arch = "wasm32")]
+    return Box::new(crate::js::ConsoleSink::new(is_error));
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if is_error {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(std::io::stdout())
+        }
+    }
+}
+
+pub fn current_time_millis() -> i64 {
+    #[cfg(target_arch = "wasm32")]
+    return js_sys::Date::now() as i64;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
+    }
+}
+
+pub fn get_constant(const_pool: &[ConstantInfo], constant_index: u16) -> Option<&ConstantInfo> {
+    const_pool.get((constant_index as usize).checked_sub(1)?)
+}
+
+fn get_utf8_constant(const_pool: &[ConstantInfo], constant_index: u16) -> Option<&String> {
+    match get_constant(const_pool, constant_index)? {
+        ConstantInfo::Utf8(str) => Some(&str.utf8_string),
+        _ => None,
+    }
+}
+
+// resolves the string of a utf8, class or string constant, indices taken from bytecode have to be resolved with this
+pub fn find_constant_string(const_pool: &[ConstantInfo], constant_index: u16) -> Option<&String> {
+    match get_constant(const_pool, constant_index)? {
+        ConstantInfo::Utf8(str) => Some(&str.utf8_string),
+        ConstantInfo::Class(cls) => get_utf8_constant(const_pool, cls.name_index),
+        ConstantInfo::String(str) => get_utf8_constant(const_pool, str.string_index),
+        _ => None,
+    }
+}
+
+pub fn get_class_constant_name(const_pool: &[ConstantInfo], class_index: u16) -> Option<&String> {
+    match get_constant(const_pool, class_index)? {
+        ConstantInfo::Class(cls) => get_utf8_constant(const_pool, cls.name_index),
+        _ => None,
+    }
+}
+
+pub fn find_constant_name_and_type(
+    const_pool: &[ConstantInfo],
+    name_and_type_index: u16,
+) -> Option<(&String, &String)> {
+    match get_constant(const_pool, name_and_type_index)? {
+        ConstantInfo::NameAndType(nat) => {
+            Some((get_utf8_constant(const_pool, nat.name_index)?, get_utf8_constant(const_pool, nat.descriptor_index)?))
+        }
+        _ => None,
+    }
+}
+
+// the indices stored in the structures of a class file are checked by validate_class_file when the class is
+// added to the classpath, so resolving them cannot fail
 pub fn get_constant_string(const_pool: &[ConstantInfo], constant_index: u16) -> &String {
-    match &const_pool[constant_index as usize - 1] {
-        ConstantInfo::Utf8(str) => &str.utf8_string,
-        ConstantInfo::Class(cls) => get_constant_string(const_pool, cls.name_index),
-        ConstantInfo::String(str) => get_constant_string(const_pool, str.string_index),
-        x => panic!("no string defined for constant info: {:?} with constant index: {}", x, constant_index),
+    match find_constant_string(const_pool, constant_index) {
+        Some(str) => str,
+        None => panic!("no string defined for constant index: {}", constant_index),
     }
 }
 
 pub fn get_constant_name_and_type(const_pool: &[ConstantInfo], name_and_type_index: u16) -> (&String, &String) {
-    match &const_pool[name_and_type_index as usize - 1] {
-        ConstantInfo::NameAndType(nat) => (
-            match &const_pool[nat.name_index as usize - 1] {
-                ConstantInfo::Utf8(str) => &str.utf8_string,
-                x => panic!("bad name: {:?}", x),
-            },
-            match &const_pool[nat.descriptor_index as usize - 1] {
-                ConstantInfo::Utf8(str) => &str.utf8_string,
-                x => panic!("bad descriptors: {:?}", x),
-            },
-        ),
-        x => panic!("bad name and type: {:?}", x),
+    match find_constant_name_and_type(const_pool, name_and_type_index) {
+        Some(name_and_type) => name_and_type,
+        None => panic!("no name and type defined for constant index: {}", name_and_type_index),
+    }
+}
+
+// checks that the constant pool entries and the class, field, method and attribute structures only
+// reference constants of the expected kind
+pub fn validate_class_file(cls: &ClassFile) -> Result<(), String> {
+    let const_pool = &cls.const_pool;
+    let is_class = |index: u16| matches!(get_constant(const_pool, index), Some(ConstantInfo::Class(_)));
+    let is_utf8 = |index: u16| get_utf8_constant(const_pool, index).is_some();
+    let is_name_and_type = |index: u16| find_constant_name_and_type(const_pool, index).is_some();
+
+    for (i, constant) in const_pool.iter().enumerate() {
+        let is_valid = match constant {
+            ConstantInfo::Class(cls) => is_utf8(cls.name_index),
+            ConstantInfo::String(str) => is_utf8(str.string_index),
+            ConstantInfo::NameAndType(nat) => is_utf8(nat.name_index) && is_utf8(nat.descriptor_index),
+            ConstantInfo::FieldRef(field_ref) => {
+                is_class(field_ref.class_index) && is_name_and_type(field_ref.name_and_type_index)
+            }
+            ConstantInfo::MethodRef(method_ref) => {
+                is_class(method_ref.class_index) && is_name_and_type(method_ref.name_and_type_index)
+            }
+            ConstantInfo::InterfaceMethodRef(method_ref) => {
+                is_class(method_ref.class_index) && is_name_and_type(method_ref.name_and_type_index)
+            }
+            _ => true,
+        };
+        if !is_valid {
+            return Err(format!("invalid constant at index {}", i + 1));
+        }
+    }
+
+    if !is_class(cls.this_class) {
+        return Err(String::from("invalid this_class index"));
+    }
+    if cls.super_class != 0 && !is_class(cls.super_class) {
+        return Err(String::from("invalid super_class index"));
+    }
+    if !cls.interfaces.iter().all(|index| is_class(*index)) {
+        return Err(String::from("invalid interface index"));
+    }
+
+    let are_attributes_valid =
+        |attributes: &[AttributeInfo]| attributes.iter().all(|attribute| is_utf8(attribute.attribute_name_index));
+    let are_members_valid = cls.fields.iter().all(|field| {
+        is_utf8(field.name_index) && is_utf8(field.descriptor_index) && are_attributes_valid(&field.attributes)
+    }) && cls.methods.iter().all(|method| {
+        is_utf8(method.name_index) && is_utf8(method.descriptor_index) && are_attributes_valid(&method.attributes)
+    });
+    if !are_members_valid || !are_attributes_valid(&cls.attributes) {
+        return Err(String::from("invalid field, method or attribute"));
+    }
+
+    Ok(())
+}
+
+pub fn get_attribute<'a>(
+    const_pool: &[ConstantInfo],
+    attributes: &'a [AttributeInfo],
+    attribute_name: &str,
+) -> Option<&'a AttributeInfo> {
+    attributes
+        .iter()
+        .find(|attribute| get_constant_string(const_pool, attribute.attribute_name_index) == attribute_name)
+}
+
+// the class name a field or method descriptor type refers to, arrays and primitives are named by their descriptor
+pub fn get_descriptor_class_name(descriptor: &str) -> &str {
+    match descriptor.strip_prefix('L').and_then(|name| name.strip_suffix(';')) {
+        Some(name) => name,
+        None => descriptor,
+    }
+}
+
+// escapes a class name, method name or argument signature following the JNI spec
+pub fn jni_mangle(str: &str) -> String {
+    let mut mangled = String::with_capacity(str.len());
+    for ch in str.chars() {
+        match ch {
+            '/' | '.' => mangled.push('_'),
+            '_' => mangled.push_str("_1"),
+            ';' => mangled.push_str("_2"),
+            '[' => mangled.push_str("_3"),
+            'a'..='z' | 'A'..='Z' | '0'..='9' => mangled.push(ch),
+            _ => {
+                let mut units = [0; 2];
+                for unit in ch.encode_utf16(&mut units) {
+                    mangled.push_str(&format!("_0{:04x}", unit));
+                }
+            }
+        }
+    }
+    mangled
+}
+
+pub fn get_jni_short_name(class_name: &str, method_name: &str) -> String {
+    format!("Java_{}_{}", jni_mangle(class_name), jni_mangle(method_name))
+}
+
+pub fn get_jni_long_name(class_name: &str, method_name: &str, method_descriptor: &str) -> String {
+    let arguments = match (method_descriptor.find('('), method_descriptor.find(')')) {
+        (Some(start), Some(end)) if start < end => &method_descriptor[start + 1..end],
+        _ => "",
+    };
+    format!("{}__{}", get_jni_short_name(class_name, method_name), jni_mangle(arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_jni_long_name, get_jni_short_name, jni_mangle};
+
+    #[test]
+    fn mangles_jni_names() {
+        assert_eq!(jni_mangle("java/lang/Object"), "java_lang_Object");
+        assert_eq!(jni_mangle("my_method"), "my_1method");
+        assert_eq!(jni_mangle("[Ljava/lang/String;"), "_3Ljava_lang_String_2");
+        assert_eq!(jni_mangle("Outer$Inner"), "Outer_00024Inner");
+        assert_eq!(jni_mangle("\u{e9}\u{1f600}"), "_000e9_0d83d_0de00");
+
+        assert_eq!(get_jni_short_name("java/lang/Object", "hashCode"), "Java_java_lang_Object_hashCode");
+        assert_eq!(
+            get_jni_long_name("pkg/My_Class", "run", "(I[JLjava/lang/String;)V"),
+            "Java_pkg_My_1Class_run__I_3JLjava_lang_String_2"
+        );
+        assert_eq!(get_jni_long_name("Test", "run", "()V"), "Java_Test_run__");
     }
 }
@@ -10,5 +10,45 @@ public class MainTest {
         random.nextBytes(nonce);
 
         System.out.println(Arrays.toString(nonce));
+
+        checkParseDouble("0.1", 0x3fb999999999999aL);
+        checkParseDouble("-0.0", 0x8000000000000000L);
+        checkParseDouble("1e23", 0x44b52d02c7e14af6L);
+        checkParseDouble("2.2250738585072014E-308", 0x0010000000000000L);
+        checkParseDouble("4.9e-324", 0x0000000000000001L);
+        checkParseDouble("1e400", 0x7ff0000000000000L);
+        checkParseDouble("NaN", 0x7ff8000000000000L);
+        checkParseFloat("0.1", 0x3dcccccd);
+        checkParseFloat("3.4028235e38", 0x7f7fffff);
+        checkParseFloat("16777217", 0x4b800000);
+
+        checkCopyOf2D();
+    }
+
+    private static void checkCopyOf2D() {
+        String[][] grid = { { "a", "b" }, { "c" } };
+        String[][] copy = Arrays.copyOf(grid, 3);
+        if (copy.getClass() != String[][].class || copy[1] != grid[1] || copy[2] != null) {
+            throw new AssertionError("Arrays.copyOf(String[][], 3) returned " + Arrays.deepToString(copy));
+        }
+        int[][] matrix = (int[][]) java.lang.reflect.Array.newInstance(int[].class, 2);
+        matrix[0] = new int[] { 1 };
+        if (matrix.getClass() != int[][].class || matrix[0][0] != 1) {
+            throw new AssertionError("Array.newInstance(int[].class, 2) returned " + matrix.getClass());
+        }
+    }
+
+    private static void checkParseDouble(String str, long expected) {
+        long bits = Double.doubleToRawLongBits(Double.parseDouble(str));
+        if (bits != expected) {
+            throw new AssertionError("Double.parseDouble(\"" + str + "\") returned " + Long.toHexString(bits));
+        }
+    }
+
+    private static void checkParseFloat(String str, int expected) {
+        int bits = Float.floatToRawIntBits(Float.parseFloat(str));
+        if (bits != expected) {
+            throw new AssertionError("Float.parseFloat(\"" + str + "\") returned " + Integer.toHexString(bits));
+        }
     }
 }